@@ -25,6 +25,8 @@ pub struct ErrorResponse {
 pub enum ApiError {
     /// Resource not found
     NotFound(String),
+    /// Invalid request parameters
+    BadRequest(String),
     /// Database error
     Database(String),
     /// GitHub API rate limited
@@ -46,6 +48,14 @@ impl IntoResponse for ApiError {
                     retry_after_secs: None,
                 },
             ),
+            ApiError::BadRequest(msg) => (
+                StatusCode::BAD_REQUEST,
+                ErrorResponse {
+                    error: msg,
+                    code: Some("bad_request".to_string()),
+                    retry_after_secs: None,
+                },
+            ),
             ApiError::Database(msg) => {
                 error!("Database error: {}", msg);
                 (
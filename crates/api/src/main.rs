@@ -44,8 +44,8 @@ async fn main() -> anyhow::Result<()> {
     if config.sync_interval_hours > 0 {
         let sync_config = SyncConfig {
             interval: Duration::from_secs(config.sync_interval_hours as u64 * 60 * 60),
-            max_age_days: 365,
             github_token: config.github_token.clone(),
+            ..Default::default()
         };
         let sync_service = SyncService::new(pool.clone(), sync_config);
         tokio::spawn(async move {
@@ -66,15 +66,51 @@ async fn main() -> anyhow::Result<()> {
     let api_router = Router::new()
         .route("/health", get(routes::health::health))
         .route("/api/repos", get(routes::repos::list))
-        .route("/api/repos/:owner/:name", get(routes::repos::get))
+        .route(
+            "/api/repos/:owner/:name",
+            get(routes::repos::get).delete(routes::repos::untrack),
+        )
+        .route(
+            "/api/repos/:owner/:name/settings",
+            get(routes::repos::get_settings).put(routes::repos::update_settings),
+        )
+        .route(
+            "/api/repos/:owner/:name/scoring-profile",
+            get(routes::scoring::get_for_repo).put(routes::scoring::assign_to_repo),
+        )
         .route(
             "/api/repos/:owner/:name/open-prs",
             get(routes::repos::open_prs),
         )
+        .route(
+            "/api/repos/:owner/:name/experts",
+            get(routes::repos::experts),
+        )
+        .route(
+            "/api/repos/:owner/:name/workload",
+            get(routes::repos::workload),
+        )
+        .route("/api/repos/:owner/:name/stale", get(routes::repos::stale))
+        .route(
+            "/api/repos/:owner/:name/analytics",
+            get(routes::repos::analytics),
+        )
+        .route(
+            "/api/repos/:owner/:name/coverage",
+            get(routes::repos::coverage),
+        )
+        .route(
+            "/api/repos/:owner/:name/prs/:number/threads",
+            get(routes::repos::pr_threads),
+        )
         .route(
             "/api/repos/:owner/:name/leaderboard",
             get(routes::leaderboard::get),
         )
+        .route(
+            "/api/repos/:owner/:name/leaderboard/responsiveness",
+            get(routes::leaderboard::repo_responsiveness),
+        )
         .route(
             "/api/repos/:owner/:name/users/:username/stats",
             get(routes::users::repo_stats),
@@ -87,6 +123,14 @@ async fn main() -> anyhow::Result<()> {
             "/api/repos/:owner/:name/users/:username/reviews",
             get(routes::users::repo_reviews),
         )
+        .route(
+            "/api/repos/:owner/:name/users/:username/xp-ledger",
+            get(routes::users::repo_xp_ledger),
+        )
+        .route(
+            "/api/repos/:owner/:name/users/:username/achievements/progress",
+            get(routes::achievements::repo_user_progress),
+        )
         .route("/api/users/:username", get(routes::users::get))
         .route("/api/users/:username/stats", get(routes::users::stats))
         .route(
@@ -94,12 +138,47 @@ async fn main() -> anyhow::Result<()> {
             get(routes::users::activity),
         )
         .route("/api/users/:username/reviews", get(routes::users::reviews))
+        .route(
+            "/api/users/:username/expertise",
+            get(routes::users::expertise),
+        )
+        .route(
+            "/api/users/:username/xp-ledger",
+            get(routes::users::xp_ledger),
+        )
         .route(
             "/api/users/:username/achievements/progress",
             get(routes::achievements::user_progress),
         )
-        .route("/api/achievements", get(routes::achievements::list))
+        .route(
+            "/api/achievements",
+            get(routes::achievements::list).post(routes::achievements::create),
+        )
+        .route(
+            "/api/achievements/metrics",
+            get(routes::achievements::metrics),
+        )
+        .route(
+            "/api/achievements/reevaluate",
+            axum::routing::post(routes::achievements::reevaluate),
+        )
+        .route(
+            "/api/achievements/revocations",
+            get(routes::achievements::revocations),
+        )
+        .route(
+            "/api/achievements/:id",
+            axum::routing::delete(routes::achievements::delete),
+        )
         .route("/api/leaderboard", get(routes::leaderboard::global))
+        .route(
+            "/api/leaderboard/decay",
+            get(routes::leaderboard::get_decay).put(routes::leaderboard::set_decay),
+        )
+        .route(
+            "/api/leaderboard/responsiveness",
+            get(routes::leaderboard::responsiveness),
+        )
         .route(
             "/api/backfill/:owner/:name",
             get(routes::backfill::status).post(routes::backfill::trigger),
@@ -108,10 +187,37 @@ async fn main() -> anyhow::Result<()> {
             "/api/recalculate",
             axum::routing::post(routes::recalc::trigger),
         )
+        .route(
+            "/api/recalculate/simulate",
+            axum::routing::post(routes::recalc::simulate),
+        )
+        .route(
+            "/api/scoring-profiles",
+            get(routes::scoring::list).post(routes::scoring::create),
+        )
+        .route(
+            "/api/scoring-profiles/:name",
+            get(routes::scoring::get)
+                .put(routes::scoring::update)
+                .delete(routes::scoring::delete),
+        )
         .route(
             "/api/categorize",
             get(routes::categorize::stats).post(routes::categorize::trigger),
         )
+        .route("/api/gaming/flags", get(routes::gaming::flags))
+        .route(
+            "/api/gaming/analyze",
+            axum::routing::post(routes::gaming::analyze),
+        )
+        .route(
+            "/api/gaming/flags/:id/penalize",
+            axum::routing::post(routes::gaming::penalize),
+        )
+        .route(
+            "/api/gaming/flags/:id/dismiss",
+            axum::routing::post(routes::gaming::dismiss),
+        )
         .route("/api/seasons", get(routes::seasons::list))
         .route("/api/seasons/current", get(routes::seasons::current))
         .route(
@@ -140,6 +246,20 @@ async fn main() -> anyhow::Result<()> {
             "/api/teams/:name/members/:username",
             axum::routing::delete(routes::teams::remove_member),
         )
+        .route(
+            "/api/teams/:name/calendar",
+            get(routes::teams::get_calendar)
+                .put(routes::teams::set_calendar)
+                .delete(routes::teams::delete_calendar),
+        )
+        .route(
+            "/api/teams/:name/members/:username/achievements",
+            get(routes::achievements::team_member_achievements),
+        )
+        .route(
+            "/api/teams/:name/members/:username/achievements/progress",
+            get(routes::achievements::team_member_progress),
+        )
         .with_state(state);
 
     // Build full router with static file serving and SPA fallback
@@ -1,15 +1,68 @@
 //! Achievement routes
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::StatusCode,
     Json,
 };
-use serde::Serialize;
+use common::models::{
+    Achievement, AchievementMetric, AchievementRarity, AchievementScope, UserAchievement,
+};
+use db::achievement_rules::AchievementRule;
+use processor::achievements::AchievementChecker;
+use processor::categorize::Category;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
 use std::sync::Arc;
+use tracing::{error, info};
+use uuid::Uuid;
 
-use crate::error::{ApiResult, DbResultExt, OptionExt};
+use crate::error::{ApiError, ApiResult, DbResultExt, OptionExt};
+use crate::routes::users::RepoUserPath;
 use crate::state::AppState;
-use db::achievements::{AchievementCategory, AchievementProgress, AchievementWithStats};
+use db::achievements::{
+    AchievementCategory, AchievementProgress, AchievementRevocation, AchievementWithStats,
+};
+
+/// Largest XP reward a custom achievement may grant
+const MAX_XP_REWARD: i32 = 10_000;
+
+/// Longest time window a custom rule may use (10 years)
+const MAX_WINDOW_DAYS: i32 = 3650;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAchievementRequest {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub emoji: String,
+    /// Defaults to common
+    pub rarity: Option<AchievementRarity>,
+    #[serde(default)]
+    pub xp_reward: i32,
+    pub metric: AchievementMetric,
+    pub threshold: i64,
+    /// Comment category, required for `category_comments`
+    pub category: Option<Category>,
+    /// Only count activity in this repository ("owner/name")
+    pub repo: Option<String>,
+    /// Only count activity from the last N days. Earned once: the achievement
+    /// is kept after the activity ages out of the window, and revocation
+    /// never takes it back.
+    pub window_days: Option<i32>,
+}
+
+/// A created custom achievement and its rule
+#[derive(Serialize)]
+pub struct CustomAchievement {
+    #[serde(flatten)]
+    pub achievement: Achievement,
+    pub metric: AchievementMetric,
+    pub threshold: i64,
+    pub category: Option<String>,
+    pub repo: Option<String>,
+    pub window_days: Option<i32>,
+}
 
 /// Grouped achievements for the catalog
 #[derive(Serialize)]
@@ -88,9 +141,349 @@ pub async fn user_progress(
         .db_err()?
         .not_found(format!("User {} not found", username))?;
 
-    let progress = db::achievements::get_user_progress(&state.pool, user.id)
+    let progress = db::achievements::get_user_progress(&state.pool, user.id, None)
         .await
         .db_err()?;
 
     Ok(Json(progress))
 }
+
+/// Get a user's progress toward achievements within a repository
+/// GET /api/repos/:owner/:name/users/:username/achievements/progress
+pub async fn repo_user_progress(
+    State(state): State<Arc<AppState>>,
+    Path(path): Path<RepoUserPath>,
+) -> ApiResult<Json<Vec<AchievementProgress>>> {
+    let repo = db::repos::get_by_name(&state.pool, &path.owner, &path.name)
+        .await
+        .db_err()?
+        .not_found(format!("Repository {}/{} not found", path.owner, path.name))?;
+
+    let user = db::users::get_by_login(&state.pool, &path.username)
+        .await
+        .db_err()?
+        .not_found(format!("User {} not found", path.username))?;
+
+    let progress = db::achievements::get_user_progress(
+        &state.pool,
+        user.id,
+        Some(AchievementScope::Repo(repo.id)),
+    )
+    .await
+    .db_err()?;
+
+    Ok(Json(progress))
+}
+
+/// A team member's unlocks within the team
+/// GET /api/teams/:name/members/:username/achievements
+pub async fn team_member_achievements(
+    State(state): State<Arc<AppState>>,
+    Path((name, username)): Path<(String, String)>,
+) -> ApiResult<Json<Vec<UserAchievement>>> {
+    let (team_id, user_id) = team_member(&state, &name, &username).await?;
+
+    let achievements = db::achievements::list_for_user_in_scope(
+        &state.pool,
+        user_id,
+        AchievementScope::Team(team_id),
+    )
+    .await
+    .db_err()?;
+
+    Ok(Json(achievements))
+}
+
+/// A team member's progress toward achievements within the team
+/// GET /api/teams/:name/members/:username/achievements/progress
+pub async fn team_member_progress(
+    State(state): State<Arc<AppState>>,
+    Path((name, username)): Path<(String, String)>,
+) -> ApiResult<Json<Vec<AchievementProgress>>> {
+    let (team_id, user_id) = team_member(&state, &name, &username).await?;
+
+    let progress = db::achievements::get_user_progress(
+        &state.pool,
+        user_id,
+        Some(AchievementScope::Team(team_id)),
+    )
+    .await
+    .db_err()?;
+
+    Ok(Json(progress))
+}
+
+/// Resolve a team and one of its members (team achievements are only tracked
+/// for members)
+async fn team_member(state: &AppState, name: &str, username: &str) -> ApiResult<(Uuid, Uuid)> {
+    let team = db::teams::get_team_by_name(&state.pool, name)
+        .await
+        .db_err()?
+        .not_found(format!("Team '{}' not found", name))?;
+
+    let user_id = db::teams::get_team_members(&state.pool, team.id)
+        .await
+        .db_err()?
+        .into_iter()
+        .find(|(_, login, _)| login == username)
+        .map(|(id, _, _)| id)
+        .not_found(format!("User '{}' is not a member of '{}'", username, name))?;
+
+    Ok((team.id, user_id))
+}
+
+/// Create a custom achievement. Existing users are checked against it in the
+/// background.
+/// POST /api/achievements
+pub async fn create(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateAchievementRequest>,
+) -> ApiResult<(StatusCode, Json<CustomAchievement>)> {
+    validate_id(&req.id)?;
+    if req.name.trim().is_empty() || req.emoji.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "name and emoji are required".to_string(),
+        ));
+    }
+    if req.threshold < 1 {
+        return Err(ApiError::BadRequest(
+            "threshold must be at least 1".to_string(),
+        ));
+    }
+    if !(0..=MAX_XP_REWARD).contains(&req.xp_reward) {
+        return Err(ApiError::BadRequest(format!(
+            "xp_reward must be between 0 and {}",
+            MAX_XP_REWARD
+        )));
+    }
+    if let Some(days) = req.window_days {
+        if !(1..=MAX_WINDOW_DAYS).contains(&days) {
+            return Err(ApiError::BadRequest(format!(
+                "window_days must be between 1 and {}",
+                MAX_WINDOW_DAYS
+            )));
+        }
+    }
+    match (req.metric, req.category) {
+        (AchievementMetric::CategoryComments, None) => {
+            return Err(ApiError::BadRequest(
+                "category is required for category_comments".to_string(),
+            ))
+        }
+        (AchievementMetric::CategoryComments, Some(_)) | (_, None) => {}
+        (_, Some(_)) => {
+            return Err(ApiError::BadRequest(
+                "category only applies to category_comments".to_string(),
+            ))
+        }
+    }
+
+    let repo_id = match req.repo.as_deref() {
+        None => None,
+        Some(full_name) => {
+            let (owner, name) = full_name.split_once('/').ok_or_else(|| {
+                ApiError::BadRequest("repo must be in owner/name form".to_string())
+            })?;
+            Some(
+                db::repos::get_by_name(&state.pool, owner, name)
+                    .await
+                    .db_err()?
+                    .not_found(format!("Repository {} not found", full_name))?
+                    .id,
+            )
+        }
+    };
+
+    if db::achievements::get_by_id(&state.pool, &req.id)
+        .await
+        .db_err()?
+        .is_some()
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Achievement '{}' already exists",
+            req.id
+        )));
+    }
+
+    let achievement = Achievement {
+        id: req.id,
+        name: req.name,
+        description: req.description,
+        emoji: req.emoji,
+        xp_reward: req.xp_reward,
+        rarity: req.rarity.unwrap_or(AchievementRarity::Common),
+    };
+    let rule = AchievementRule {
+        achievement_id: achievement.id.clone(),
+        metric: req.metric,
+        threshold: req.threshold,
+        category: req.category.map(|c| c.as_str().to_string()),
+        repo_id,
+        team_id: None,
+        window_days: req.window_days,
+    };
+
+    info!(
+        "Creating custom achievement '{}': {:?}",
+        achievement.id, rule
+    );
+    db::achievements::create_custom(&state.pool, &achievement, &rule)
+        .await
+        .db_err()?;
+
+    spawn_check(state.pool.clone());
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CustomAchievement {
+            achievement,
+            metric: rule.metric,
+            threshold: rule.threshold,
+            category: rule.category,
+            repo: req.repo,
+            window_days: rule.window_days,
+        }),
+    ))
+}
+
+/// Delete a custom achievement. Only possible while nobody has unlocked it.
+/// DELETE /api/achievements/:id
+pub async fn delete(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> ApiResult<StatusCode> {
+    db::achievements::get_by_id(&state.pool, &id)
+        .await
+        .db_err()?
+        .not_found(format!("Achievement '{}' not found", id))?;
+
+    if !db::achievements::delete_custom(&state.pool, &id)
+        .await
+        .db_err()?
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Achievement '{}' is built in or already unlocked",
+            id
+        )));
+    }
+
+    info!("Deleted custom achievement '{}'", id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReevaluateParams {
+    /// Take back achievements users no longer qualify for
+    #[serde(default)]
+    pub revoke: bool,
+}
+
+/// Result of re-evaluating every user's achievements
+#[derive(Serialize)]
+pub struct ReevaluateResponse {
+    pub unlocked: usize,
+    /// Held achievements no longer met that were kept
+    pub stale: usize,
+    pub revoked: usize,
+    /// The same within repositories and teams
+    pub scoped_unlocked: usize,
+    pub scoped_stale: usize,
+    pub scoped_revoked: usize,
+}
+
+/// Re-evaluate every user's achievements against their current metrics.
+/// Unlocks newly met achievements; with `revoke=true` also takes back those no
+/// longer met (audit-logged, not announced).
+/// POST /api/achievements/reevaluate?revoke=bool
+pub async fn reevaluate(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ReevaluateParams>,
+) -> ApiResult<Json<ReevaluateResponse>> {
+    info!(
+        "Achievement re-evaluation triggered (revoke: {})",
+        params.revoke
+    );
+    let evaluation = AchievementChecker::new(state.pool.clone())
+        .reevaluate_all(params.revoke)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Re-evaluation failed: {}", e)))?;
+
+    Ok(Json(ReevaluateResponse {
+        unlocked: evaluation.unlocked.len(),
+        stale: evaluation.stale.len() - evaluation.revoked.len(),
+        revoked: evaluation.revoked.len(),
+        scoped_unlocked: evaluation.scoped_unlocked.len(),
+        scoped_stale: evaluation.scoped_stale.len() - evaluation.scoped_revoked.len(),
+        scoped_revoked: evaluation.scoped_revoked.len(),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RevocationsParams {
+    /// Only this user's revocations
+    pub user: Option<String>,
+    #[serde(default = "default_revocations_limit")]
+    pub limit: i64,
+}
+
+fn default_revocations_limit() -> i64 {
+    50
+}
+
+/// Audit log of revoked achievements, newest first
+/// GET /api/achievements/revocations?user=login&limit=N
+pub async fn revocations(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<RevocationsParams>,
+) -> ApiResult<Json<Vec<AchievementRevocation>>> {
+    let user_id = match params.user.as_deref() {
+        None => None,
+        Some(login) => Some(
+            db::users::get_by_login(&state.pool, login)
+                .await
+                .db_err()?
+                .not_found(format!("User {} not found", login))?
+                .id,
+        ),
+    };
+
+    let revocations =
+        db::achievements::list_revocations(&state.pool, user_id, params.limit.clamp(1, 500))
+            .await
+            .db_err()?;
+    Ok(Json(revocations))
+}
+
+/// Metrics custom achievements can be built from
+/// GET /api/achievements/metrics
+pub async fn metrics() -> Json<Vec<AchievementMetric>> {
+    Json(AchievementMetric::ALL.to_vec())
+}
+
+/// Achievement IDs: lowercase letters, digits and '_'
+fn validate_id(id: &str) -> ApiResult<()> {
+    let valid = !id.is_empty()
+        && id.len() <= 64
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid {
+        return Err(ApiError::BadRequest(
+            "id must be 1-64 characters of a-z, 0-9 or '_'".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Check every user against the achievements without blocking the request
+fn spawn_check(pool: PgPool) {
+    tokio::spawn(async move {
+        match AchievementChecker::new(pool).reevaluate_all(false).await {
+            Ok(evaluation) => info!(
+                "Custom achievement check unlocked {} achievements",
+                evaluation.unlocked.len()
+            ),
+            Err(e) => error!("Achievement check after creating achievement failed: {}", e),
+        }
+    });
+}
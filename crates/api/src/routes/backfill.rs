@@ -75,13 +75,19 @@ pub async fn trigger(
     );
 
     match backfiller.backfill_repo(&owner, &name).await {
-        Ok(progress) => Ok(Json(BackfillResponse {
-            success: true,
-            message: format!("Backfill complete for {}/{}", owner, name),
-            prs_processed: progress.prs_processed,
-            reviews_processed: progress.reviews_processed,
-            users_created: progress.users_created,
-        })),
+        Ok(progress) => {
+            if let Err(e) = processor::recalculate_incremental(&state.pool).await {
+                tracing::error!("XP recalculation failed after backfill: {}", e);
+            }
+
+            Ok(Json(BackfillResponse {
+                success: true,
+                message: format!("Backfill complete for {}/{}", owner, name),
+                prs_processed: progress.prs_processed,
+                reviews_processed: progress.reviews_processed,
+                users_created: progress.users_created,
+            }))
+        }
         Err(processor::backfill::BackfillError::RateLimited(retry_after)) => {
             Err(ApiError::RateLimited(retry_after))
         }
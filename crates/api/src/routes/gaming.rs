@@ -0,0 +1,132 @@
+//! Anti-gaming flag review routes
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use db::gaming::{FlagStatus, GamingFlag};
+use processor::gaming::GamingReport;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+use crate::error::{ApiError, ApiResult, DbResultExt, OptionExt};
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct FlagsParams {
+    /// open, dismissed or penalized (default: all)
+    pub status: Option<String>,
+    #[serde(default = "default_flags_limit")]
+    pub limit: i64,
+}
+
+fn default_flags_limit() -> i64 {
+    50
+}
+
+/// Flags raised by the anti-gaming analysis, newest first
+/// GET /api/gaming/flags?status=open&limit=N
+pub async fn flags(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<FlagsParams>,
+) -> ApiResult<Json<Vec<GamingFlag>>> {
+    let status = match params.status.as_deref() {
+        None => None,
+        Some(s) => Some(FlagStatus::parse(s).ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "Invalid status '{}': expected open, dismissed or penalized",
+                s
+            ))
+        })?),
+    };
+
+    let flags = db::gaming::list_flags(&state.pool, status, params.limit.clamp(1, 500))
+        .await
+        .db_err()?;
+    Ok(Json(flags))
+}
+
+/// Run the analysis now instead of waiting for the next sync
+/// POST /api/gaming/analyze
+pub async fn analyze(State(state): State<Arc<AppState>>) -> ApiResult<Json<GamingReport>> {
+    info!("Gaming analysis triggered via API");
+    let report = processor::gaming::analyze(&state.pool)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Gaming analysis failed: {}", e)))?;
+    Ok(Json(report))
+}
+
+#[derive(Serialize)]
+pub struct PenalizeResponse {
+    pub flag: GamingFlag,
+    /// (PR, user) pairs newly excluded from XP
+    pub pairs_excluded: u64,
+    /// XP the flag's pairs lost
+    pub xp_removed: i64,
+    /// Non-fatal failures of the follow-up recalculation
+    pub errors: Vec<String>,
+}
+
+/// Zero out the XP involved in a flag and keep it excluded from future
+/// recalculations
+/// POST /api/gaming/flags/:id/penalize
+pub async fn penalize(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<PenalizeResponse>> {
+    let before = db::gaming::get_flag(&state.pool, id)
+        .await
+        .db_err()?
+        .not_found(format!("Gaming flag {} not found", id))?;
+
+    let pairs_excluded = db::gaming::penalize(&state.pool, id)
+        .await
+        .db_err()?
+        .not_found(format!("Gaming flag {} not found", id))?;
+    info!(
+        "Gaming flag {} penalized, {} pairs excluded",
+        id, pairs_excluded
+    );
+
+    // The excluded pairs were touched, so this rescores just them
+    let stats = processor::recalculate_incremental(&state.pool)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Recalculation failed: {}", e)))?;
+
+    let flag = db::gaming::get_flag(&state.pool, id)
+        .await
+        .db_err()?
+        .not_found(format!("Gaming flag {} not found", id))?;
+
+    Ok(Json(PenalizeResponse {
+        xp_removed: before.xp_involved - flag.xp_involved,
+        flag,
+        pairs_excluded,
+        errors: stats.errors,
+    }))
+}
+
+/// Mark a flag as a false positive, restoring any XP it removed
+/// POST /api/gaming/flags/:id/dismiss
+pub async fn dismiss(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<GamingFlag>> {
+    let found = db::gaming::dismiss(&state.pool, id).await.db_err()?;
+    if !found {
+        return Err(ApiError::NotFound(format!("Gaming flag {} not found", id)));
+    }
+    info!("Gaming flag {} dismissed", id);
+
+    // Restore XP if the flag had been penalized
+    processor::recalculate_incremental(&state.pool)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Recalculation failed: {}", e)))?;
+
+    let flag = db::gaming::get_flag(&state.pool, id)
+        .await
+        .db_err()?
+        .not_found(format!("Gaming flag {} not found", id))?;
+    Ok(Json(flag))
+}
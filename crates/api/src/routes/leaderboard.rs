@@ -7,14 +7,19 @@ use axum::{
 use chrono::{Duration, Utc};
 use serde::Deserialize;
 use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
 
-use crate::error::{ApiResult, DbResultExt, OptionExt};
+use crate::error::{ApiError, ApiResult, DbResultExt, OptionExt};
 use crate::state::AppState;
-use common::models::LeaderboardEntry;
+use common::models::{LeaderboardEntry, ResponsivenessEntry};
+use db::decay::DecaySettings;
+use db::leaderboard::Ranking;
 
 #[derive(Deserialize)]
 pub struct LeaderboardQuery {
-    /// Time period: "week", "month", "all"
+    /// Time period: "week", "month", "all", or "decayed" (all XP, weighted
+    /// by age)
     #[serde(default = "default_period")]
     period: String,
     /// Limit
@@ -30,12 +35,13 @@ fn default_limit() -> i32 {
     25
 }
 
-fn period_to_since(period: &str) -> chrono::DateTime<Utc> {
+pub(crate) fn period_to_ranking(period: &str) -> Ranking {
     match period {
-        "week" => Utc::now() - Duration::days(7),
-        "month" => Utc::now() - Duration::days(30),
-        "all" => Utc::now() - Duration::days(365 * 10),
-        _ => Utc::now() - Duration::days(30),
+        "week" => Ranking::Since(Utc::now() - Duration::days(7)),
+        "month" => Ranking::Since(Utc::now() - Duration::days(30)),
+        "all" => Ranking::Since(Utc::now() - Duration::days(365 * 10)),
+        "decayed" => Ranking::Decayed,
+        _ => Ranking::Since(Utc::now() - Duration::days(30)),
     }
 }
 
@@ -43,9 +49,9 @@ pub async fn global(
     State(state): State<Arc<AppState>>,
     Query(query): Query<LeaderboardQuery>,
 ) -> ApiResult<Json<Vec<LeaderboardEntry>>> {
-    let since = period_to_since(&query.period);
+    let ranking = period_to_ranking(&query.period);
 
-    let leaderboard = db::leaderboard::get_leaderboard(&state.pool, None, since, query.limit)
+    let leaderboard = db::leaderboard::get_leaderboard(&state.pool, None, ranking, query.limit)
         .await
         .db_err()?;
 
@@ -62,12 +68,85 @@ pub async fn get(
         .db_err()?
         .not_found(format!("Repository {}/{} not found", owner, name))?;
 
-    let since = period_to_since(&query.period);
+    let ranking = period_to_ranking(&query.period);
 
     let leaderboard =
-        db::leaderboard::get_leaderboard(&state.pool, Some(repo.id), since, query.limit)
+        db::leaderboard::get_leaderboard(&state.pool, Some(repo.id), ranking, query.limit)
             .await
             .db_err()?;
 
     Ok(Json(leaderboard))
 }
+
+/// Authors ranked by responsiveness to review feedback
+/// GET /api/leaderboard/responsiveness
+pub async fn responsiveness(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<LeaderboardQuery>,
+) -> ApiResult<Json<Vec<ResponsivenessEntry>>> {
+    responsiveness_for(&state, None, &query).await
+}
+
+/// Authors ranked by responsiveness within a repo
+/// GET /api/repos/:owner/:name/leaderboard/responsiveness
+pub async fn repo_responsiveness(
+    State(state): State<Arc<AppState>>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(query): Query<LeaderboardQuery>,
+) -> ApiResult<Json<Vec<ResponsivenessEntry>>> {
+    let repo = db::repos::get_by_name(&state.pool, &owner, &name)
+        .await
+        .db_err()?
+        .not_found(format!("Repository {}/{} not found", owner, name))?;
+
+    responsiveness_for(&state, Some(repo.id), &query).await
+}
+
+/// The score doesn't decay, so `period=decayed` covers all time
+async fn responsiveness_for(
+    state: &AppState,
+    repo_id: Option<Uuid>,
+    query: &LeaderboardQuery,
+) -> ApiResult<Json<Vec<ResponsivenessEntry>>> {
+    let since = period_to_ranking(&query.period).since();
+    let data = db::responsiveness::get_author_data(&state.pool, repo_id, since)
+        .await
+        .db_err()?;
+
+    Ok(Json(processor::responsiveness::rank(
+        data,
+        query.limit.max(0) as usize,
+    )))
+}
+
+#[derive(Deserialize)]
+pub struct DecayRequest {
+    pub half_life_days: f64,
+}
+
+/// Get the half-life used by the decayed ranking
+/// GET /api/leaderboard/decay
+pub async fn get_decay(State(state): State<Arc<AppState>>) -> ApiResult<Json<DecaySettings>> {
+    let settings = db::decay::get_settings(&state.pool).await.db_err()?;
+    Ok(Json(settings))
+}
+
+/// Set the half-life used by the decayed ranking and recompute the weights
+/// PUT /api/leaderboard/decay
+pub async fn set_decay(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<DecayRequest>,
+) -> ApiResult<Json<DecaySettings>> {
+    if !(1.0..=3650.0).contains(&req.half_life_days) {
+        return Err(ApiError::BadRequest(
+            "half_life_days must be between 1 and 3650".to_string(),
+        ));
+    }
+
+    info!("Setting XP decay half-life to {} days", req.half_life_days);
+    let settings = db::decay::set_half_life(&state.pool, req.half_life_days)
+        .await
+        .db_err()?;
+
+    Ok(Json(settings))
+}
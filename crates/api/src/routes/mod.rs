@@ -3,10 +3,12 @@
 pub mod achievements;
 pub mod backfill;
 pub mod categorize;
+pub mod gaming;
 pub mod health;
 pub mod leaderboard;
 pub mod recalc;
 pub mod repos;
+pub mod scoring;
 pub mod seasons;
 pub mod teams;
 pub mod users;
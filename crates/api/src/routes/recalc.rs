@@ -1,34 +1,170 @@
 //! XP recalculation routes
 
-use axum::{extract::State, Json};
-use serde::Serialize;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use processor::achievements::AchievementChecker;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::sync::Arc;
 use tracing::info;
 
-use crate::error::{ApiError, ApiResult};
+use crate::error::{ApiError, ApiResult, DbResultExt, OptionExt};
+use crate::routes::scoring::merge_weights;
 use crate::state::AppState;
 
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RecalcMode {
+    /// Reset and rescore everything (use after formula changes)
+    #[default]
+    Full,
+    /// Rescore only (PR, reviewer) pairs touched since the last run
+    Incremental,
+}
+
+#[derive(Deserialize)]
+pub struct RecalcParams {
+    #[serde(default)]
+    pub mode: RecalcMode,
+    /// Afterwards, re-evaluate every user and revoke achievements they no
+    /// longer qualify for
+    #[serde(default)]
+    pub revoke_achievements: bool,
+}
+
 #[derive(Serialize)]
 pub struct RecalcResponse {
     pub status: String,
+    pub mode: RecalcMode,
     pub total_reviews: usize,
     pub total_sessions: usize,
     pub total_xp_awarded: i64,
     pub users_updated: usize,
+    pub pairs_processed: usize,
+    pub achievements_unlocked: usize,
+    /// Held achievements no longer met (0 after revoking them)
+    pub achievements_stale: usize,
+    pub achievements_revoked: usize,
+    /// Unlocks within a repository or team
+    pub scoped_achievements_unlocked: usize,
+    /// Non-fatal write failures (XP itself is swapped in atomically)
+    pub errors: Vec<String>,
 }
 
-pub async fn trigger(State(state): State<Arc<AppState>>) -> ApiResult<Json<RecalcResponse>> {
-    info!("Recalculation triggered via API");
+pub async fn trigger(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<RecalcParams>,
+) -> ApiResult<Json<RecalcResponse>> {
+    info!(
+        "Recalculation triggered via API (mode: {:?}, revoke achievements: {})",
+        params.mode, params.revoke_achievements
+    );
 
-    let stats = processor::recalculate_all_xp(&state.pool)
-        .await
-        .map_err(|e| ApiError::Internal(format!("Recalculation failed: {}", e)))?;
+    let result = match params.mode {
+        RecalcMode::Full => processor::recalculate_all_xp(&state.pool).await,
+        RecalcMode::Incremental => processor::recalculate_incremental(&state.pool).await,
+    };
+    let mut stats =
+        result.map_err(|e| ApiError::Internal(format!("Recalculation failed: {}", e)))?;
+
+    let mut achievements_revoked = 0;
+    if params.revoke_achievements {
+        match AchievementChecker::new(state.pool.clone())
+            .reevaluate_all(true)
+            .await
+        {
+            Ok(evaluation) => {
+                stats.achievements_unlocked += evaluation.unlocked.len();
+                stats.scoped_achievements_unlocked += evaluation.scoped_unlocked.len();
+                stats.achievements_stale = 0;
+                achievements_revoked = evaluation.revoked.len();
+            }
+            Err(e) => stats
+                .errors
+                .push(format!("achievement re-evaluation: {}", e)),
+        }
+    }
 
     Ok(Json(RecalcResponse {
-        status: "complete".to_string(),
+        status: if stats.errors.is_empty() {
+            "complete".to_string()
+        } else {
+            "complete_with_errors".to_string()
+        },
+        mode: params.mode,
         total_reviews: stats.total_reviews,
         total_sessions: stats.total_sessions,
         total_xp_awarded: stats.total_xp_awarded,
         users_updated: stats.users_updated,
+        pairs_processed: stats.pairs_processed,
+        achievements_unlocked: stats.achievements_unlocked,
+        achievements_stale: stats.achievements_stale,
+        achievements_revoked,
+        scoped_achievements_unlocked: stats.scoped_achievements_unlocked,
+        errors: stats.errors,
     }))
 }
+
+#[derive(Deserialize)]
+pub struct SimulateRequest {
+    /// Score every repo with this profile instead of its own
+    pub profile: Option<String>,
+    /// Weight overrides applied on top of each repo's (or `profile`'s) weights
+    #[serde(default)]
+    pub weights: Map<String, Value>,
+    /// How many of the most changed sessions to return
+    #[serde(default = "default_top_sessions")]
+    pub top_sessions: usize,
+}
+
+fn default_top_sessions() -> usize {
+    20
+}
+
+/// Dry-run a recalculation with alternative scoring and compare it with the
+/// XP users currently hold. Nothing is written.
+/// POST /api/recalculate/simulate
+pub async fn simulate(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SimulateRequest>,
+) -> ApiResult<Json<processor::SimulationReport>> {
+    let top_sessions = req.top_sessions.min(500);
+
+    let report = match req.profile.as_deref() {
+        Some(profile_name) => {
+            let profile = db::scoring_profiles::get_by_name(&state.pool, profile_name)
+                .await
+                .db_err()?
+                .not_found(format!("Scoring profile '{}' not found", profile_name))?;
+            let weights = merge_weights(&profile.weights, req.weights)?;
+
+            info!("Simulating recalculation with profile '{}'", profile_name);
+            processor::simulate_recalculation(&state.pool, |_| weights.clone(), top_sessions).await
+        }
+        None => {
+            // Overrides must be valid on top of every profile in use
+            let profiles = db::scoring_profiles::list(&state.pool).await.db_err()?;
+            for profile in &profiles {
+                merge_weights(&profile.weights, req.weights.clone()).map_err(|e| match e {
+                    ApiError::BadRequest(msg) => {
+                        ApiError::BadRequest(format!("{} (profile '{}')", msg, profile.name))
+                    }
+                    e => e,
+                })?;
+            }
+
+            info!("Simulating recalculation with overrides {:?}", req.weights);
+            processor::simulate_recalculation(
+                &state.pool,
+                |base| merge_weights(base, req.weights.clone()).unwrap_or_else(|_| base.clone()),
+                top_sessions,
+            )
+            .await
+        }
+    };
+    let report = report.map_err(|e| ApiError::Internal(format!("Simulation failed: {}", e)))?;
+
+    Ok(Json(report))
+}
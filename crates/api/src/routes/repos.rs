@@ -1,17 +1,23 @@
 //! Repository routes
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     Json,
 };
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::info;
 
-use crate::error::{ApiResult, DbResultExt, OptionExt};
+use crate::error::{ApiError, ApiResult, DbResultExt, OptionExt};
 use crate::state::AppState;
-use common::models::Repository;
+use common::models::{
+    CoverageReport, RepoAnalytics, RepoExperts, RepoSyncSettings, RepoWorkload, Repository,
+    ReviewerSuggestion, StalePrs,
+};
+use db::review_threads::ReviewThread;
+use processor::latency::Calendars;
+use processor::recommend;
 
 /// Allowed orgs for auto-discovery
 const ALLOWED_ORGS: &[&str] = &["sigp", "ethereum", "chainsafe", "offchainlabs"];
@@ -104,7 +110,7 @@ pub async fn get(
                     "Recalculating XP after sync for {}/{}",
                     owner_clone, name_clone
                 );
-                if let Err(e) = processor::recalculate_all_xp(&pool).await {
+                if let Err(e) = processor::recalculate_incremental(&pool).await {
                     tracing::error!(
                         "XP recalculation failed after sync for {}/{}: {}",
                         owner_clone,
@@ -138,12 +144,19 @@ async fn get_sync_status(state: &AppState, repo: &Repository) -> SyncStatus {
         .ok()
         .flatten();
 
-    let target_date = Utc::now() - chrono::Duration::days(365);
+    let max_age_days = db::repos::get_sync_settings(&state.pool, repo.id)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+        .max_age_days;
 
-    // Calculate progress: how much of the 365-day window we have data for
+    let target_date = Utc::now() - chrono::Duration::days(max_age_days as i64);
+
+    // Calculate progress: how much of the configured history window we have data for
     let progress_pct = match oldest_data {
         Some(oldest) => {
-            let total_days = 365.0;
+            let total_days = max_age_days.max(1) as f64;
             let days_covered = (Utc::now() - oldest).num_days() as f64;
             (days_covered / total_days * 100.0).min(100.0)
         }
@@ -172,8 +185,10 @@ pub struct OpenPrResponse {
     pub author: String,
     pub author_avatar: Option<String>,
     pub created_at: DateTime<Utc>,
+    /// Working hours on the author's calendar since the PR was opened
     pub age_hours: i64,
     pub first_review_at: Option<DateTime<Utc>>,
+    /// Working hours on the author's calendar until the first review
     pub hours_to_first_review: Option<i64>,
     pub review_count: i32,
     pub approvals: i32,
@@ -181,6 +196,9 @@ pub struct OpenPrResponse {
     pub comments_count: i32,
     pub status: String, // "approved", "changes_requested", "reviewed", "needs_review"
     pub reviewers: Vec<String>,
+    /// Who should pick it up next, best first (empty once approved, or
+    /// unless requested with `?suggest=true`)
+    pub suggested_reviewers: Vec<ReviewerSuggestion>,
     pub url: String,
 }
 
@@ -194,10 +212,19 @@ pub struct OpenPrsSummary {
     pub prs: Vec<OpenPrResponse>,
 }
 
+#[derive(Deserialize)]
+pub struct OpenPrsQuery {
+    /// Suggest reviewers for PRs waiting on review (several extra queries)
+    #[serde(default)]
+    pub suggest: bool,
+}
+
 /// Get open PRs for a repository
+/// GET /api/repos/:owner/:name/open-prs?suggest=true
 pub async fn open_prs(
     State(state): State<Arc<AppState>>,
     Path((owner, name)): Path<(String, String)>,
+    Query(query): Query<OpenPrsQuery>,
 ) -> ApiResult<Json<OpenPrsSummary>> {
     let repo = db::repos::get_by_name(&state.pool, &owner, &name)
         .await
@@ -207,7 +234,15 @@ pub async fn open_prs(
     let prs = db::prs::list_open_with_stats(&state.pool, repo.id)
         .await
         .db_err()?;
-
+    // Ages and waits count the author's working hours
+    let calendars = Calendars::load(&state.pool).await.db_err()?;
+    let mut suggestions = if query.suggest {
+        recommend::recommend_for_prs(&state.pool, repo.id, &prs, &calendars, 3)
+            .await
+            .db_err()?
+    } else {
+        Default::default()
+    };
     let now = Utc::now();
     let mut needs_review = 0i64;
     let mut approved = 0i64;
@@ -216,8 +251,11 @@ pub async fn open_prs(
     let prs: Vec<OpenPrResponse> = prs
         .into_iter()
         .map(|pr| {
-            let age_hours = (now - pr.created_at).num_hours();
-            let hours_to_first_review = pr.first_review_at.map(|t| (t - pr.created_at).num_hours());
+            let calendar = calendars.for_user(pr.author_id);
+            let age_hours = calendar.working_time(pr.created_at, now).num_hours();
+            let hours_to_first_review = pr
+                .first_review_at
+                .map(|t| calendar.working_time(pr.created_at, t).num_hours());
 
             let status = if pr.approvals > 0 && pr.changes_requested == 0 {
                 approved += 1;
@@ -247,6 +285,7 @@ pub async fn open_prs(
                 comments_count: pr.comments_count,
                 status: status.to_string(),
                 reviewers: pr.reviewers,
+                suggested_reviewers: suggestions.remove(&pr.id).unwrap_or_default(),
                 url: format!("https://github.com/{}/{}/pull/{}", owner, name, pr.number),
             }
         })
@@ -260,3 +299,367 @@ pub async fn open_prs(
         prs,
     }))
 }
+
+/// Review threads on a PR with their metrics
+/// GET /api/repos/:owner/:name/prs/:number/threads
+pub async fn pr_threads(
+    State(state): State<Arc<AppState>>,
+    Path((owner, name, number)): Path<(String, String, i32)>,
+) -> ApiResult<Json<Vec<ReviewThread>>> {
+    let repo = db::repos::get_by_name(&state.pool, &owner, &name)
+        .await
+        .db_err()?
+        .not_found(format!("Repository {}/{} not found", owner, name))?;
+
+    let pr = db::prs::get_by_number(&state.pool, repo.id, number)
+        .await
+        .db_err()?
+        .not_found(format!("PR #{} not found in {}/{}", number, owner, name))?;
+
+    let threads = db::review_threads::list_for_pr(&state.pool, pr.id)
+        .await
+        .db_err()?;
+
+    Ok(Json(threads))
+}
+
+#[derive(Deserialize)]
+pub struct ExpertsQuery {
+    /// Areas returned of each kind
+    #[serde(default = "default_expert_areas")]
+    pub areas: i64,
+    /// Reviewers returned per area
+    #[serde(default = "default_experts_per_area")]
+    pub experts: i64,
+}
+
+fn default_expert_areas() -> i64 {
+    20
+}
+
+fn default_experts_per_area() -> i64 {
+    3
+}
+
+/// Who knows what: the top reviewers of the repo's most reviewed
+/// directories, extensions and languages over the last year
+/// GET /api/repos/:owner/:name/experts?areas=N&experts=N
+pub async fn experts(
+    State(state): State<Arc<AppState>>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(query): Query<ExpertsQuery>,
+) -> ApiResult<Json<RepoExperts>> {
+    let repo = db::repos::get_by_name(&state.pool, &owner, &name)
+        .await
+        .db_err()?
+        .not_found(format!("Repository {}/{} not found", owner, name))?;
+
+    let now = Utc::now();
+    let since = processor::expertise::window_start(now);
+    let comments = db::expertise::path_comments(&state.pool, None, Some(repo.id), since)
+        .await
+        .db_err()?;
+    let mut user_ids: Vec<_> = comments.iter().map(|c| c.user_id).collect();
+    user_ids.sort();
+    user_ids.dedup();
+    let logins = db::users::logins_by_id(&state.pool, &user_ids)
+        .await
+        .db_err()?;
+
+    Ok(Json(processor::expertise::repo_experts(
+        &comments,
+        &logins,
+        now,
+        query.areas.clamp(1, 100) as usize,
+        query.experts.clamp(1, 20) as usize,
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WorkloadQuery {
+    /// Active PRs at which a reviewer counts as overloaded
+    #[serde(default = "default_overloaded_at")]
+    pub overloaded_at: i64,
+    /// Weeks to average review sessions over
+    #[serde(default = "default_workload_weeks")]
+    pub weeks: i64,
+}
+
+fn default_overloaded_at() -> i64 {
+    processor::workload::DEFAULT_OVERLOADED_AT
+}
+
+fn default_workload_weeks() -> i64 {
+    processor::workload::DEFAULT_WEEKS
+}
+
+/// Per reviewer: open PRs they're reviewing, those awaiting their re-review
+/// after new commits, how old those PRs are and recent sessions per week
+/// GET /api/repos/:owner/:name/workload?overloaded_at=N&weeks=N
+pub async fn workload(
+    State(state): State<Arc<AppState>>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(query): Query<WorkloadQuery>,
+) -> ApiResult<Json<RepoWorkload>> {
+    let repo = db::repos::get_by_name(&state.pool, &owner, &name)
+        .await
+        .db_err()?
+        .not_found(format!("Repository {}/{} not found", owner, name))?;
+
+    let workload = processor::workload::repo_workload(
+        &state.pool,
+        repo.id,
+        query.overloaded_at.clamp(1, 1000),
+        query.weeks.clamp(1, 52),
+    )
+    .await
+    .db_err()?;
+
+    Ok(Json(workload))
+}
+
+/// Open PRs with no commits, reviews or comments for the repo's
+/// `stale_after_days`, with who they're waiting on
+/// GET /api/repos/:owner/:name/stale
+pub async fn stale(
+    State(state): State<Arc<AppState>>,
+    Path((owner, name)): Path<(String, String)>,
+) -> ApiResult<Json<StalePrs>> {
+    let repo = db::repos::get_by_name(&state.pool, &owner, &name)
+        .await
+        .db_err()?
+        .not_found(format!("Repository {}/{} not found", owner, name))?;
+
+    let stale = processor::stale::repo_stale_prs(&state.pool, &repo)
+        .await
+        .db_err()?;
+
+    Ok(Json(stale))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnalyticsQuery {
+    /// Weeks of merged PRs covered, the current week included
+    #[serde(default = "default_analytics_weeks")]
+    pub weeks: i64,
+}
+
+fn default_analytics_weeks() -> i64 {
+    processor::analytics::DEFAULT_WEEKS
+}
+
+/// Cycle times (first review, review rounds, approval to merge, time to
+/// merge) of recently merged PRs, as p50/p90 overall, per week and per author
+/// GET /api/repos/:owner/:name/analytics?weeks=N
+pub async fn analytics(
+    State(state): State<Arc<AppState>>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(query): Query<AnalyticsQuery>,
+) -> ApiResult<Json<RepoAnalytics>> {
+    let repo = db::repos::get_by_name(&state.pool, &owner, &name)
+        .await
+        .db_err()?
+        .not_found(format!("Repository {}/{} not found", owner, name))?;
+
+    let analytics =
+        processor::analytics::repo_analytics(&state.pool, repo.id, query.weeks.clamp(1, 104))
+            .await
+            .db_err()?;
+
+    Ok(Json(analytics))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CoverageQuery {
+    /// Weeks of merged PRs covered, the current week included
+    #[serde(default = "default_coverage_weeks")]
+    pub weeks: i64,
+}
+
+fn default_coverage_weeks() -> i64 {
+    processor::coverage::DEFAULT_WEEKS
+}
+
+/// Recently merged PRs with no review, only rubber stamps, or a standing
+/// change request, with weekly counts and per-reviewer rubber-stamp rates
+/// GET /api/repos/:owner/:name/coverage?weeks=N
+pub async fn coverage(
+    State(state): State<Arc<AppState>>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(query): Query<CoverageQuery>,
+) -> ApiResult<Json<CoverageReport>> {
+    let repo = db::repos::get_by_name(&state.pool, &owner, &name)
+        .await
+        .db_err()?
+        .not_found(format!("Repository {}/{} not found", owner, name))?;
+
+    let report = processor::coverage::repo_coverage(&state.pool, &repo, query.weeks.clamp(1, 104))
+        .await
+        .db_err()?;
+
+    Ok(Json(report))
+}
+
+/// Partial update of a repository's sync settings
+#[derive(Debug, Deserialize)]
+pub struct UpdateSyncSettingsRequest {
+    pub enabled: Option<bool>,
+    /// Hours between syncs (0 = use global SYNC_INTERVAL_HOURS)
+    pub interval_hours: Option<i32>,
+    pub max_age_days: Option<i32>,
+    pub priority: Option<i32>,
+    pub fetch_commits: Option<bool>,
+    pub fetch_comments: Option<bool>,
+    pub stale_after_days: Option<i32>,
+}
+
+/// Get sync settings for a repository
+/// GET /api/repos/:owner/:name/settings
+pub async fn get_settings(
+    State(state): State<Arc<AppState>>,
+    Path((owner, name)): Path<(String, String)>,
+) -> ApiResult<Json<RepoSyncSettings>> {
+    let repo = db::repos::get_by_name(&state.pool, &owner, &name)
+        .await
+        .db_err()?
+        .not_found(format!("Repository {}/{} not found", owner, name))?;
+
+    let settings = db::repos::get_sync_settings(&state.pool, repo.id)
+        .await
+        .db_err()?
+        .not_found(format!("Repository {}/{} not found", owner, name))?;
+
+    Ok(Json(settings))
+}
+
+/// Update sync settings for a repository
+/// PUT /api/repos/:owner/:name/settings
+pub async fn update_settings(
+    State(state): State<Arc<AppState>>,
+    Path((owner, name)): Path<(String, String)>,
+    Json(req): Json<UpdateSyncSettingsRequest>,
+) -> ApiResult<Json<RepoSyncSettings>> {
+    let repo = db::repos::get_by_name(&state.pool, &owner, &name)
+        .await
+        .db_err()?
+        .not_found(format!("Repository {}/{} not found", owner, name))?;
+
+    let mut settings = db::repos::get_sync_settings(&state.pool, repo.id)
+        .await
+        .db_err()?
+        .not_found(format!("Repository {}/{} not found", owner, name))?;
+
+    if let Some(enabled) = req.enabled {
+        settings.enabled = enabled;
+    }
+    if let Some(hours) = req.interval_hours {
+        if !(0..=24 * 30).contains(&hours) {
+            return Err(ApiError::BadRequest(
+                "interval_hours must be between 0 and 720".to_string(),
+            ));
+        }
+        settings.interval_hours = (hours > 0).then_some(hours);
+    }
+    if let Some(days) = req.max_age_days {
+        if !(1..=3650).contains(&days) {
+            return Err(ApiError::BadRequest(
+                "max_age_days must be between 1 and 3650".to_string(),
+            ));
+        }
+        settings.max_age_days = days;
+    }
+    if let Some(priority) = req.priority {
+        settings.priority = priority;
+    }
+    if let Some(fetch_commits) = req.fetch_commits {
+        settings.fetch_commits = fetch_commits;
+    }
+    if let Some(fetch_comments) = req.fetch_comments {
+        settings.fetch_comments = fetch_comments;
+    }
+    if let Some(days) = req.stale_after_days {
+        if !(1..=365).contains(&days) {
+            return Err(ApiError::BadRequest(
+                "stale_after_days must be between 1 and 365".to_string(),
+            ));
+        }
+        settings.stale_after_days = days;
+    }
+
+    info!(
+        "Updating sync settings for {}/{}: {:?}",
+        owner, name, settings
+    );
+
+    let settings = db::repos::update_sync_settings(&state.pool, repo.id, &settings)
+        .await
+        .db_err()?;
+
+    Ok(Json(settings))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UntrackParams {
+    /// "freeze" (default) keeps history, "purge" deletes it
+    #[serde(default)]
+    pub mode: processor::UntrackMode,
+    /// After a purge, revoke achievements the affected users no longer
+    /// qualify for (otherwise they are only reported)
+    #[serde(default)]
+    pub revoke_achievements: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UntrackResponse {
+    pub repo: String,
+    pub mode: processor::UntrackMode,
+    pub affected_users: usize,
+    pub prs_deleted: i64,
+    pub reviews_deleted: i64,
+    pub comments_deleted: i64,
+    pub commits_deleted: i64,
+    pub xp_recalculated: bool,
+    /// Held achievements no longer met (0 after revoking them)
+    pub achievements_stale: usize,
+    pub achievements_revoked: usize,
+}
+
+/// Stop tracking a repository, optionally purging its data
+/// DELETE /api/repos/:owner/:name?mode=freeze|purge&revoke_achievements=bool
+pub async fn untrack(
+    State(state): State<Arc<AppState>>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<UntrackParams>,
+) -> ApiResult<Json<UntrackResponse>> {
+    let repo = db::repos::get_by_name(&state.pool, &owner, &name)
+        .await
+        .db_err()?
+        .not_found(format!("Repository {}/{} not found", owner, name))?;
+
+    info!(
+        "Untrack requested for {}/{} ({:?}, revoke achievements: {})",
+        owner, name, params.mode, params.revoke_achievements
+    );
+
+    let stats = processor::untrack_repo(
+        &state.pool,
+        repo.id,
+        params.mode,
+        params.revoke_achievements,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(format!("Untrack failed: {}", e)))?;
+
+    Ok(Json(UntrackResponse {
+        repo: format!("{}/{}", owner, name),
+        mode: stats.mode,
+        affected_users: stats.affected_users,
+        prs_deleted: stats.prs_deleted,
+        reviews_deleted: stats.reviews_deleted,
+        comments_deleted: stats.comments_deleted,
+        commits_deleted: stats.commits_deleted,
+        xp_recalculated: stats.recalculation.is_some(),
+        achievements_stale: stats.achievements_stale.len(),
+        achievements_revoked: stats.achievements_revoked.len(),
+    }))
+}
@@ -0,0 +1,250 @@
+//! Scoring profile routes
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use common::models::{ScoringProfile, ScoringWeights};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::error::{ApiError, ApiResult, DbResultExt, OptionExt};
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct AssignProfileRequest {
+    /// Profile name (null = default)
+    pub profile: Option<String>,
+}
+
+/// List all scoring profiles
+/// GET /api/scoring-profiles
+pub async fn list(State(state): State<Arc<AppState>>) -> ApiResult<Json<Vec<ScoringProfile>>> {
+    let profiles = db::scoring_profiles::list(&state.pool).await.db_err()?;
+    Ok(Json(profiles))
+}
+
+/// Get a scoring profile by name
+/// GET /api/scoring-profiles/:name
+pub async fn get(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<ScoringProfile>> {
+    let profile = db::scoring_profiles::get_by_name(&state.pool, &name)
+        .await
+        .db_err()?
+        .not_found(format!("Scoring profile '{}' not found", name))?;
+
+    Ok(Json(profile))
+}
+
+/// Create a scoring profile. Omitted weights take the default values.
+/// POST /api/scoring-profiles
+pub async fn create(
+    State(state): State<Arc<AppState>>,
+    Json(mut body): Json<Map<String, Value>>,
+) -> ApiResult<(StatusCode, Json<ScoringProfile>)> {
+    let name = match body.remove("name") {
+        Some(Value::String(name)) => name,
+        _ => return Err(ApiError::BadRequest("name is required".to_string())),
+    };
+    validate_name(&name)?;
+    let description = take_description(&mut body)?.flatten();
+    let weights = merge_weights(&ScoringWeights::default(), body)?;
+
+    if db::scoring_profiles::get_by_name(&state.pool, &name)
+        .await
+        .db_err()?
+        .is_some()
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Scoring profile '{}' already exists",
+            name
+        )));
+    }
+
+    info!("Creating scoring profile '{}': {:?}", name, weights);
+    let profile =
+        db::scoring_profiles::create(&state.pool, &name, description.as_deref(), &weights)
+            .await
+            .db_err()?;
+
+    Ok((StatusCode::CREATED, Json(profile)))
+}
+
+/// Update a scoring profile. Only the given fields change; repos using the
+/// profile are rescored in the background.
+/// PUT /api/scoring-profiles/:name
+pub async fn update(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(mut body): Json<Map<String, Value>>,
+) -> ApiResult<Json<ScoringProfile>> {
+    let profile = db::scoring_profiles::get_by_name(&state.pool, &name)
+        .await
+        .db_err()?
+        .not_found(format!("Scoring profile '{}' not found", name))?;
+
+    if body.contains_key("name") {
+        return Err(ApiError::BadRequest(
+            "Profiles cannot be renamed".to_string(),
+        ));
+    }
+    let description = take_description(&mut body)?.unwrap_or(profile.description);
+    let weights = merge_weights(&profile.weights, body)?;
+
+    info!("Updating scoring profile '{}': {:?}", name, weights);
+    let profile =
+        db::scoring_profiles::update(&state.pool, profile.id, description.as_deref(), &weights)
+            .await
+            .db_err()?;
+
+    spawn_recalculation(state.pool.clone());
+    Ok(Json(profile))
+}
+
+/// Delete a scoring profile. Repos using it fall back to the default.
+/// DELETE /api/scoring-profiles/:name
+pub async fn delete(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> ApiResult<StatusCode> {
+    if name == db::scoring_profiles::DEFAULT_PROFILE {
+        return Err(ApiError::BadRequest(
+            "The default profile cannot be deleted".to_string(),
+        ));
+    }
+
+    let profile = db::scoring_profiles::get_by_name(&state.pool, &name)
+        .await
+        .db_err()?
+        .not_found(format!("Scoring profile '{}' not found", name))?;
+
+    db::scoring_profiles::delete(&state.pool, profile.id)
+        .await
+        .db_err()?;
+
+    spawn_recalculation(state.pool.clone());
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Get the scoring profile a repository uses
+/// GET /api/repos/:owner/:name/scoring-profile
+pub async fn get_for_repo(
+    State(state): State<Arc<AppState>>,
+    Path((owner, name)): Path<(String, String)>,
+) -> ApiResult<Json<ScoringProfile>> {
+    let repo = db::repos::get_by_name(&state.pool, &owner, &name)
+        .await
+        .db_err()?
+        .not_found(format!("Repository {}/{} not found", owner, name))?;
+
+    let profile = db::scoring_profiles::get_for_repo(&state.pool, repo.id)
+        .await
+        .db_err()?
+        .not_found(format!("Repository {}/{} not found", owner, name))?;
+
+    Ok(Json(profile))
+}
+
+/// Assign a scoring profile to a repository and rescore it in the background
+/// PUT /api/repos/:owner/:name/scoring-profile
+pub async fn assign_to_repo(
+    State(state): State<Arc<AppState>>,
+    Path((owner, name)): Path<(String, String)>,
+    Json(req): Json<AssignProfileRequest>,
+) -> ApiResult<Json<ScoringProfile>> {
+    let repo = db::repos::get_by_name(&state.pool, &owner, &name)
+        .await
+        .db_err()?
+        .not_found(format!("Repository {}/{} not found", owner, name))?;
+
+    let profile_id = match req.profile.as_deref() {
+        None | Some(db::scoring_profiles::DEFAULT_PROFILE) => None,
+        Some(profile_name) => Some(
+            db::scoring_profiles::get_by_name(&state.pool, profile_name)
+                .await
+                .db_err()?
+                .not_found(format!("Scoring profile '{}' not found", profile_name))?
+                .id,
+        ),
+    };
+
+    info!(
+        "Assigning scoring profile {:?} to {}/{}",
+        req.profile, owner, name
+    );
+    db::scoring_profiles::assign_to_repo(&state.pool, repo.id, profile_id)
+        .await
+        .db_err()?;
+
+    spawn_recalculation(state.pool.clone());
+
+    let profile = db::scoring_profiles::get_for_repo(&state.pool, repo.id)
+        .await
+        .db_err()?
+        .not_found(format!("Repository {}/{} not found", owner, name))?;
+
+    Ok(Json(profile))
+}
+
+/// Profile names are used in URLs: lowercase letters, digits, '-' and '_'
+fn validate_name(name: &str) -> ApiResult<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid {
+        return Err(ApiError::BadRequest(
+            "name must be 1-64 characters of a-z, 0-9, '-' or '_'".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Remove `description` from the body: None = absent, Some(None) = cleared
+fn take_description(body: &mut Map<String, Value>) -> ApiResult<Option<Option<String>>> {
+    match body.remove("description") {
+        None => Ok(None),
+        Some(Value::Null) => Ok(Some(None)),
+        Some(Value::String(description)) => Ok(Some(Some(description))),
+        Some(_) => Err(ApiError::BadRequest(
+            "description must be a string".to_string(),
+        )),
+    }
+}
+
+/// Overlay the given fields on `base` and validate the result
+pub(crate) fn merge_weights(
+    base: &ScoringWeights,
+    patch: Map<String, Value>,
+) -> ApiResult<ScoringWeights> {
+    let mut merged = serde_json::to_value(base).map_err(|e| ApiError::Internal(e.to_string()))?;
+    if let Value::Object(fields) = &mut merged {
+        fields.extend(patch);
+    }
+
+    let weights: ScoringWeights = serde_json::from_value(merged)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid scoring weights: {}", e)))?;
+    weights.validate().map_err(ApiError::BadRequest)?;
+
+    Ok(weights)
+}
+
+/// Rescore affected reviews without blocking the request
+fn spawn_recalculation(pool: PgPool) {
+    tokio::spawn(async move {
+        if let Err(e) = processor::recalculate_incremental(&pool).await {
+            error!(
+                "XP recalculation after scoring profile change failed: {}",
+                e
+            );
+        }
+    });
+}
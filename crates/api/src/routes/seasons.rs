@@ -16,6 +16,8 @@ use crate::state::AppState;
 pub struct LeaderboardParams {
     #[serde(default = "default_limit")]
     pub limit: i32,
+    /// "decayed" ranks by the season's XP weighted by age at the season's end
+    pub period: Option<String>,
 }
 
 fn default_limit() -> i32 {
@@ -57,9 +59,11 @@ pub async fn leaderboard(
         .db_err()?
         .ok_or_else(|| ApiError::NotFound(format!("Season {} not found", number)))?;
 
-    let entries = db::seasons::get_season_leaderboard(&state.pool, season.id, None, params.limit)
-        .await
-        .db_err()?;
+    let decayed = params.period.as_deref() == Some("decayed");
+    let entries =
+        db::seasons::get_season_leaderboard(&state.pool, season.id, None, decayed, params.limit)
+            .await
+            .db_err()?;
 
     Ok(Json(serde_json::json!({
         "season": season,
@@ -8,11 +8,15 @@ use axum::{
     Json,
 };
 use chrono::{Duration, Utc};
-use common::models::{Team, TeamLeaderboardEntry};
+use common::models::{Team, TeamLeaderboardEntry, WorkCalendar};
+use db::leaderboard::Ranking;
+use processor::latency::Calendar;
 use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::{error, info};
 use uuid::Uuid;
 
-use crate::error::{ApiResult, DbResultExt, OptionExt};
+use crate::error::{ApiError, ApiResult, DbResultExt, OptionExt};
 use crate::state::AppState;
 
 #[derive(Debug, Deserialize)]
@@ -127,6 +131,7 @@ pub async fn add_member(
     db::teams::add_member(&state.pool, team.id, user.id)
         .await
         .db_err()?;
+    rescore_if_calendar(&state, team.id, user.id).await?;
 
     Ok(StatusCode::CREATED)
 }
@@ -149,24 +154,111 @@ pub async fn remove_member(
     db::teams::remove_member(&state.pool, team.id, user.id)
         .await
         .db_err()?;
+    rescore_if_calendar(&state, team.id, user.id).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// A team's working calendar
+/// GET /api/teams/:name/calendar
+pub async fn get_calendar(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> ApiResult<Json<WorkCalendar>> {
+    let team = db::teams::get_team_by_name(&state.pool, &name)
+        .await
+        .db_err()?
+        .not_found(format!("Team '{}' not found", name))?;
+
+    let calendar = db::calendars::get(&state.pool, team.id)
+        .await
+        .db_err()?
+        .not_found(format!("Team '{}' has no calendar", name))?;
+    Ok(Json(calendar))
+}
+
+/// Set a team's working calendar and rescore its members in the background
+/// PUT /api/teams/:name/calendar
+pub async fn set_calendar(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(calendar): Json<WorkCalendar>,
+) -> ApiResult<Json<WorkCalendar>> {
+    let team = db::teams::get_team_by_name(&state.pool, &name)
+        .await
+        .db_err()?
+        .not_found(format!("Team '{}' not found", name))?;
+    Calendar::new(&calendar).map_err(ApiError::BadRequest)?;
+
+    db::calendars::set(&state.pool, team.id, &calendar)
+        .await
+        .db_err()?;
+    info!("Calendar of team '{}' set to {:?}", name, calendar);
+    spawn_recalculation(state.pool.clone());
+
+    Ok(Json(calendar))
+}
+
+/// Remove a team's calendar (members are measured around the clock again)
+/// DELETE /api/teams/:name/calendar
+pub async fn delete_calendar(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> ApiResult<StatusCode> {
+    let team = db::teams::get_team_by_name(&state.pool, &name)
+        .await
+        .db_err()?
+        .not_found(format!("Team '{}' not found", name))?;
+
+    let deleted = db::calendars::delete(&state.pool, team.id).await.db_err()?;
+    if !deleted {
+        return Err(ApiError::NotFound(format!(
+            "Team '{}' has no calendar",
+            name
+        )));
+    }
+    spawn_recalculation(state.pool.clone());
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Joining or leaving a team with a calendar changes how the user's review
+/// latency is measured
+async fn rescore_if_calendar(state: &AppState, team_id: Uuid, user_id: Uuid) -> ApiResult<()> {
+    let calendar = db::calendars::get(&state.pool, team_id).await.db_err()?;
+    if calendar.is_some() {
+        db::calendars::touch_user_reviews(&state.pool, user_id)
+            .await
+            .db_err()?;
+        spawn_recalculation(state.pool.clone());
+    }
+    Ok(())
+}
+
+/// Rescore affected reviews without blocking the request
+fn spawn_recalculation(pool: PgPool) {
+    tokio::spawn(async move {
+        if let Err(e) = processor::recalculate_incremental(&pool).await {
+            error!("XP recalculation after calendar change failed: {}", e);
+        }
+    });
+}
+
 /// Get team leaderboard
 pub async fn leaderboard(
     State(state): State<Arc<AppState>>,
     Query(params): Query<LeaderboardQuery>,
 ) -> ApiResult<Json<Vec<TeamLeaderboardEntry>>> {
-    let since = match params.period.as_deref() {
-        Some("week") => Utc::now() - Duration::days(7),
-        Some("month") => Utc::now() - Duration::days(30),
-        _ => Utc::now() - Duration::days(365 * 10), // "all" = 10 years
+    let ranking = match params.period.as_deref() {
+        Some("week") => Ranking::Since(Utc::now() - Duration::days(7)),
+        Some("month") => Ranking::Since(Utc::now() - Duration::days(30)),
+        Some("decayed") => Ranking::Decayed,
+        _ => Ranking::Since(Utc::now() - Duration::days(365 * 10)), // "all" = 10 years
     };
 
     let limit = params.limit.unwrap_or(50);
 
-    let leaderboard = db::teams::get_team_leaderboard(&state.pool, None, since, limit)
+    let leaderboard = db::teams::get_team_leaderboard(&state.pool, None, ranking, limit)
         .await
         .db_err()?;
 
@@ -10,7 +10,7 @@ use std::sync::Arc;
 
 use crate::error::{ApiResult, DbResultExt, OptionExt};
 use crate::state::AppState;
-use common::models::{User, UserAchievement, UserStats};
+use common::models::{AchievementScope, User, UserAchievement, UserExpertise, UserStats};
 
 /// Path parameters for repo-scoped user endpoints
 #[derive(Deserialize)]
@@ -53,6 +53,29 @@ pub struct ReviewsQuery {
     pub limit: i64,
 }
 
+#[derive(Deserialize)]
+pub struct LedgerQuery {
+    /// Only entries for this PR number (repo-scoped endpoint)
+    pub pr: Option<i32>,
+    #[serde(default = "default_ledger_limit")]
+    pub limit: i64,
+}
+
+#[derive(Serialize)]
+pub struct XpLedger {
+    pub login: String,
+    /// Net XP across all matching entries (not just the returned page)
+    pub net_xp: i64,
+    pub entries: Vec<db::xp_ledger::LedgerEntry>,
+}
+
+#[derive(Deserialize)]
+pub struct ExpertiseQuery {
+    /// Areas returned of each kind
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
 #[derive(Deserialize)]
 pub struct StatsQuery {
     #[serde(default = "default_period")]
@@ -67,6 +90,10 @@ fn default_limit() -> i64 {
     10
 }
 
+fn default_ledger_limit() -> i64 {
+    50
+}
+
 fn period_to_since(period: &str) -> chrono::DateTime<Utc> {
     match period {
         "week" => Utc::now() - Duration::days(7),
@@ -110,9 +137,13 @@ pub async fn stats(
         .db_err()?;
 
     // Get full user stats for the period
-    let stats = db::users::get_stats(&state.pool, user.id, since)
+    let mut stats = db::users::get_stats(&state.pool, user.id, since)
         .await
         .db_err()?;
+    stats.avg_time_to_first_review_secs =
+        processor::latency::avg_time_to_first_review_secs(&state.pool, user.id, None, since)
+            .await
+            .db_err()?;
 
     Ok(Json(UserProfile {
         user,
@@ -192,10 +223,14 @@ pub async fn repo_stats(
         .db_err()?
         .not_found(format!("User '{}' not found", path.username))?;
 
-    // Get achievements (global, not repo-scoped)
-    let achievements = db::achievements::list_for_user(&state.pool, user.id)
-        .await
-        .db_err()?;
+    // Get achievements unlocked in this repo
+    let achievements = db::achievements::list_for_user_in_scope(
+        &state.pool,
+        user.id,
+        AchievementScope::Repo(repo.id),
+    )
+    .await
+    .db_err()?;
 
     // Get rank within this repo
     let since = Utc::now() - Duration::days(365 * 10);
@@ -204,9 +239,17 @@ pub async fn repo_stats(
         .db_err()?;
 
     // Get stats scoped to this repo
-    let stats = db::users::get_stats_for_repo(&state.pool, user.id, Some(repo.id), since)
+    let mut stats = db::users::get_stats_for_repo(&state.pool, user.id, Some(repo.id), since)
         .await
         .db_err()?;
+    stats.avg_time_to_first_review_secs = processor::latency::avg_time_to_first_review_secs(
+        &state.pool,
+        user.id,
+        Some(repo.id),
+        since,
+    )
+    .await
+    .db_err()?;
 
     Ok(Json(UserProfile {
         user,
@@ -285,3 +328,94 @@ pub async fn repo_reviews(
             .collect(),
     ))
 }
+
+/// Itemized XP history for a user
+/// GET /api/users/:username/xp-ledger?limit=N
+pub async fn xp_ledger(
+    State(state): State<Arc<AppState>>,
+    Path(username): Path<String>,
+    Query(query): Query<LedgerQuery>,
+) -> ApiResult<Json<XpLedger>> {
+    let user = db::users::get_by_login(&state.pool, &username)
+        .await
+        .db_err()?
+        .not_found(format!("User '{}' not found", username))?;
+
+    let filter = db::xp_ledger::LedgerFilter {
+        repo_id: None,
+        pr_number: None,
+        limit: query.limit.clamp(1, 500),
+    };
+    ledger_response(&state, user, filter).await
+}
+
+/// Itemized XP history for a user in one repository, optionally for one PR
+/// GET /api/repos/:owner/:name/users/:username/xp-ledger?pr=N&limit=N
+pub async fn repo_xp_ledger(
+    State(state): State<Arc<AppState>>,
+    Path(path): Path<RepoUserPath>,
+    Query(query): Query<LedgerQuery>,
+) -> ApiResult<Json<XpLedger>> {
+    let repo = db::repos::get_by_name(&state.pool, &path.owner, &path.name)
+        .await
+        .db_err()?
+        .not_found(format!("Repository {}/{} not found", path.owner, path.name))?;
+
+    let user = db::users::get_by_login(&state.pool, &path.username)
+        .await
+        .db_err()?
+        .not_found(format!("User '{}' not found", path.username))?;
+
+    let filter = db::xp_ledger::LedgerFilter {
+        repo_id: Some(repo.id),
+        pr_number: query.pr,
+        limit: query.limit.clamp(1, 500),
+    };
+    ledger_response(&state, user, filter).await
+}
+
+async fn ledger_response(
+    state: &AppState,
+    user: User,
+    filter: db::xp_ledger::LedgerFilter,
+) -> ApiResult<Json<XpLedger>> {
+    let entries = db::xp_ledger::list_for_user(&state.pool, user.id, &filter)
+        .await
+        .db_err()?;
+    let net_xp = db::xp_ledger::net_for_user(&state.pool, user.id, &filter)
+        .await
+        .db_err()?;
+
+    Ok(Json(XpLedger {
+        login: user.login,
+        net_xp,
+        entries,
+    }))
+}
+
+/// Directories, extensions and languages a user reviews, from their review
+/// comments over the last year
+/// GET /api/users/:username/expertise?limit=N
+pub async fn expertise(
+    State(state): State<Arc<AppState>>,
+    Path(username): Path<String>,
+    Query(query): Query<ExpertiseQuery>,
+) -> ApiResult<Json<UserExpertise>> {
+    let user = db::users::get_by_login(&state.pool, &username)
+        .await
+        .db_err()?
+        .not_found(format!("User '{}' not found", username))?;
+
+    let now = Utc::now();
+    let since = processor::expertise::window_start(now);
+    let comments = db::expertise::path_comments(&state.pool, Some(user.id), None, since)
+        .await
+        .db_err()?;
+
+    Ok(Json(processor::expertise::user_expertise(
+        user,
+        &comments,
+        now,
+        query.limit.clamp(1, 100) as usize,
+    )))
+}
@@ -1,6 +1,7 @@
 //! Bot commands
 
 use chrono::{Duration, Utc};
+use db::leaderboard::Ranking;
 use serenity::model::channel::Message;
 use serenity::prelude::*;
 use sqlx::PgPool;
@@ -29,6 +30,14 @@ pub async fn handle(
             roast(ctx, msg, pool, username).await
         }
         Some(&"digest") | Some(&"weekly") => weekly_digest(ctx, msg, pool).await,
+        Some(&"reviewers") => {
+            // !rr reviewers owner/name [pr number]
+            let repo = parts.get(1).copied();
+            let number = parts
+                .get(2)
+                .and_then(|n| n.trim_start_matches('#').parse().ok());
+            reviewers(ctx, msg, pool, repo, number).await
+        }
         Some(&"help") => help(ctx, msg).await,
         _ => {
             msg.reply(&ctx.http, "Unknown command. Try `!rr help`")
@@ -55,7 +64,7 @@ async fn leaderboard(
         _ => (Utc::now() - Duration::days(30), "This Month"), // Default: month
     };
 
-    let entries = db::leaderboard::get_leaderboard(pool, None, since, 10).await?;
+    let entries = db::leaderboard::get_leaderboard(pool, None, Ranking::Since(since), 10).await?;
 
     if entries.is_empty() {
         msg.reply(&ctx.http, "No reviews yet! Get reviewing! 🔍")
@@ -309,7 +318,7 @@ async fn weekly_digest(
     let since = Utc::now() - Duration::days(7);
 
     // Get top 5 for the week
-    let entries = db::leaderboard::get_leaderboard(pool, None, since, 5).await?;
+    let entries = db::leaderboard::get_leaderboard(pool, None, Ranking::Since(since), 5).await?;
 
     if entries.is_empty() {
         msg.reply(&ctx.http, "📭 No reviews this week. Everyone on vacation?")
@@ -380,6 +389,71 @@ async fn weekly_digest(
     Ok(())
 }
 
+async fn reviewers(
+    ctx: &Context,
+    msg: &Message,
+    pool: &PgPool,
+    repo: Option<&str>,
+    number: Option<i32>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    info!("Reviewers command for {:?} from {}", repo, msg.author.name);
+
+    let Some((owner, name)) = repo.and_then(|r| r.split_once('/')) else {
+        msg.reply(&ctx.http, "Usage: `!rr reviewers owner/repo [pr number]`")
+            .await?;
+        return Ok(());
+    };
+    let Some(repo) = db::repos::get_by_name(pool, owner, name).await? else {
+        msg.reply(
+            &ctx.http,
+            format!("Repository `{}/{}` not tracked", owner, name),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let prs = db::prs::list_open_with_stats(pool, repo.id).await?;
+    let calendars = processor::latency::Calendars::load(pool).await?;
+    let suggestions =
+        processor::recommend::recommend_for_prs(pool, repo.id, &prs, &calendars, 3).await?;
+    let mut prs: Vec<_> = prs
+        .into_iter()
+        .filter(|pr| number.is_none_or(|n| pr.number == n))
+        .filter_map(|pr| suggestions.get(&pr.id).map(|s| (pr, s)))
+        .collect();
+    prs.sort_by_key(|(pr, _)| pr.created_at);
+
+    if prs.is_empty() {
+        let reply = match number {
+            Some(n) => format!("PR #{} is not open or already approved ✅", n),
+            None => "Nothing waiting for review! 🎉".to_string(),
+        };
+        msg.reply(&ctx.http, reply).await?;
+        return Ok(());
+    }
+
+    let mut response = format!("🕵️ **Suggested Reviewers** ({}/{})\n\n", owner, name);
+    let lines = prs.iter().map(|(pr, picks)| {
+        let picks = if picks.is_empty() {
+            "no candidates yet".to_string()
+        } else {
+            picks
+                .iter()
+                .map(|s| format!("{} ({} open)", s.login, s.open_reviews))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        format!(
+            "**#{}** {} by {} — {}\n",
+            pr.number, pr.title, pr.author_login, picks
+        )
+    });
+    crate::push_capped(&mut response, lines, 10, prs.len());
+
+    msg.reply(&ctx.http, response).await?;
+    Ok(())
+}
+
 async fn help(
     ctx: &Context,
     msg: &Message,
@@ -389,6 +463,7 @@ async fn help(
         `!rr stats [username]` — Show user stats\n\
         `!rr roast [username]` — Roast a reviewer 🔥\n\
         `!rr digest` — Weekly digest summary 📊\n\
+        `!rr reviewers owner/repo [pr]` — Suggest reviewers for open PRs 🕵️\n\
         `!rr help` — Show this help\n\n\
         **Scoring:** Reviews earn XP based on depth and speed. More comments = more XP! 🔥";
 
@@ -1,5 +1,6 @@
 //! Review Royale Discord Bot
 
+use common::models::WaitingOn;
 use serenity::async_trait;
 use serenity::model::channel::Message;
 use serenity::model::gateway::Ready;
@@ -12,6 +13,36 @@ use tracing::{error, info, warn};
 
 mod commands;
 
+/// Longest message Discord accepts
+const DISCORD_MESSAGE_LIMIT: usize = 2000;
+
+/// Most stale PRs listed in one reminder
+const STALE_PRS_PER_MESSAGE: usize = 10;
+
+/// Append up to `max` of `lines` to `message`, stopping early rather than go
+/// over Discord's limit, then "…and N more" for the rest of `total`. Returns
+/// the number of lines added (the first one always is).
+fn push_capped(
+    message: &mut String,
+    lines: impl IntoIterator<Item = String>,
+    max: usize,
+    total: usize,
+) -> usize {
+    let mut added = 0;
+    for line in lines.into_iter().take(max) {
+        // Keep room for the "…and N more" line
+        if added > 0 && message.len() + line.len() > DISCORD_MESSAGE_LIMIT - 40 {
+            break;
+        }
+        message.push_str(&line);
+        added += 1;
+    }
+    if total > added {
+        message.push_str(&format!("…and {} more\n", total - added));
+    }
+    added
+}
+
 struct Bot {
     pool: PgPool,
 }
@@ -51,8 +82,15 @@ impl EventHandler for Bot {
         // Start achievement notification loop
         let ctx = Arc::new(ctx);
         let pool = self.pool.clone();
+        let achievement_ctx = ctx.clone();
+        tokio::spawn(async move {
+            achievement_notification_loop(achievement_ctx, pool).await;
+        });
+
+        // Start stale PR reminder loop
+        let pool = self.pool.clone();
         tokio::spawn(async move {
-            achievement_notification_loop(ctx, pool).await;
+            stale_pr_loop(ctx, pool).await;
         });
     }
 }
@@ -130,6 +168,97 @@ async fn achievement_notification_loop(ctx: Arc<Context>, pool: PgPool) {
     }
 }
 
+/// Background loop that posts reminders about stale PRs, tagging whoever each
+/// PR is waiting on
+async fn stale_pr_loop(ctx: Arc<Context>, pool: PgPool) {
+    let channel_id = match std::env::var("DISCORD_NOTIFICATION_CHANNEL") {
+        Ok(id) => match id.parse::<u64>() {
+            Ok(id) => ChannelId::new(id),
+            Err(_) => {
+                warn!("Invalid DISCORD_NOTIFICATION_CHANNEL, stale PR reminders disabled");
+                return;
+            }
+        },
+        Err(_) => {
+            info!("DISCORD_NOTIFICATION_CHANNEL not set, stale PR reminders disabled");
+            return;
+        }
+    };
+
+    info!("🕸️ Stale PR reminders enabled for channel {}", channel_id);
+
+    // Check every hour
+    let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+
+    loop {
+        interval.tick().await;
+
+        let repos = match db::repos::list(&pool).await {
+            Ok(repos) => repos,
+            Err(e) => {
+                error!("Failed to list repos for stale PR reminders: {}", e);
+                continue;
+            }
+        };
+
+        for repo in repos {
+            let due = match processor::stale::due_alerts(&pool, &repo).await {
+                Ok(due) => due,
+                Err(e) => {
+                    error!(
+                        "Failed to find stale PRs in {}/{}: {}",
+                        repo.owner, repo.name, e
+                    );
+                    continue;
+                }
+            };
+            if due.is_empty() {
+                continue;
+            }
+
+            // Discord rejects messages over 2000 characters. PRs left out stay
+            // due and are posted on a later run.
+            let mut message = format!("🕸️ **Stale PRs** in {}/{}\n\n", repo.owner, repo.name);
+            let lines = due.iter().map(|pr| {
+                let nudge = match (&pr.nudge, pr.waiting_on) {
+                    (Some(login), _) => format!("@{}", login),
+                    (None, WaitingOn::Reviewer) => "needs a reviewer".to_string(),
+                    (None, WaitingOn::Author) => format!("@{}", pr.author),
+                };
+                format!(
+                    "• [#{}]({}) {} — idle {} days, waiting on {}\n",
+                    pr.number, pr.url, pr.title, pr.idle_days, nudge
+                )
+            });
+            let posted = &due[..push_capped(&mut message, lines, STALE_PRS_PER_MESSAGE, due.len())];
+
+            match channel_id.say(&ctx.http, &message).await {
+                Ok(_) => {
+                    info!(
+                        "Posted {} of {} stale PR reminders for {}/{}",
+                        posted.len(),
+                        due.len(),
+                        repo.owner,
+                        repo.name
+                    );
+                    let now = chrono::Utc::now();
+                    for pr in posted {
+                        if let Err(e) = db::stale::mark_alerted(&pool, pr.pr_id, now).await {
+                            error!("Failed to record stale PR reminder: {}", e);
+                        }
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to post stale PR reminders: {}", e);
+                }
+            }
+
+            // Small delay between repos to avoid rate limits
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Load .env file
@@ -1,6 +1,6 @@
 //! Domain models
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -139,6 +139,60 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_default_scoring_weights_are_valid() {
+        assert_eq!(ScoringWeights::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_scoring_weights_reject_negative_xp() {
+        let weights = ScoringWeights {
+            logic_bonus: -1,
+            ..Default::default()
+        };
+        assert!(weights.validate().unwrap_err().contains("logic_bonus"));
+    }
+
+    #[test]
+    fn test_scoring_weights_reject_zero_session_gap() {
+        let weights = ScoringWeights {
+            session_gap_hours: 0,
+            ..Default::default()
+        };
+        assert!(weights.validate().is_err());
+    }
+
+    #[test]
+    fn test_scoring_weights_deep_below_thorough() {
+        let weights = ScoringWeights {
+            thorough_threshold: 8,
+            deep_threshold: 5,
+            ..Default::default()
+        };
+        assert!(weights.validate().unwrap_err().contains("deep_threshold"));
+    }
+
+    #[test]
+    fn test_scoring_weights_reject_unknown_fields() {
+        let mut json = serde_json::to_value(ScoringWeights::default()).unwrap();
+        assert!(serde_json::from_value::<ScoringWeights>(json.clone()).is_ok());
+
+        json["typo_xp"] = serde_json::json!(1);
+        assert!(serde_json::from_value::<ScoringWeights>(json).is_err());
+    }
+
+    #[test]
+    fn test_achievement_metric_names_match_serde() {
+        for metric in AchievementMetric::ALL {
+            assert_eq!(
+                serde_json::to_value(metric).unwrap(),
+                serde_json::json!(metric.as_str())
+            );
+            assert_eq!(AchievementMetric::parse(metric.as_str()), Some(metric));
+        }
+        assert_eq!(AchievementMetric::parse("night_owl"), None);
+    }
 }
 
 /// A tracked GitHub repository
@@ -151,6 +205,218 @@ pub struct Repository {
     pub created_at: DateTime<Utc>,
 }
 
+/// Per-repository sync configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RepoSyncSettings {
+    /// Whether the scheduler syncs this repo (false = paused)
+    pub enabled: bool,
+    /// Hours between syncs (None = global SYNC_INTERVAL_HOURS)
+    pub interval_hours: Option<i32>,
+    /// How far back the initial sync looks (days)
+    pub max_age_days: i32,
+    /// Higher priority repos are synced first
+    pub priority: i32,
+    /// Fetch PR commits (needed for session boundaries)
+    pub fetch_commits: bool,
+    /// Fetch review comments (needed for quality scoring)
+    pub fetch_comments: bool,
+    /// Days without activity before an open PR counts as stale
+    pub stale_after_days: i32,
+}
+
+impl Default for RepoSyncSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_hours: None,
+            max_age_days: 365,
+            priority: 0,
+            fetch_commits: true,
+            fetch_comments: true,
+            stale_after_days: 7,
+        }
+    }
+}
+
+/// A named set of XP weights, assignable per repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoringProfile {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(flatten)]
+    pub weights: ScoringWeights,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// XP constants used to score review sessions
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ScoringWeights {
+    /// XP for any session that passes the rubber-stamp check
+    pub base_xp: i32,
+    /// Per comment with quality 1-3
+    pub low_quality_xp: i32,
+    /// Per comment with quality 4-6
+    pub medium_quality_xp: i32,
+    /// Per comment with quality 7-10
+    pub high_quality_xp: i32,
+    /// Per comment not yet categorized
+    pub uncategorized_xp: i32,
+    /// Extra per logic comment
+    pub logic_bonus: i32,
+    /// Extra per structural comment
+    pub structural_bonus: i32,
+    /// Extra per comment addressed by a follow-up commit
+    pub addressed_bonus: i32,
+    /// Bonus for reviewing soon after a push
+    pub fast_review_bonus: i32,
+    /// "Soon" for the fast review bonus
+    pub fast_review_minutes: i32,
+    /// More than this many comments earns the thorough bonus
+    pub thorough_threshold: i32,
+    pub thorough_bonus: i32,
+    /// More than this many comments earns the deep bonus
+    pub deep_threshold: i32,
+    pub deep_bonus: i32,
+    /// Gap between reviews that starts a new session
+    pub session_gap_hours: i32,
+    /// Comments shorter than this earn no comment XP
+    pub min_comment_chars: i32,
+    /// Cap of the PR difficulty multiplier on base and comment XP, in percent
+    /// (100 turns it off)
+    pub difficulty_max_pct: i32,
+    /// Lines changed at which the size half of the multiplier maxes out
+    pub difficulty_lines: i32,
+    /// Files changed at which the spread half of the multiplier maxes out
+    pub difficulty_files: i32,
+}
+
+impl Default for ScoringWeights {
+    fn default() -> Self {
+        Self {
+            base_xp: 10,
+            low_quality_xp: 2,
+            medium_quality_xp: 5,
+            high_quality_xp: 8,
+            uncategorized_xp: 5,
+            logic_bonus: 3,
+            structural_bonus: 2,
+            addressed_bonus: 5,
+            fast_review_bonus: 10,
+            fast_review_minutes: 60,
+            thorough_threshold: 5,
+            thorough_bonus: 5,
+            deep_threshold: 10,
+            deep_bonus: 10,
+            session_gap_hours: 24,
+            min_comment_chars: 20,
+            difficulty_max_pct: 150,
+            difficulty_lines: 500,
+            difficulty_files: 20,
+        }
+    }
+}
+
+impl ScoringWeights {
+    /// Largest gap between reviews that still counts as one session
+    pub fn session_gap(&self) -> Duration {
+        Duration::hours(self.session_gap_hours as i64)
+    }
+
+    /// Check that all values are in a sane range
+    pub fn validate(&self) -> Result<(), String> {
+        let xp_fields = [
+            ("base_xp", self.base_xp),
+            ("low_quality_xp", self.low_quality_xp),
+            ("medium_quality_xp", self.medium_quality_xp),
+            ("high_quality_xp", self.high_quality_xp),
+            ("uncategorized_xp", self.uncategorized_xp),
+            ("logic_bonus", self.logic_bonus),
+            ("structural_bonus", self.structural_bonus),
+            ("addressed_bonus", self.addressed_bonus),
+            ("fast_review_bonus", self.fast_review_bonus),
+            ("thorough_bonus", self.thorough_bonus),
+            ("deep_bonus", self.deep_bonus),
+        ];
+        for (field, value) in xp_fields {
+            if !(0..=1000).contains(&value) {
+                return Err(format!("{} must be between 0 and 1000", field));
+            }
+        }
+        if !(1..=24 * 60).contains(&self.fast_review_minutes) {
+            return Err("fast_review_minutes must be between 1 and 1440".to_string());
+        }
+        if !(1..=24 * 30).contains(&self.session_gap_hours) {
+            return Err("session_gap_hours must be between 1 and 720".to_string());
+        }
+        if !(0..=1000).contains(&self.thorough_threshold) {
+            return Err("thorough_threshold must be between 0 and 1000".to_string());
+        }
+        if self.deep_threshold < self.thorough_threshold || self.deep_threshold > 1000 {
+            return Err("deep_threshold must be between thorough_threshold and 1000".to_string());
+        }
+        if !(0..=1000).contains(&self.min_comment_chars) {
+            return Err("min_comment_chars must be between 0 and 1000".to_string());
+        }
+        if !(100..=300).contains(&self.difficulty_max_pct) {
+            return Err("difficulty_max_pct must be between 100 and 300".to_string());
+        }
+        if !(1..=100_000).contains(&self.difficulty_lines) {
+            return Err("difficulty_lines must be between 1 and 100000".to_string());
+        }
+        if !(1..=10_000).contains(&self.difficulty_files) {
+            return Err("difficulty_files must be between 1 and 10000".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Itemized XP for one award (a review session or an achievement reward)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct XpBreakdown {
+    pub base: i32,
+    pub comments_low: i32,
+    pub comments_medium: i32,
+    pub comments_high: i32,
+    pub comments_uncategorized: i32,
+    pub logic_bonus: i32,
+    pub structural_bonus: i32,
+    pub addressed_bonus: i32,
+    pub fast_bonus: i32,
+    pub thorough_bonus: i32,
+    pub deep_bonus: i32,
+    /// Extra base and comment XP from the PR difficulty multiplier
+    pub difficulty_bonus: i32,
+    /// The multiplier itself, in percent (not XP, left out of the total)
+    pub difficulty_pct: i32,
+    pub achievement: i32,
+}
+
+impl XpBreakdown {
+    /// Sum of all XP components
+    pub fn total(&self) -> i64 {
+        [
+            self.base,
+            self.comments_low,
+            self.comments_medium,
+            self.comments_high,
+            self.comments_uncategorized,
+            self.logic_bonus,
+            self.structural_bonus,
+            self.addressed_bonus,
+            self.fast_bonus,
+            self.thorough_bonus,
+            self.deep_bonus,
+            self.difficulty_bonus,
+            self.achievement,
+        ]
+        .iter()
+        .map(|&v| v as i64)
+        .sum()
+    }
+}
+
 /// A GitHub user
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
@@ -180,6 +446,21 @@ pub struct PullRequest {
     pub closed_at: Option<DateTime<Utc>>,
 }
 
+/// Lines and files a PR changes, as reported by GitHub
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PrSize {
+    pub additions: i32,
+    pub deletions: i32,
+    pub changed_files: i32,
+}
+
+impl PrSize {
+    /// Lines added plus lines deleted
+    pub fn lines_changed(&self) -> i64 {
+        self.additions as i64 + self.deletions as i64
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum PrState {
@@ -244,12 +525,148 @@ pub enum AchievementRarity {
     Legendary,
 }
 
+impl AchievementRarity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Common => "common",
+            Self::Uncommon => "uncommon",
+            Self::Rare => "rare",
+            Self::Epic => "epic",
+            Self::Legendary => "legendary",
+        }
+    }
+}
+
+/// What an achievement rule counts for a user
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AchievementMetric {
+    /// Reviews submitted
+    ReviewCount,
+    /// Review sessions that earned the fast review bonus
+    FastReviews,
+    /// PRs where the user submitted the first review
+    FirstReviews,
+    /// Review comments categorized as `nit`
+    NitComments,
+    /// Review comments categorized as `logic` (bugs caught)
+    BugCatches,
+    /// Review comments addressed by a later commit touching the commented
+    /// lines before merge (verified catches)
+    AddressedComments,
+    /// Review comments in the rule's category
+    CategoryComments,
+    /// Reviews with 10+ comments
+    DeepReviews,
+    /// Longest run of consecutive days with at least one review
+    LongestStreakDays,
+    /// Reviews submitted after 30+ days without reviewing
+    Comebacks,
+    /// Most distinct PRs reviewed on a single day
+    MaxPrsReviewedInDay,
+    /// Other people's PRs the user approved that were merged
+    ApprovedMerges,
+    /// Review threads the user started that the PR author thanked them on
+    ThanksReplies,
+    /// PRs authored
+    PrsAuthored,
+    /// Authored PRs that were merged
+    PrsMerged,
+    /// Change requests on the user's PRs followed by a push within 24 hours
+    QuickFixes,
+    /// Review threads on the user's PRs the user replied to
+    ThreadsReplied,
+}
+
+impl AchievementMetric {
+    pub const ALL: [AchievementMetric; 17] = [
+        Self::ReviewCount,
+        Self::FastReviews,
+        Self::FirstReviews,
+        Self::NitComments,
+        Self::BugCatches,
+        Self::AddressedComments,
+        Self::CategoryComments,
+        Self::DeepReviews,
+        Self::LongestStreakDays,
+        Self::Comebacks,
+        Self::MaxPrsReviewedInDay,
+        Self::ApprovedMerges,
+        Self::ThanksReplies,
+        Self::PrsAuthored,
+        Self::PrsMerged,
+        Self::QuickFixes,
+        Self::ThreadsReplied,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ReviewCount => "review_count",
+            Self::FastReviews => "fast_reviews",
+            Self::FirstReviews => "first_reviews",
+            Self::NitComments => "nit_comments",
+            Self::BugCatches => "bug_catches",
+            Self::AddressedComments => "addressed_comments",
+            Self::CategoryComments => "category_comments",
+            Self::DeepReviews => "deep_reviews",
+            Self::LongestStreakDays => "longest_streak_days",
+            Self::Comebacks => "comebacks",
+            Self::MaxPrsReviewedInDay => "max_prs_reviewed_in_day",
+            Self::ApprovedMerges => "approved_merges",
+            Self::ThanksReplies => "thanks_replies",
+            Self::PrsAuthored => "prs_authored",
+            Self::PrsMerged => "prs_merged",
+            Self::QuickFixes => "quick_fixes",
+            Self::ThreadsReplied => "threads_replied",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.as_str() == s)
+    }
+
+    /// Whether the metric is about PRs the user authored (vs. reviewed)
+    pub fn is_author_metric(&self) -> bool {
+        matches!(
+            self,
+            Self::PrsAuthored | Self::PrsMerged | Self::QuickFixes | Self::ThreadsReplied
+        )
+    }
+}
+
+/// Where a scoped achievement is tracked: activity in one repository, or
+/// reviews of PRs authored by one team's members
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(tag = "scope", content = "scope_id", rename_all = "lowercase")]
+pub enum AchievementScope {
+    Repo(Uuid),
+    Team(Uuid),
+}
+
+impl AchievementScope {
+    pub fn repo_id(&self) -> Option<Uuid> {
+        match self {
+            Self::Repo(id) => Some(*id),
+            Self::Team(_) => None,
+        }
+    }
+
+    pub fn team_id(&self) -> Option<Uuid> {
+        match self {
+            Self::Repo(_) => None,
+            Self::Team(id) => Some(*id),
+        }
+    }
+}
+
 /// A user's unlocked achievement
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserAchievement {
     pub user_id: Uuid,
     pub achievement_id: String,
     pub unlocked_at: DateTime<Utc>,
+    /// XP credited at unlock (the achievement's `xp_reward` at the time)
+    pub xp_awarded: i32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -280,10 +697,13 @@ pub struct UserStats {
     pub avg_time_to_first_review_secs: Option<f64>,
     pub avg_review_depth: Option<f64>,
     pub review_streak_days: i32,
-    /// XP earned in this period (sum of xp_earned from reviews)
+    /// XP earned in this period: xp_earned from reviews, plus achievement
+    /// rewards unlocked in the period when not scoped to a repo
     pub period_xp: i64,
     /// Number of review sessions (grouped by commit boundaries + time gaps)
     pub sessions: i32,
+    /// Review threads the user started that someone replied to
+    pub conversations_started: i32,
 }
 
 /// Leaderboard entry
@@ -295,6 +715,25 @@ pub struct LeaderboardEntry {
     pub stats: UserStats,
 }
 
+/// Author responsiveness leaderboard entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponsivenessEntry {
+    pub rank: i32,
+    pub user: User,
+    pub score: i64,
+    /// Change requests received on the user's PRs
+    pub change_requests: i32,
+    /// Change requests followed by a push
+    pub change_requests_addressed: i32,
+    /// Median hours from a change request to the next push
+    pub median_response_hours: Option<f64>,
+    /// Review threads started by others on the user's PRs
+    pub threads: i32,
+    pub threads_replied: i32,
+    /// Fraction of threads the user replied to
+    pub reply_rate: Option<f64>,
+}
+
 /// A team of reviewers
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Team {
@@ -305,6 +744,56 @@ pub struct Team {
     pub created_at: DateTime<Utc>,
 }
 
+/// A team's working hours: review latency only counts time inside them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct WorkCalendar {
+    /// IANA timezone, e.g. "Europe/Berlin"
+    pub timezone: String,
+    /// ISO weekdays worked (1 = Monday ... 7 = Sunday)
+    pub work_days: Vec<i32>,
+    /// Local hour the working day starts (0-23)
+    pub day_start_hour: i32,
+    /// Local hour the working day ends (1-24, after the start)
+    pub day_end_hour: i32,
+    /// Local dates not worked
+    pub holidays: Vec<NaiveDate>,
+}
+
+impl Default for WorkCalendar {
+    fn default() -> Self {
+        Self {
+            timezone: "UTC".to_string(),
+            work_days: vec![1, 2, 3, 4, 5],
+            day_start_hour: 9,
+            day_end_hour: 17,
+            holidays: Vec::new(),
+        }
+    }
+}
+
+impl WorkCalendar {
+    /// Check that all values are in a sane range (the timezone is checked
+    /// when the calendar is loaded, see `processor::latency`)
+    pub fn validate(&self) -> Result<(), String> {
+        if self.work_days.is_empty() || self.work_days.iter().any(|d| !(1..=7).contains(d)) {
+            return Err(
+                "work_days must be a non-empty list of 1 (Monday) to 7 (Sunday)".to_string(),
+            );
+        }
+        if !(0..=23).contains(&self.day_start_hour) {
+            return Err("day_start_hour must be between 0 and 23".to_string());
+        }
+        if self.day_end_hour <= self.day_start_hour || self.day_end_hour > 24 {
+            return Err("day_end_hour must be after day_start_hour and at most 24".to_string());
+        }
+        if self.holidays.len() > 1000 {
+            return Err("at most 1000 holidays".to_string());
+        }
+        Ok(())
+    }
+}
+
 /// Team leaderboard entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TeamLeaderboardEntry {
@@ -314,3 +803,226 @@ pub struct TeamLeaderboardEntry {
     pub member_count: i32,
     pub reviews_count: i32,
 }
+
+/// A code area (directory, file extension or language) a user reviewed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExpertiseArea {
+    pub name: String,
+    /// Comments on the area, each weighted by recency and quality
+    pub score: f64,
+    pub comments: i32,
+    pub last_reviewed_at: DateTime<Utc>,
+}
+
+/// What a user knows, from the files they left review comments on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserExpertise {
+    pub user: User,
+    pub directories: Vec<ExpertiseArea>,
+    pub extensions: Vec<ExpertiseArea>,
+    pub languages: Vec<ExpertiseArea>,
+}
+
+/// One reviewer's standing in a code area
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AreaExpert {
+    pub login: String,
+    pub score: f64,
+    pub comments: i32,
+}
+
+/// The top reviewers of one code area
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AreaExperts {
+    pub name: String,
+    /// Sum of every reviewer's score in the area
+    pub score: f64,
+    pub experts: Vec<AreaExpert>,
+}
+
+/// "Who knows what" in a repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoExperts {
+    pub directories: Vec<AreaExperts>,
+    pub extensions: Vec<AreaExperts>,
+    pub languages: Vec<AreaExperts>,
+}
+
+/// A suggested reviewer for an open PR, with the signals behind the pick
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReviewerSuggestion {
+    pub login: String,
+    /// Ranking score, higher is better
+    pub score: f64,
+    /// Knowledge of the PR's files relative to the best candidate (0-1)
+    pub expertise: f64,
+    /// Open PRs they are reviewing, counting suggestions made earlier in the
+    /// same list
+    pub open_reviews: i64,
+    /// Average working hours from a PR being ready to their first review
+    pub avg_response_hours: Option<f64>,
+    /// PRs by the same author they reviewed recently
+    pub author_prs_reviewed: i64,
+}
+
+/// A reviewer's current review load in a repo
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReviewerWorkload {
+    pub login: String,
+    /// Open PRs they reviewed
+    pub active_prs: i64,
+    /// Active PRs with commits since their last review
+    pub awaiting_rereview: i64,
+    /// Mean working hours since their active PRs were opened (author's
+    /// calendar, as on the open-PRs list)
+    pub avg_open_pr_age_hours: Option<f64>,
+    /// Review sessions per week over the report's window
+    pub sessions_per_week: f64,
+    /// `active_prs` reached the overload threshold
+    pub overloaded: bool,
+}
+
+/// Review load across a repo's reviewers, most loaded first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoWorkload {
+    /// Active PRs at which a reviewer counts as overloaded
+    pub overloaded_at: i64,
+    /// Weeks `sessions_per_week` is averaged over
+    pub weeks: i64,
+    pub overloaded: i64,
+    pub reviewers: Vec<ReviewerWorkload>,
+}
+
+/// Who an open PR is waiting on
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WaitingOn {
+    /// Nobody reviewed since the author last pushed or replied
+    Reviewer,
+    /// A reviewer acted last: the author should address it (or merge)
+    Author,
+}
+
+/// An open PR with no activity for at least the repo's stale threshold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StalePr {
+    pub pr_id: Uuid,
+    pub number: i32,
+    pub title: String,
+    pub author: String,
+    pub url: String,
+    /// Latest commit, review or comment (or when it was opened)
+    pub last_activity_at: DateTime<Utc>,
+    pub idle_days: i64,
+    pub waiting_on: WaitingOn,
+    /// Who to nudge: the last reviewer when waiting on a reviewer (None if
+    /// nobody reviewed yet), the author otherwise
+    pub nudge: Option<String>,
+}
+
+/// A repo's stale PRs, most idle first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StalePrs {
+    pub stale_after_days: i64,
+    pub prs: Vec<StalePr>,
+}
+
+/// Median and 90th percentile of a cycle-time metric
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Percentiles {
+    /// PRs the metric applies to
+    pub count: i64,
+    pub p50: Option<f64>,
+    pub p90: Option<f64>,
+}
+
+/// Cycle times of a set of merged PRs, in working hours on each author's
+/// calendar
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CycleTimeStats {
+    pub prs: i64,
+    /// Opened to first review (reviewed PRs only)
+    pub time_to_first_review_hours: Percentiles,
+    /// Review sessions per PR, across reviewers
+    pub review_rounds: Percentiles,
+    /// Last approval to merge (approved PRs only)
+    pub approval_to_merge_hours: Percentiles,
+    /// Opened to merged
+    pub time_to_merge_hours: Percentiles,
+}
+
+/// Cycle times of the PRs merged in a week (starting Monday, UTC)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyCycleTime {
+    pub week_start: NaiveDate,
+    #[serde(flatten)]
+    pub stats: CycleTimeStats,
+}
+
+/// Cycle times of one author's merged PRs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorCycleTime {
+    pub author: String,
+    #[serde(flatten)]
+    pub stats: CycleTimeStats,
+}
+
+/// Cycle-time analytics of a repo's PRs merged over the last `weeks`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoAnalytics {
+    pub weeks: i64,
+    pub overall: CycleTimeStats,
+    /// Oldest week first, including weeks without merges
+    pub by_week: Vec<WeeklyCycleTime>,
+    /// Most PRs first
+    pub by_author: Vec<AuthorCycleTime>,
+}
+
+/// A merged PR flagged by the coverage report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoveragePr {
+    pub number: i32,
+    pub title: String,
+    pub author: String,
+    pub url: String,
+    pub merged_at: DateTime<Utc>,
+    /// Who rubber-stamped it, or whose change request was still standing
+    pub reviewers: Vec<String>,
+}
+
+/// Review coverage of the PRs merged in a week (starting Monday, UTC)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CoverageWeek {
+    pub week_start: NaiveDate,
+    pub merged: i64,
+    pub unreviewed: i64,
+    pub rubber_stamped: i64,
+    pub merged_over_changes_requested: i64,
+}
+
+/// How often a reviewer's approvals are rubber stamps
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RubberStampRate {
+    pub login: String,
+    /// Review sessions with an approval, on the report's merged PRs
+    pub approvals: i64,
+    pub rubber_stamps: i64,
+    pub rate: f64,
+}
+
+/// Review coverage of a repo's PRs merged over the last `weeks`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverageReport {
+    pub weeks: i64,
+    pub merged: i64,
+    /// Merged without any review
+    pub unreviewed: Vec<CoveragePr>,
+    /// Merged with nothing but rubber-stamp reviews
+    pub rubber_stamped: Vec<CoveragePr>,
+    /// Merged while a reviewer's latest verdict was changes requested
+    pub merged_over_changes_requested: Vec<CoveragePr>,
+    /// Oldest week first, including weeks without merges
+    pub by_week: Vec<CoverageWeek>,
+    /// Highest rubber-stamp rate first
+    pub reviewers: Vec<RubberStampRate>,
+}
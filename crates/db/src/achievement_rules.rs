@@ -0,0 +1,418 @@
+//! Declarative achievement rules
+//!
+//! Every achievement is a rule: a metric counted per user, optionally limited
+//! to one repository (or a team's PRs) and a trailing time window, and a
+//! threshold. The checker
+//! unlocks an achievement when the metric reaches the threshold, and the
+//! progress API reports the same metric against the same threshold.
+//!
+//! Built-in rules are declared here; custom achievements store theirs on the
+//! `achievements` row.
+
+use chrono::{Duration, Utc};
+use common::models::{AchievementMetric, AchievementScope};
+use sqlx::{PgPool, Row};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::achievements::AchievementCategory;
+use crate::responsiveness::QUICK_FIX_HOURS;
+
+/// Built-in rules: (achievement id, metric, threshold)
+const BUILTIN_RULES: &[(&str, AchievementMetric, i64)] = &[
+    // Milestones
+    ("first_review", AchievementMetric::ReviewCount, 1),
+    ("review_10", AchievementMetric::ReviewCount, 10),
+    ("review_50", AchievementMetric::ReviewCount, 50),
+    ("review_100", AchievementMetric::ReviewCount, 100),
+    ("review_500", AchievementMetric::ReviewCount, 500),
+    ("review_1000", AchievementMetric::ReviewCount, 1000),
+    ("first_pr", AchievementMetric::PrsAuthored, 1),
+    ("pr_merged_10", AchievementMetric::PrsMerged, 10),
+    ("pr_merged_100", AchievementMetric::PrsMerged, 100),
+    // Speed
+    ("speed_demon", AchievementMetric::FastReviews, 10),
+    ("first_responder", AchievementMetric::FirstReviews, 25),
+    // Quality
+    ("nitpicker", AchievementMetric::NitComments, 50),
+    ("bug_hunter", AchievementMetric::AddressedComments, 10),
+    ("thorough", AchievementMetric::DeepReviews, 5),
+    // Streaks
+    ("review_streak_7", AchievementMetric::LongestStreakDays, 7),
+    ("review_streak_30", AchievementMetric::LongestStreakDays, 30),
+    // Special
+    ("comeback_kid", AchievementMetric::Comebacks, 1),
+    ("review_rampage", AchievementMetric::MaxPrsReviewedInDay, 5),
+    ("the_closer", AchievementMetric::ApprovedMerges, 10),
+    ("helpful", AchievementMetric::ThanksReplies, 10),
+    // Author responsiveness
+    ("quick_fix", AchievementMetric::QuickFixes, 10),
+    ("good_listener", AchievementMetric::ThreadsReplied, 50),
+];
+
+/// An achievement's unlock condition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AchievementRule {
+    pub achievement_id: String,
+    pub metric: AchievementMetric,
+    pub threshold: i64,
+    /// Comment category counted by `CategoryComments`
+    pub category: Option<String>,
+    /// Only count activity in this repository
+    pub repo_id: Option<Uuid>,
+    /// Only count activity on PRs authored by this team's members
+    pub team_id: Option<Uuid>,
+    /// Only count activity from the last N days
+    pub window_days: Option<i32>,
+}
+
+impl AchievementRule {
+    /// Whether a metric value unlocks the achievement
+    pub fn is_met(&self, value: i64) -> bool {
+        value >= self.threshold
+    }
+
+    /// Whether the achievement is kept once unlocked even if the metric drops
+    /// below the threshold. Windowed rules count recent activity, which ages
+    /// out of the window, so they are never stale.
+    pub fn is_earned_once(&self) -> bool {
+        self.window_days.is_some()
+    }
+
+    /// (current, target) for progress display; current is capped at target
+    pub fn progress(&self, value: i64) -> (i64, i64) {
+        (value.clamp(0, self.threshold), self.threshold)
+    }
+
+    /// Catalog grouping, derived from the metric
+    pub fn category(&self) -> AchievementCategory {
+        match self.metric {
+            AchievementMetric::ReviewCount
+            | AchievementMetric::PrsAuthored
+            | AchievementMetric::PrsMerged => AchievementCategory::Milestone,
+            AchievementMetric::FastReviews
+            | AchievementMetric::FirstReviews
+            | AchievementMetric::QuickFixes => AchievementCategory::Speed,
+            AchievementMetric::NitComments
+            | AchievementMetric::BugCatches
+            | AchievementMetric::AddressedComments
+            | AchievementMetric::CategoryComments
+            | AchievementMetric::DeepReviews => AchievementCategory::Quality,
+            AchievementMetric::LongestStreakDays => AchievementCategory::Streak,
+            AchievementMetric::Comebacks
+            | AchievementMetric::MaxPrsReviewedInDay
+            | AchievementMetric::ApprovedMerges
+            | AchievementMetric::ThanksReplies
+            | AchievementMetric::ThreadsReplied => AchievementCategory::Special,
+        }
+    }
+
+    /// The rule evaluated within a scope, for scoped unlock tracking. None
+    /// for rules already pinned to a repository.
+    pub fn scoped(&self, scope: AchievementScope) -> Option<AchievementRule> {
+        if self.repo_id.is_some() || self.team_id.is_some() {
+            return None;
+        }
+        Some(AchievementRule {
+            repo_id: scope.repo_id(),
+            team_id: scope.team_id(),
+            ..self.clone()
+        })
+    }
+
+    /// What the metric query depends on (rules sharing it share one query)
+    fn query_key(
+        &self,
+    ) -> (
+        AchievementMetric,
+        Option<&str>,
+        Option<Uuid>,
+        Option<Uuid>,
+        Option<i32>,
+    ) {
+        (
+            self.metric,
+            self.category.as_deref(),
+            self.repo_id,
+            self.team_id,
+            self.window_days,
+        )
+    }
+}
+
+/// All built-in rules
+pub fn builtin_rules() -> Vec<AchievementRule> {
+    BUILTIN_RULES
+        .iter()
+        .map(|&(id, metric, threshold)| AchievementRule {
+            achievement_id: id.to_string(),
+            metric,
+            threshold,
+            category: None,
+            repo_id: None,
+            team_id: None,
+            window_days: None,
+        })
+        .collect()
+}
+
+/// The built-in rule for an achievement, if any
+pub fn rule_for(achievement_id: &str) -> Option<AchievementRule> {
+    builtin_rules()
+        .into_iter()
+        .find(|r| r.achievement_id == achievement_id)
+}
+
+/// Built-in rules followed by every custom achievement's rule
+pub async fn list_rules(pool: &PgPool) -> Result<Vec<AchievementRule>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, metric, threshold, metric_category, repo_id, window_days
+        FROM achievements
+        WHERE is_custom AND metric IS NOT NULL
+        ORDER BY id
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let custom = rows.into_iter().filter_map(|r| {
+        Some(AchievementRule {
+            achievement_id: r.get("id"),
+            metric: AchievementMetric::parse(r.get::<&str, _>("metric"))?,
+            threshold: r.get("threshold"),
+            category: r.get("metric_category"),
+            repo_id: r.get("repo_id"),
+            team_id: None,
+            window_days: r.get("window_days"),
+        })
+    });
+
+    Ok(builtin_rules().into_iter().chain(custom).collect())
+}
+
+/// Activity visible to a rule: `$2` repo filter, `$3` window start and `$5`
+/// team filter (all optional), `$4` comment category. Every metric query
+/// starts with this; `$6` is the quick fix window in hours.
+const SCOPE_SQL: &str = r#"
+    WITH params AS (
+        SELECT $4::text as category
+    ),
+    team_authors AS (
+        SELECT user_id FROM team_members WHERE team_id = $5::uuid
+    ),
+    scoped_prs AS (
+        SELECT *
+        FROM pull_requests
+        WHERE ($2::uuid IS NULL OR repo_id = $2)
+          AND ($5::uuid IS NULL OR author_id IN (SELECT user_id FROM team_authors))
+    ),
+    scoped_reviews AS (
+        SELECT r.*
+        FROM reviews r
+        JOIN scoped_prs p ON p.id = r.pr_id
+        WHERE ($3::timestamptz IS NULL OR r.submitted_at >= $3)
+    ),
+    scoped_comments AS (
+        SELECT c.*
+        FROM review_comments c
+        JOIN scoped_prs p ON p.id = c.pr_id
+        WHERE ($3::timestamptz IS NULL OR c.created_at >= $3)
+    ),
+    authored_prs AS (
+        SELECT *
+        FROM scoped_prs
+        WHERE $3::timestamptz IS NULL OR created_at >= $3
+    )
+"#;
+
+/// SQL computing a metric for user `$1` as a single bigint `value`, over the
+/// scoped tables of [`SCOPE_SQL`] (extra CTEs start with a comma)
+fn metric_sql(metric: AchievementMetric) -> &'static str {
+    match metric {
+        AchievementMetric::ReviewCount => {
+            "SELECT COUNT(*)::bigint as value FROM scoped_reviews WHERE reviewer_id = $1"
+        }
+        AchievementMetric::FastReviews => {
+            r#"
+            SELECT COUNT(*)::bigint as value
+            FROM scoped_reviews r
+            WHERE r.reviewer_id = $1
+              AND (
+                  SELECT SUM(l.fast_bonus)
+                  FROM xp_ledger l
+                  WHERE l.session_review_id = r.id
+                    AND l.kind IN ('session', 'reversal')
+              ) > 0
+            "#
+        }
+        AchievementMetric::FirstReviews => {
+            r#"
+            , first_reviews AS (
+                SELECT DISTINCT ON (r.pr_id) r.reviewer_id
+                FROM scoped_reviews r
+                JOIN users u ON u.id = r.reviewer_id
+                WHERE r.pr_id IN (SELECT pr_id FROM scoped_reviews WHERE reviewer_id = $1)
+                  AND u.login NOT LIKE '%[bot]'
+                ORDER BY r.pr_id, r.submitted_at ASC
+            )
+            SELECT COUNT(*)::bigint as value FROM first_reviews WHERE reviewer_id = $1
+            "#
+        }
+        AchievementMetric::NitComments => {
+            "SELECT COUNT(*)::bigint as value FROM scoped_comments WHERE user_id = $1 AND category = 'nit'"
+        }
+        AchievementMetric::BugCatches => {
+            "SELECT COUNT(*)::bigint as value FROM scoped_comments WHERE user_id = $1 AND category = 'logic'"
+        }
+        AchievementMetric::AddressedComments => {
+            "SELECT COUNT(*)::bigint as value FROM scoped_comments WHERE user_id = $1 AND addressed_by IS NOT NULL"
+        }
+        AchievementMetric::CategoryComments => {
+            r#"
+            SELECT COUNT(*)::bigint as value
+            FROM scoped_comments
+            WHERE user_id = $1 AND category = (SELECT category FROM params)
+            "#
+        }
+        AchievementMetric::DeepReviews => {
+            "SELECT COUNT(*)::bigint as value FROM scoped_reviews WHERE reviewer_id = $1 AND comments_count >= 10"
+        }
+        AchievementMetric::LongestStreakDays => {
+            r#"
+            , review_dates AS (
+                SELECT DISTINCT DATE(submitted_at) as review_date
+                FROM scoped_reviews
+                WHERE reviewer_id = $1
+            ),
+            streaks AS (
+                SELECT review_date - (ROW_NUMBER() OVER (ORDER BY review_date))::int AS streak_group
+                FROM review_dates
+            )
+            SELECT COALESCE(MAX(streak_len), 0)::bigint as value
+            FROM (SELECT COUNT(*) as streak_len FROM streaks GROUP BY streak_group) s
+            "#
+        }
+        AchievementMetric::Comebacks => {
+            r#"
+            , review_dates AS (
+                SELECT DISTINCT DATE(submitted_at) as review_date
+                FROM scoped_reviews
+                WHERE reviewer_id = $1
+            ),
+            gaps AS (
+                SELECT review_date - LAG(review_date) OVER (ORDER BY review_date) as gap_days
+                FROM review_dates
+            )
+            SELECT COUNT(*)::bigint as value FROM gaps WHERE gap_days >= 30
+            "#
+        }
+        AchievementMetric::MaxPrsReviewedInDay => {
+            r#"
+            SELECT COALESCE(MAX(prs), 0)::bigint as value
+            FROM (
+                SELECT COUNT(DISTINCT pr_id) as prs
+                FROM scoped_reviews
+                WHERE reviewer_id = $1
+                GROUP BY DATE(submitted_at)
+            ) d
+            "#
+        }
+        AchievementMetric::ApprovedMerges => {
+            r#"
+            SELECT COUNT(DISTINCT r.pr_id)::bigint as value
+            FROM scoped_reviews r
+            JOIN pull_requests p ON p.id = r.pr_id
+            WHERE r.reviewer_id = $1
+              AND r.state = 'approved'
+              AND p.state = 'merged'
+              AND p.author_id <> $1
+            "#
+        }
+        AchievementMetric::ThanksReplies => {
+            r#"
+            SELECT COUNT(*)::bigint as value
+            FROM scoped_comments c
+            JOIN review_threads t ON t.id = c.id
+            WHERE t.starter_id = $1 AND t.author_thanked
+            "#
+        }
+        AchievementMetric::PrsAuthored => {
+            "SELECT COUNT(*)::bigint as value FROM authored_prs WHERE author_id = $1"
+        }
+        AchievementMetric::PrsMerged => {
+            "SELECT COUNT(*)::bigint as value FROM authored_prs WHERE author_id = $1 AND state = 'merged'"
+        }
+        AchievementMetric::QuickFixes => {
+            r#"
+            SELECT COUNT(*)::bigint as value
+            FROM scoped_reviews r
+            JOIN pull_requests p ON p.id = r.pr_id
+            WHERE p.author_id = $1
+              AND r.reviewer_id <> $1
+              AND r.state = 'changes_requested'
+              AND EXISTS (
+                  SELECT 1 FROM commits c
+                  WHERE c.pr_id = r.pr_id
+                    AND c.committed_at > r.submitted_at
+                    AND c.committed_at <= r.submitted_at + $6 * INTERVAL '1 hour'
+              )
+            "#
+        }
+        AchievementMetric::ThreadsReplied => {
+            r#"
+            SELECT COUNT(*)::bigint as value
+            FROM scoped_comments root
+            JOIN pull_requests p ON p.id = root.pr_id
+            WHERE p.author_id = $1
+              AND root.user_id <> $1
+              AND root.in_reply_to_id IS NULL
+              AND EXISTS (
+                  SELECT 1 FROM review_comments reply
+                  WHERE reply.in_reply_to_id = root.github_id AND reply.user_id = $1
+              )
+            "#
+        }
+    }
+}
+
+/// Compute a rule's metric for a user
+pub async fn metric_value(
+    pool: &PgPool,
+    user_id: Uuid,
+    rule: &AchievementRule,
+) -> Result<i64, sqlx::Error> {
+    let since = rule
+        .window_days
+        .map(|days| Utc::now() - Duration::days(days as i64));
+    let row = sqlx::query(&format!("{}{}", SCOPE_SQL, metric_sql(rule.metric)))
+        .bind(user_id)
+        .bind(rule.repo_id)
+        .bind(since)
+        .bind(rule.category.as_deref())
+        .bind(rule.team_id)
+        .bind(QUICK_FIX_HOURS)
+        .fetch_one(pool)
+        .await?;
+    Ok(row.get("value"))
+}
+
+/// Compute each rule's metric for a user, in rule order. Rules that count
+/// the same thing share one query.
+pub async fn metric_values(
+    pool: &PgPool,
+    user_id: Uuid,
+    rules: &[AchievementRule],
+) -> Result<Vec<i64>, sqlx::Error> {
+    let mut cache = HashMap::new();
+    let mut values = Vec::with_capacity(rules.len());
+    for rule in rules {
+        let value = match cache.entry(rule.query_key()) {
+            Entry::Occupied(entry) => *entry.get(),
+            Entry::Vacant(entry) => *entry.insert(metric_value(pool, user_id, rule).await?),
+        };
+        values.push(value);
+    }
+    Ok(values)
+}
@@ -0,0 +1,142 @@
+//! Tests for the achievement rules
+
+#[cfg(test)]
+mod tests {
+    use crate::achievement_rules::{builtin_rules, rule_for, AchievementRule};
+    use crate::achievements::AchievementCategory;
+    use common::models::{AchievementMetric, AchievementScope};
+    use std::collections::HashSet;
+    use uuid::Uuid;
+
+    /// Achievement IDs seeded by schema.sql
+    fn seeded_ids() -> HashSet<String> {
+        let schema = include_str!("../../../migrations/schema.sql");
+        let start = schema
+            .find("INSERT INTO achievements")
+            .expect("achievement seed");
+        let end = start + schema[start..].find("ON CONFLICT").unwrap();
+        schema[start..end]
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("('"))
+            .map(|rest| rest.split('\'').next().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_every_seeded_achievement_has_a_rule() {
+        let seeded = seeded_ids();
+        assert_eq!(seeded.len(), 22);
+
+        let ruled: HashSet<String> = builtin_rules()
+            .into_iter()
+            .map(|r| r.achievement_id)
+            .collect();
+        assert_eq!(seeded, ruled);
+    }
+
+    #[test]
+    fn test_rule_ids_are_unique() {
+        let rules = builtin_rules();
+        let ids: HashSet<_> = rules.iter().map(|r| &r.achievement_id).collect();
+        assert_eq!(ids.len(), rules.len());
+    }
+
+    #[test]
+    fn test_first_review_unlocks_when_checked_late() {
+        let rule = rule_for("first_review").unwrap();
+        assert!(!rule.is_met(0));
+        assert!(rule.is_met(1));
+        assert!(rule.is_met(37));
+    }
+
+    #[test]
+    fn test_progress_is_capped_at_threshold() {
+        let rule = rule_for("review_50").unwrap();
+        assert_eq!(rule.progress(0), (0, 50));
+        assert_eq!(rule.progress(20), (20, 50));
+        assert_eq!(rule.progress(80), (50, 50));
+    }
+
+    #[test]
+    fn test_rules_share_metrics() {
+        let streak_7 = rule_for("review_streak_7").unwrap();
+        let streak_30 = rule_for("review_streak_30").unwrap();
+        assert_eq!(streak_7.metric, AchievementMetric::LongestStreakDays);
+        assert_eq!(streak_30.metric, AchievementMetric::LongestStreakDays);
+        assert!(streak_7.is_met(12));
+        assert!(!streak_30.is_met(12));
+    }
+
+    #[test]
+    fn test_category_from_metric() {
+        let category = |id: &str| rule_for(id).unwrap().category();
+        assert!(matches!(
+            category("review_1000"),
+            AchievementCategory::Milestone
+        ));
+        assert!(matches!(
+            category("pr_merged_10"),
+            AchievementCategory::Milestone
+        ));
+        assert!(matches!(
+            category("first_responder"),
+            AchievementCategory::Speed
+        ));
+        assert!(matches!(
+            category("bug_hunter"),
+            AchievementCategory::Quality
+        ));
+        assert!(matches!(
+            category("review_streak_30"),
+            AchievementCategory::Streak
+        ));
+        assert!(matches!(category("helpful"), AchievementCategory::Special));
+        assert!(rule_for("night_owl").is_none());
+    }
+
+    #[test]
+    fn test_builtin_rules_are_unscoped() {
+        for rule in builtin_rules() {
+            assert_ne!(rule.metric, AchievementMetric::CategoryComments);
+            assert!(rule.category.is_none());
+            assert!(rule.repo_id.is_none());
+            assert!(rule.window_days.is_none());
+        }
+    }
+
+    #[test]
+    fn test_scoped_rules() {
+        let repo = Uuid::new_v4();
+        let team = Uuid::new_v4();
+        let centurion = rule_for("review_100").unwrap();
+
+        let in_repo = centurion.scoped(AchievementScope::Repo(repo)).unwrap();
+        assert_eq!(in_repo.repo_id, Some(repo));
+        assert_eq!(in_repo.team_id, None);
+        assert_eq!(in_repo.threshold, 100);
+
+        let in_team = centurion.scoped(AchievementScope::Team(team)).unwrap();
+        assert_eq!(in_team.repo_id, None);
+        assert_eq!(in_team.team_id, Some(team));
+
+        // Rules pinned to a repository aren't tracked per scope
+        assert!(in_repo.scoped(AchievementScope::Team(team)).is_none());
+    }
+
+    #[test]
+    fn test_windowed_rules_are_earned_once() {
+        let rule = rule_for("review_10").unwrap();
+        assert!(!rule.is_earned_once());
+
+        let sprint = AchievementRule {
+            window_days: Some(7),
+            ..rule
+        };
+        assert!(sprint.is_earned_once());
+        // Scoping keeps the window
+        assert!(sprint
+            .scoped(AchievementScope::Repo(Uuid::new_v4()))
+            .unwrap()
+            .is_earned_once());
+    }
+}
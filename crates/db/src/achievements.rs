@@ -1,37 +1,370 @@
 //! Achievement queries
 
-use common::models::{Achievement, AchievementRarity, UserAchievement};
+use chrono::{DateTime, Utc};
+use common::models::{Achievement, AchievementRarity, AchievementScope, UserAchievement};
 use serde::Serialize;
 use sqlx::{PgPool, Row};
+use std::collections::HashMap;
 use uuid::Uuid;
 
-/// Unlock an achievement for a user
+use crate::achievement_rules::AchievementRule;
+
+/// Unlock an achievement for a user and credit its `xp_reward`.
+///
+/// Returns None if the user already has it. The reward is snapshotted on the
+/// unlock, recorded in the XP ledger and added to the user's total in one
+/// transaction.
 pub async fn unlock(
     pool: &PgPool,
     user_id: Uuid,
     achievement_id: &str,
-) -> Result<UserAchievement, sqlx::Error> {
+    rule_version: i32,
+) -> Result<Option<UserAchievement>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
     let row = sqlx::query(
         r#"
-        INSERT INTO user_achievements (user_id, achievement_id, unlocked_at)
-        VALUES ($1, $2, NOW())
-        ON CONFLICT (user_id, achievement_id) DO UPDATE SET unlocked_at = user_achievements.unlocked_at
-        RETURNING user_id, achievement_id, unlocked_at
+        INSERT INTO user_achievements (user_id, achievement_id, unlocked_at, xp_awarded, notified_at)
+        SELECT $1, id, NOW(), xp_reward,
+               -- Earning back a revoked achievement isn't announced again
+               CASE WHEN EXISTS (
+                   SELECT 1 FROM achievement_revocations
+                   WHERE user_id = $1 AND achievement_id = $2
+               ) THEN NOW() END
+        FROM achievements WHERE id = $2
+        ON CONFLICT (user_id, achievement_id) DO NOTHING
+        RETURNING user_id, achievement_id, unlocked_at, xp_awarded
         "#,
     )
     .bind(user_id)
     .bind(achievement_id)
-    .fetch_one(pool)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let unlocked = UserAchievement {
+        user_id: row.get("user_id"),
+        achievement_id: row.get("achievement_id"),
+        unlocked_at: row.get("unlocked_at"),
+        xp_awarded: row.get("xp_awarded"),
+        name: None,
+        description: None,
+        emoji: None,
+    };
+
+    crate::xp_ledger::record_achievement(&mut tx, &unlocked, rule_version).await?;
+    crate::recalc::refresh_user_totals(&mut tx, Some(&[user_id])).await?;
+
+    tx.commit().await?;
+    Ok(Some(unlocked))
+}
+
+/// Revoke an achievement the user no longer qualifies for and take back its
+/// XP.
+///
+/// Returns None if the user doesn't have it. The unlock is deleted (so a
+/// pending Discord notification is never posted), the revocation is
+/// audit-logged with the metric value that failed the rule, and the ledger
+/// and user total are updated in one transaction.
+pub async fn revoke(
+    pool: &PgPool,
+    user_id: Uuid,
+    rule: &AchievementRule,
+    metric_value: i64,
+    rule_version: i32,
+) -> Result<Option<UserAchievement>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let row = sqlx::query(
+        r#"
+        DELETE FROM user_achievements
+        WHERE user_id = $1 AND achievement_id = $2
+        RETURNING user_id, achievement_id, unlocked_at, xp_awarded
+        "#,
+    )
+    .bind(user_id)
+    .bind(&rule.achievement_id)
+    .fetch_optional(&mut *tx)
     .await?;
 
-    Ok(UserAchievement {
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let revoked = UserAchievement {
         user_id: row.get("user_id"),
         achievement_id: row.get("achievement_id"),
         unlocked_at: row.get("unlocked_at"),
+        xp_awarded: row.get("xp_awarded"),
         name: None,
         description: None,
         emoji: None,
-    })
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO achievement_revocations (
+            user_id, achievement_id, unlocked_at, xp_revoked, metric_value, threshold, rule_version
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+    )
+    .bind(revoked.user_id)
+    .bind(&revoked.achievement_id)
+    .bind(revoked.unlocked_at)
+    .bind(revoked.xp_awarded)
+    .bind(metric_value)
+    .bind(rule.threshold)
+    .bind(rule_version)
+    .execute(&mut *tx)
+    .await?;
+
+    crate::xp_ledger::record_revocation(&mut tx, &revoked, rule_version).await?;
+    crate::recalc::refresh_user_totals(&mut tx, Some(&[user_id])).await?;
+
+    tx.commit().await?;
+    Ok(Some(revoked))
+}
+
+/// Record a scoped unlock. Returns false if the user already has it in that
+/// scope. Scoped unlocks carry no XP and aren't announced.
+pub async fn unlock_scoped(
+    pool: &PgPool,
+    user_id: Uuid,
+    achievement_id: &str,
+    scope: AchievementScope,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO scoped_achievements (user_id, achievement_id, repo_id, team_id)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(user_id)
+    .bind(achievement_id)
+    .bind(scope.repo_id())
+    .bind(scope.team_id())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Revoke a scoped unlock the user no longer qualifies for, audit-logged
+/// like [`revoke`]. Returns false if the user doesn't have it.
+pub async fn revoke_scoped(
+    pool: &PgPool,
+    user_id: Uuid,
+    rule: &AchievementRule,
+    scope: AchievementScope,
+    metric_value: i64,
+    rule_version: i32,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let row = sqlx::query(
+        r#"
+        DELETE FROM scoped_achievements
+        WHERE user_id = $1 AND achievement_id = $2
+          AND repo_id IS NOT DISTINCT FROM $3
+          AND team_id IS NOT DISTINCT FROM $4
+        RETURNING unlocked_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(&rule.achievement_id)
+    .bind(scope.repo_id())
+    .bind(scope.team_id())
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(false);
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO achievement_revocations (
+            user_id, achievement_id, unlocked_at, xp_revoked, metric_value, threshold,
+            rule_version, repo_id, team_id
+        )
+        VALUES ($1, $2, $3, 0, $4, $5, $6, $7, $8)
+        "#,
+    )
+    .bind(user_id)
+    .bind(&rule.achievement_id)
+    .bind(row.get::<DateTime<Utc>, _>("unlocked_at"))
+    .bind(metric_value)
+    .bind(rule.threshold)
+    .bind(rule_version)
+    .bind(scope.repo_id())
+    .bind(scope.team_id())
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// A user's unlocks within a scope
+pub async fn list_for_user_in_scope(
+    pool: &PgPool,
+    user_id: Uuid,
+    scope: AchievementScope,
+) -> Result<Vec<UserAchievement>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT sa.user_id, sa.achievement_id, sa.unlocked_at,
+               a.name, a.description, a.emoji
+        FROM scoped_achievements sa
+        JOIN achievements a ON a.id = sa.achievement_id
+        WHERE sa.user_id = $1
+          AND sa.repo_id IS NOT DISTINCT FROM $2
+          AND sa.team_id IS NOT DISTINCT FROM $3
+        ORDER BY sa.unlocked_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .bind(scope.repo_id())
+    .bind(scope.team_id())
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| UserAchievement {
+            user_id: r.get("user_id"),
+            achievement_id: r.get("achievement_id"),
+            unlocked_at: r.get("unlocked_at"),
+            xp_awarded: 0,
+            name: Some(r.get("name")),
+            description: Some(r.get("description")),
+            emoji: Some(r.get("emoji")),
+        })
+        .collect())
+}
+
+/// Scopes a user's achievements are tracked in: repositories they reviewed
+/// or authored PRs in, teams they belong to, and any scope they already hold
+/// an unlock in
+pub async fn list_scopes_for_user(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<AchievementScope>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT p.repo_id, NULL::uuid as team_id
+        FROM reviews r
+        JOIN pull_requests p ON p.id = r.pr_id
+        WHERE r.reviewer_id = $1
+        UNION
+        SELECT repo_id, NULL FROM pull_requests WHERE author_id = $1
+        UNION
+        SELECT NULL, team_id FROM team_members WHERE user_id = $1
+        UNION
+        SELECT repo_id, team_id FROM scoped_achievements WHERE user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|r| {
+            match (
+                r.get::<Option<Uuid>, _>("repo_id"),
+                r.get::<Option<Uuid>, _>("team_id"),
+            ) {
+                (Some(repo_id), _) => Some(AchievementScope::Repo(repo_id)),
+                (None, Some(team_id)) => Some(AchievementScope::Team(team_id)),
+                (None, None) => None,
+            }
+        })
+        .collect())
+}
+
+/// An audit log entry for a revoked achievement
+#[derive(Debug, Clone, Serialize)]
+pub struct AchievementRevocation {
+    pub id: i64,
+    pub user_login: String,
+    pub achievement_id: String,
+    pub achievement_name: String,
+    pub unlocked_at: DateTime<Utc>,
+    pub xp_revoked: i32,
+    /// Metric value at revocation, below `threshold`
+    pub metric_value: i64,
+    pub threshold: i64,
+    pub rule_version: i32,
+    /// Set for scoped unlocks ("owner/name")
+    pub repo: Option<String>,
+    /// Set for scoped unlocks
+    pub team: Option<String>,
+    pub revoked_at: DateTime<Utc>,
+}
+
+/// List revocations, newest first, optionally for one user
+pub async fn list_revocations(
+    pool: &PgPool,
+    user_id: Option<Uuid>,
+    limit: i64,
+) -> Result<Vec<AchievementRevocation>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT ar.*, u.login as user_login, a.name as achievement_name,
+               r.owner || '/' || r.name as repo, t.name as team
+        FROM achievement_revocations ar
+        JOIN users u ON u.id = ar.user_id
+        JOIN achievements a ON a.id = ar.achievement_id
+        LEFT JOIN repositories r ON r.id = ar.repo_id
+        LEFT JOIN teams t ON t.id = ar.team_id
+        WHERE $1::uuid IS NULL OR ar.user_id = $1
+        ORDER BY ar.id DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| AchievementRevocation {
+            id: r.get("id"),
+            user_login: r.get("user_login"),
+            achievement_id: r.get("achievement_id"),
+            achievement_name: r.get("achievement_name"),
+            unlocked_at: r.get("unlocked_at"),
+            xp_revoked: r.get("xp_revoked"),
+            metric_value: r.get("metric_value"),
+            threshold: r.get("threshold"),
+            rule_version: r.get("rule_version"),
+            repo: r.get("repo"),
+            team: r.get("team"),
+            revoked_at: r.get("revoked_at"),
+        })
+        .collect())
+}
+
+/// Total achievement XP credited per user
+pub async fn xp_by_user(pool: &PgPool) -> Result<HashMap<Uuid, i64>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT user_id, SUM(xp_awarded)::bigint as xp
+        FROM user_achievements
+        GROUP BY user_id
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.get("user_id"), r.get("xp")))
+        .collect())
 }
 
 /// Check if user has achievement
@@ -63,7 +396,7 @@ pub async fn list_for_user(
 ) -> Result<Vec<UserAchievement>, sqlx::Error> {
     let rows = sqlx::query(
         r#"
-        SELECT ua.user_id, ua.achievement_id, ua.unlocked_at,
+        SELECT ua.user_id, ua.achievement_id, ua.unlocked_at, ua.xp_awarded,
                a.name, a.description, a.emoji
         FROM user_achievements ua
         JOIN achievements a ON a.id = ua.achievement_id
@@ -81,6 +414,7 @@ pub async fn list_for_user(
             user_id: r.get("user_id"),
             achievement_id: r.get("achievement_id"),
             unlocked_at: r.get("unlocked_at"),
+            xp_awarded: r.get("xp_awarded"),
             name: Some(r.get("name")),
             description: Some(r.get("description")),
             emoji: Some(r.get("emoji")),
@@ -95,7 +429,7 @@ pub async fn list_recent_unlocks(
 ) -> Result<Vec<UserAchievement>, sqlx::Error> {
     let rows = sqlx::query(
         r#"
-        SELECT ua.user_id, ua.achievement_id, ua.unlocked_at,
+        SELECT ua.user_id, ua.achievement_id, ua.unlocked_at, ua.xp_awarded,
                a.name, a.description, a.emoji
         FROM user_achievements ua
         JOIN achievements a ON a.id = ua.achievement_id
@@ -113,6 +447,7 @@ pub async fn list_recent_unlocks(
             user_id: r.get("user_id"),
             achievement_id: r.get("achievement_id"),
             unlocked_at: r.get("unlocked_at"),
+            xp_awarded: r.get("xp_awarded"),
             name: Some(r.get("name")),
             description: Some(r.get("description")),
             emoji: Some(r.get("emoji")),
@@ -248,6 +583,73 @@ pub async fn list_all(pool: &PgPool) -> Result<Vec<Achievement>, sqlx::Error> {
         .collect())
 }
 
+/// Get an achievement definition by ID
+pub async fn get_by_id(pool: &PgPool, id: &str) -> Result<Option<Achievement>, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT id, name, description, emoji, xp_reward, rarity FROM achievements WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| Achievement {
+        id: r.get("id"),
+        name: r.get("name"),
+        description: r.get("description"),
+        emoji: r.get("emoji"),
+        xp_reward: r.get("xp_reward"),
+        rarity: parse_rarity(r.get::<&str, _>("rarity")),
+    }))
+}
+
+/// Create a custom achievement with its unlock rule
+pub async fn create_custom(
+    pool: &PgPool,
+    achievement: &Achievement,
+    rule: &AchievementRule,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO achievements (
+            id, name, description, emoji, xp_reward, rarity, is_custom,
+            metric, threshold, metric_category, repo_id, window_days
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, TRUE, $7, $8, $9, $10, $11)
+        "#,
+    )
+    .bind(&achievement.id)
+    .bind(&achievement.name)
+    .bind(&achievement.description)
+    .bind(&achievement.emoji)
+    .bind(achievement.xp_reward)
+    .bind(achievement.rarity.as_str())
+    .bind(rule.metric.as_str())
+    .bind(rule.threshold)
+    .bind(rule.category.as_deref())
+    .bind(rule.repo_id)
+    .bind(rule.window_days)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Delete a custom achievement nobody has unlocked. Returns false if it is
+/// built in or has been unlocked.
+pub async fn delete_custom(pool: &PgPool, id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM achievements
+        WHERE id = $1
+          AND is_custom
+          AND NOT EXISTS (SELECT 1 FROM user_achievements WHERE achievement_id = $1)
+        "#,
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
 /// Achievement category for grouping in the UI
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -265,14 +667,17 @@ pub struct AchievementWithStats {
     #[serde(flatten)]
     pub achievement: Achievement,
     pub category: AchievementCategory,
+    /// Created through the API rather than built in
+    pub custom: bool,
     pub unlock_count: i64,
 }
 
 /// List all achievements with unlock counts
 pub async fn list_all_with_stats(pool: &PgPool) -> Result<Vec<AchievementWithStats>, sqlx::Error> {
+    let rules = crate::achievement_rules::list_rules(pool).await?;
     let rows = sqlx::query(
         r#"
-        SELECT a.id, a.name, a.description, a.emoji, a.xp_reward, a.rarity,
+        SELECT a.id, a.name, a.description, a.emoji, a.xp_reward, a.rarity, a.is_custom,
                COALESCE(c.count, 0) as unlock_count
         FROM achievements a
         LEFT JOIN (
@@ -298,7 +703,7 @@ pub async fn list_all_with_stats(pool: &PgPool) -> Result<Vec<AchievementWithSta
         .into_iter()
         .map(|r| {
             let id: String = r.get("id");
-            let category = categorize_achievement(&id);
+            let category = categorize_achievement(&rules, &id);
             AchievementWithStats {
                 achievement: Achievement {
                     id,
@@ -309,28 +714,19 @@ pub async fn list_all_with_stats(pool: &PgPool) -> Result<Vec<AchievementWithSta
                     rarity: parse_rarity(r.get::<&str, _>("rarity")),
                 },
                 category,
+                custom: r.get("is_custom"),
                 unlock_count: r.get("unlock_count"),
             }
         })
         .collect())
 }
 
-fn categorize_achievement(id: &str) -> AchievementCategory {
-    match id {
-        // Milestones
-        "first_review" | "review_10" | "review_50" | "review_100" | "review_500"
-        | "review_1000" | "first_pr" | "pr_merged_10" | "pr_merged_100" => {
-            AchievementCategory::Milestone
-        }
-        // Speed
-        "speed_demon" | "first_responder" => AchievementCategory::Speed,
-        // Quality
-        "nitpicker" | "bug_hunter" | "thorough" => AchievementCategory::Quality,
-        // Streaks
-        "review_streak_7" | "review_streak_30" => AchievementCategory::Streak,
-        // Special/Fun
-        _ => AchievementCategory::Special,
-    }
+fn categorize_achievement(rules: &[AchievementRule], id: &str) -> AchievementCategory {
+    rules
+        .iter()
+        .find(|rule| rule.achievement_id == id)
+        .map(|rule| rule.category())
+        .unwrap_or(AchievementCategory::Special)
 }
 
 /// User's progress toward achievements
@@ -353,52 +749,38 @@ pub struct AchievementProgress {
     pub unlocked: bool,
 }
 
-/// Get a user's progress toward all achievements
+/// Get a user's progress toward all achievements, globally or within a
+/// scope. Scoped progress only lists achievements that can be scoped.
 pub async fn get_user_progress(
     pool: &PgPool,
     user_id: Uuid,
+    scope: Option<AchievementScope>,
 ) -> Result<Vec<AchievementProgress>, sqlx::Error> {
     // Get all achievements
     let achievements = list_all(pool).await?;
 
     // Get user's unlocked achievements
-    let unlocked: std::collections::HashSet<String> = list_for_user(pool, user_id)
-        .await?
-        .into_iter()
-        .map(|a| a.achievement_id)
-        .collect();
-
-    // Get user stats for progress calculation
-    let stats = sqlx::query(
-        r#"
-        SELECT 
-            (SELECT COUNT(*) FROM reviews WHERE reviewer_id = $1) as total_reviews,
-            (SELECT COUNT(*) FROM reviews WHERE reviewer_id = $1 AND comments_count >= 10) as deep_reviews,
-            (SELECT COUNT(*) FROM pull_requests WHERE author_id = $1) as prs_authored,
-            (SELECT COUNT(*) FROM pull_requests WHERE author_id = $1 AND state = 'merged') as prs_merged
-        "#,
-    )
-    .bind(user_id)
-    .fetch_one(pool)
-    .await?;
-
-    let total_reviews: i64 = stats.get("total_reviews");
-    let first_reviews: i64 = 0; // Would need first_review_at tracking per PR
-    let deep_reviews: i64 = stats.get("deep_reviews");
-    let prs_authored: i64 = stats.get("prs_authored");
-    let prs_merged: i64 = stats.get("prs_merged");
+    let unlocked = match scope {
+        None => list_for_user(pool, user_id).await?,
+        Some(scope) => list_for_user_in_scope(pool, user_id, scope).await?,
+    };
+    let unlocked: std::collections::HashSet<String> =
+        unlocked.into_iter().map(|a| a.achievement_id).collect();
+
+    // Evaluate each achievement's rule
+    let rules = crate::achievement_rules::list_rules(pool).await?;
+    let rules: Vec<AchievementRule> = match scope {
+        None => rules,
+        Some(scope) => rules.iter().filter_map(|r| r.scoped(scope)).collect(),
+    };
+    let values = crate::achievement_rules::metric_values(pool, user_id, &rules).await?;
 
     let mut progress_list: Vec<AchievementProgress> = achievements
         .into_iter()
+        .filter(|a| scope.is_none() || rules.iter().any(|r| r.achievement_id == a.id))
         .map(|a| {
-            let (current, target) = get_progress_values(
-                &a.id,
-                total_reviews,
-                first_reviews,
-                deep_reviews,
-                prs_authored,
-                prs_merged,
-            );
+            let rule = rules.iter().position(|r| r.achievement_id == a.id);
+            let (current, target) = rule.map(|i| rules[i].progress(values[i])).unwrap_or((0, 1));
             let is_unlocked = unlocked.contains(&a.id);
             let progress_pct = if target > 0 {
                 ((current as f64 / target as f64) * 100.0).min(100.0)
@@ -413,7 +795,7 @@ pub async fn get_user_progress(
                 description: a.description,
                 xp_reward: a.xp_reward,
                 rarity: a.rarity,
-                category: categorize_achievement(&a.id),
+                category: categorize_achievement(&rules, &a.id),
                 current,
                 target,
                 progress_pct,
@@ -434,42 +816,3 @@ pub async fn get_user_progress(
 
     Ok(progress_list)
 }
-
-/// Get current/target values for a specific achievement
-fn get_progress_values(
-    achievement_id: &str,
-    total_reviews: i64,
-    first_reviews: i64,
-    deep_reviews: i64,
-    prs_authored: i64,
-    prs_merged: i64,
-) -> (i64, i64) {
-    match achievement_id {
-        // Review milestones
-        "first_review" => (total_reviews.min(1), 1),
-        "review_10" => (total_reviews.min(10), 10),
-        "review_50" => (total_reviews.min(50), 50),
-        "review_100" => (total_reviews.min(100), 100),
-        "review_500" => (total_reviews.min(500), 500),
-        "review_1000" => (total_reviews.min(1000), 1000),
-        // Speed achievements
-        "first_responder" => (first_reviews.min(25), 25),
-        "speed_demon" => (0, 10), // Would need to query fast reviews separately
-        // Quality achievements
-        "thorough" => (deep_reviews.min(5), 5),
-        "bug_hunter" => (0, 10), // Would need AI categorization data
-        "nitpicker" => (0, 50),  // Would need nit comment tracking
-        // PR author achievements
-        "first_pr" => (prs_authored.min(1), 1),
-        "pr_merged_10" => (prs_merged.min(10), 10),
-        "pr_merged_100" => (prs_merged.min(100), 100),
-        // Streaks and special - can't easily calculate without more queries
-        "review_streak_7" => (0, 7),
-        "review_streak_30" => (0, 30),
-        "comeback_kid" => (0, 1),
-        "review_rampage" => (0, 5),
-        "the_closer" => (0, 10),
-        "helpful" => (0, 10),
-        _ => (0, 1),
-    }
-}
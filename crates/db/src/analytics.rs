@@ -0,0 +1,122 @@
+//! PR lifecycle queries
+//!
+//! Merged PRs with their review milestones, for cycle-time analytics in
+//! `processor::analytics` and the coverage report in `processor::coverage`.
+
+use chrono::{DateTime, Utc};
+use common::models::Review;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::reviews::parse_review_state;
+
+/// A merged PR and when it reached each review milestone. Reviews by the
+/// author or bots, and reviews after the merge, don't count.
+#[derive(Debug, Clone)]
+pub struct MergedPr {
+    pub pr_id: Uuid,
+    pub number: i32,
+    pub title: String,
+    pub author_id: Uuid,
+    pub author_login: String,
+    pub created_at: DateTime<Utc>,
+    pub merged_at: DateTime<Utc>,
+    /// First review before the merge
+    pub first_review_at: Option<DateTime<Utc>>,
+    /// Latest approval before the merge
+    pub last_approval_at: Option<DateTime<Utc>>,
+}
+
+/// PRs of a repo merged since `since`, oldest merge first
+pub async fn merged_prs(
+    pool: &PgPool,
+    repo_id: Uuid,
+    since: DateTime<Utc>,
+) -> Result<Vec<MergedPr>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            p.id,
+            p.number,
+            p.title,
+            p.author_id,
+            author.login as author_login,
+            p.created_at,
+            p.merged_at,
+            rs.first_review_at,
+            rs.last_approval_at
+        FROM pull_requests p
+        JOIN users author ON author.id = p.author_id
+        LEFT JOIN LATERAL (
+            SELECT
+                MIN(r.submitted_at) FILTER (
+                    WHERE r.submitted_at <= p.merged_at
+                ) as first_review_at,
+                MAX(r.submitted_at) FILTER (
+                    WHERE r.state = 'approved' AND r.submitted_at <= p.merged_at
+                ) as last_approval_at
+            FROM reviews r
+            JOIN users u ON u.id = r.reviewer_id
+            WHERE r.pr_id = p.id
+              AND r.reviewer_id <> p.author_id
+              AND u.login NOT LIKE '%[bot]'
+        ) rs ON true
+        WHERE p.repo_id = $1
+          AND p.merged_at IS NOT NULL
+          AND p.merged_at >= $2
+        ORDER BY p.merged_at ASC
+        "#,
+    )
+    .bind(repo_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| MergedPr {
+            pr_id: r.get("id"),
+            number: r.get("number"),
+            title: r.get("title"),
+            author_id: r.get("author_id"),
+            author_login: r.get("author_login"),
+            created_at: r.get("created_at"),
+            merged_at: r.get("merged_at"),
+            first_review_at: r.get("first_review_at"),
+            last_approval_at: r.get("last_approval_at"),
+        })
+        .collect())
+}
+
+/// Reviews on the given PRs, other than by each PR's author (bots excluded)
+pub async fn reviews_for_prs(pool: &PgPool, pr_ids: &[Uuid]) -> Result<Vec<Review>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT r.id, r.pr_id, r.reviewer_id, r.github_id, r.state, r.body, r.comments_count, r.submitted_at
+        FROM reviews r
+        JOIN pull_requests p ON p.id = r.pr_id
+        JOIN users u ON u.id = r.reviewer_id
+        WHERE r.pr_id = ANY($1)
+          AND r.reviewer_id <> p.author_id
+          AND u.login NOT LIKE '%[bot]'
+        ORDER BY r.submitted_at ASC
+        "#,
+    )
+    .bind(pr_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| Review {
+            id: r.get("id"),
+            pr_id: r.get("pr_id"),
+            reviewer_id: r.get("reviewer_id"),
+            github_id: r.get("github_id"),
+            state: parse_review_state(r.get("state")),
+            body: r.get("body"),
+            comments_count: r.get("comments_count"),
+            submitted_at: r.get("submitted_at"),
+        })
+        .collect())
+}
@@ -0,0 +1,155 @@
+//! Team working calendars
+//!
+//! Review latency is measured in the reviewer's working hours (see
+//! `processor::latency`). A user's calendar is the one of the first team, by
+//! name, they belong to that has a calendar; users without one are measured
+//! around the clock.
+
+use common::models::WorkCalendar;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+fn row_to_calendar(r: &PgRow) -> WorkCalendar {
+    WorkCalendar {
+        timezone: r.get("timezone"),
+        work_days: r.get("work_days"),
+        day_start_hour: r.get("day_start_hour"),
+        day_end_hour: r.get("day_end_hour"),
+        holidays: r.get("holidays"),
+    }
+}
+
+/// A team's calendar, None if it has none
+pub async fn get(pool: &PgPool, team_id: Uuid) -> Result<Option<WorkCalendar>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT timezone, work_days, day_start_hour, day_end_hour, holidays
+        FROM team_calendars
+        WHERE team_id = $1
+        "#,
+    )
+    .bind(team_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.as_ref().map(row_to_calendar))
+}
+
+/// Set a team's calendar and touch its members' reviews so the next
+/// incremental recalculation rescores them
+pub async fn set(pool: &PgPool, team_id: Uuid, calendar: &WorkCalendar) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO team_calendars (team_id, timezone, work_days, day_start_hour, day_end_hour, holidays)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (team_id) DO UPDATE SET
+            timezone = EXCLUDED.timezone,
+            work_days = EXCLUDED.work_days,
+            day_start_hour = EXCLUDED.day_start_hour,
+            day_end_hour = EXCLUDED.day_end_hour,
+            holidays = EXCLUDED.holidays,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(team_id)
+    .bind(&calendar.timezone)
+    .bind(&calendar.work_days)
+    .bind(calendar.day_start_hour)
+    .bind(calendar.day_end_hour)
+    .bind(&calendar.holidays)
+    .execute(&mut *tx)
+    .await?;
+
+    touch_member_reviews(&mut tx, team_id).await?;
+    tx.commit().await
+}
+
+/// Remove a team's calendar. Returns false if it had none.
+pub async fn delete(pool: &PgPool, team_id: Uuid) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let deleted = sqlx::query("DELETE FROM team_calendars WHERE team_id = $1")
+        .bind(team_id)
+        .execute(&mut *tx)
+        .await?;
+    if deleted.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    touch_member_reviews(&mut tx, team_id).await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// The calendar of every user who has one
+pub async fn by_user(pool: &PgPool) -> Result<HashMap<Uuid, WorkCalendar>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT DISTINCT ON (tm.user_id)
+            tm.user_id, tc.timezone, tc.work_days, tc.day_start_hour, tc.day_end_hour, tc.holidays
+        FROM team_members tm
+        JOIN teams t ON t.id = tm.team_id
+        JOIN team_calendars tc ON tc.team_id = tm.team_id
+        ORDER BY tm.user_id, t.name
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| (r.get("user_id"), row_to_calendar(r)))
+        .collect())
+}
+
+/// One user's calendar, None if they have none
+pub async fn for_user(pool: &PgPool, user_id: Uuid) -> Result<Option<WorkCalendar>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT tc.timezone, tc.work_days, tc.day_start_hour, tc.day_end_hour, tc.holidays
+        FROM team_members tm
+        JOIN teams t ON t.id = tm.team_id
+        JOIN team_calendars tc ON tc.team_id = tm.team_id
+        WHERE tm.user_id = $1
+        ORDER BY t.name
+        LIMIT 1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.as_ref().map(row_to_calendar))
+}
+
+/// Touch one user's reviews, e.g. after they joined or left a team with a
+/// calendar
+pub async fn touch_user_reviews(pool: &PgPool, user_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE reviews SET touched_at = NOW() WHERE reviewer_id = $1")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Bump touched_at on every review by a member of the team
+async fn touch_member_reviews(
+    tx: &mut Transaction<'_, Postgres>,
+    team_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE reviews r SET touched_at = NOW()
+        FROM team_members tm
+        WHERE tm.team_id = $1 AND r.reviewer_id = tm.user_id
+        "#,
+    )
+    .bind(team_id)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
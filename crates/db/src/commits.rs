@@ -22,7 +22,11 @@ pub async fn insert(
         ON CONFLICT (pr_id, sha) DO UPDATE
         SET author_id = EXCLUDED.author_id,
             committed_at = EXCLUDED.committed_at,
-            message = EXCLUDED.message
+            message = EXCLUDED.message,
+            touched_at = CASE
+                WHEN commits.committed_at IS DISTINCT FROM EXCLUDED.committed_at THEN NOW()
+                ELSE commits.touched_at
+            END
         RETURNING id, pr_id, sha, author_id, committed_at, message, created_at
         "#,
     )
@@ -99,3 +103,92 @@ pub async fn list_all(pool: &PgPool) -> Result<Vec<Commit>, sqlx::Error> {
         })
         .collect())
 }
+
+/// List commits on the given PRs (for incremental recalculation)
+pub async fn list_for_prs(pool: &PgPool, pr_ids: &[Uuid]) -> Result<Vec<Commit>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, pr_id, sha, author_id, committed_at, message, created_at
+        FROM commits
+        WHERE pr_id = ANY($1)
+        ORDER BY committed_at ASC
+        "#,
+    )
+    .bind(pr_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| Commit {
+            id: r.get("id"),
+            pr_id: r.get("pr_id"),
+            sha: r.get("sha"),
+            author_id: r.get("author_id"),
+            committed_at: r.get("committed_at"),
+            message: r.get("message"),
+            created_at: r.get("created_at"),
+        })
+        .collect())
+}
+
+/// Lines a commit changed in one file, on the old side of the diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    pub path: String,
+    pub old_start: i32,
+    pub old_lines: i32,
+}
+
+/// Commits on a PR whose changed lines haven't been fetched yet, as (id, sha)
+pub async fn list_without_hunks(
+    pool: &PgPool,
+    pr_id: Uuid,
+) -> Result<Vec<(Uuid, String)>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, sha
+        FROM commits
+        WHERE pr_id = $1 AND hunks_fetched_at IS NULL
+        ORDER BY committed_at ASC
+        "#,
+    )
+    .bind(pr_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.get("id"), r.get("sha")))
+        .collect())
+}
+
+/// Store the lines a commit changed, replacing any stored before
+pub async fn set_hunks(pool: &PgPool, commit_id: Uuid, hunks: &[Hunk]) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM commit_hunks WHERE commit_id = $1")
+        .bind(commit_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO commit_hunks (commit_id, path, old_start, old_lines)
+        SELECT $1, * FROM UNNEST($2::text[], $3::int[], $4::int[])
+        "#,
+    )
+    .bind(commit_id)
+    .bind(hunks.iter().map(|h| h.path.clone()).collect::<Vec<_>>())
+    .bind(hunks.iter().map(|h| h.old_start).collect::<Vec<_>>())
+    .bind(hunks.iter().map(|h| h.old_lines).collect::<Vec<_>>())
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("UPDATE commits SET hunks_fetched_at = NOW() WHERE id = $1")
+        .bind(commit_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await
+}
@@ -0,0 +1,142 @@
+//! Time-decayed XP
+//!
+//! Session XP loses half its weight every `half_life_days`. Each review stores
+//! `decay_weight = 2^((submitted_at - epoch) / half_life)`, so decayed XP at
+//! time `t` is `SUM(xp_earned * decay_weight) * 2^((epoch - t) / half_life)`:
+//! a plain sum over any window, repo or team, scaled by a factor that is the
+//! same for everyone. Weights are filled in after each recalculation and
+//! recomputed when the half-life changes or the epoch is rebased.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+
+/// Half-life used until an admin sets one
+pub const DEFAULT_HALF_LIFE_DAYS: f64 = 14.0;
+
+/// Move the epoch to now once it is this many half-lives old, keeping new
+/// weights (which grow with time since the epoch) well inside f64 range
+const REBASE_AFTER_HALF_LIVES: f64 = 64.0;
+
+/// Smallest exponent used; older XP has decayed to nothing anyway (and
+/// Postgres errors on float underflow)
+const MIN_EXPONENT: f64 = -1000.0;
+
+/// Decay configuration
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DecaySettings {
+    pub half_life_days: f64,
+    /// Reference time of the stored weights
+    pub epoch: DateTime<Utc>,
+}
+
+impl DecaySettings {
+    /// Half-lives elapsed from `from` to `to` (negative if `to` is earlier)
+    fn half_lives(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+        (to - from).num_milliseconds() as f64 / (self.half_life_days * 86_400_000.0)
+    }
+
+    /// Stored weight of XP earned at `earned_at`
+    pub fn weight(&self, earned_at: DateTime<Utc>) -> f64 {
+        self.half_lives(self.epoch, earned_at)
+            .max(MIN_EXPONENT)
+            .exp2()
+    }
+
+    /// Factor turning `SUM(xp_earned * decay_weight)` into XP decayed to `at`
+    pub fn scale_at(&self, at: DateTime<Utc>) -> f64 {
+        self.half_lives(at, self.epoch).max(MIN_EXPONENT).exp2()
+    }
+
+    fn needs_rebase(&self, now: DateTime<Utc>) -> bool {
+        self.half_lives(self.epoch, now) > REBASE_AFTER_HALF_LIVES
+    }
+}
+
+/// Current decay settings
+pub async fn get_settings(pool: &PgPool) -> Result<DecaySettings, sqlx::Error> {
+    let row = sqlx::query("SELECT half_life_days, epoch FROM xp_decay WHERE id = TRUE")
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(match row {
+        Some(r) => DecaySettings {
+            half_life_days: r.get("half_life_days"),
+            epoch: r.get("epoch"),
+        },
+        None => DecaySettings {
+            half_life_days: DEFAULT_HALF_LIFE_DAYS,
+            epoch: Utc::now(),
+        },
+    })
+}
+
+/// Change the half-life and recompute every weight
+pub async fn set_half_life(
+    pool: &PgPool,
+    half_life_days: f64,
+) -> Result<DecaySettings, sqlx::Error> {
+    rebase(pool, Some(half_life_days)).await?;
+    get_settings(pool).await
+}
+
+/// Fill in weights for reviews that don't have one yet, rebasing first if
+/// the epoch is too old. Returns the number of reviews updated.
+pub async fn refresh_weights(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let settings = get_settings(pool).await?;
+    if settings.needs_rebase(Utc::now()) {
+        return rebase(pool, None).await;
+    }
+
+    let result = sqlx::query(&format!(
+        "{} AND r.decay_weight IS NULL",
+        UPDATE_WEIGHTS_SQL
+    ))
+    .bind(MIN_EXPONENT)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Recompute `reviews.decay_weight` from the stored settings
+const UPDATE_WEIGHTS_SQL: &str = r#"
+    UPDATE reviews r
+    SET decay_weight = power(
+        2::float8,
+        GREATEST(
+            EXTRACT(EPOCH FROM (r.submitted_at - d.epoch)) / (d.half_life_days * 86400.0),
+            $1
+        )
+    )
+    FROM xp_decay d
+    WHERE d.id = TRUE
+"#;
+
+/// Move the epoch to now (optionally changing the half-life) and recompute
+/// every weight, in one transaction
+async fn rebase(pool: &PgPool, half_life_days: Option<f64>) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO xp_decay (id, half_life_days, epoch)
+        VALUES (TRUE, COALESCE($1, $2), NOW())
+        ON CONFLICT (id) DO UPDATE SET
+            half_life_days = COALESCE($1, xp_decay.half_life_days),
+            epoch = NOW()
+        "#,
+    )
+    .bind(half_life_days)
+    .bind(DEFAULT_HALF_LIFE_DAYS)
+    .execute(&mut *tx)
+    .await?;
+
+    let result = sqlx::query(UPDATE_WEIGHTS_SQL)
+        .bind(MIN_EXPONENT)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(result.rows_affected())
+}
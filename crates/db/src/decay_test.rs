@@ -0,0 +1,44 @@
+//! Tests for time-decayed XP weights
+
+#[cfg(test)]
+mod tests {
+    use crate::decay::DecaySettings;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn settings() -> DecaySettings {
+        DecaySettings {
+            half_life_days: 14.0,
+            epoch: Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+        }
+    }
+
+    fn decayed(settings: &DecaySettings, xp: f64, age_days: i64) -> f64 {
+        let now = settings.epoch + Duration::days(100);
+        xp * settings.weight(now - Duration::days(age_days)) * settings.scale_at(now)
+    }
+
+    #[test]
+    fn test_xp_halves_every_half_life() {
+        let s = settings();
+        assert!((decayed(&s, 100.0, 0) - 100.0).abs() < 1e-9);
+        assert!((decayed(&s, 100.0, 14) - 50.0).abs() < 1e-9);
+        assert!((decayed(&s, 100.0, 28) - 25.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_decay_does_not_depend_on_epoch() {
+        let a = settings();
+        let b = DecaySettings {
+            epoch: a.epoch - Duration::days(300),
+            ..a
+        };
+        assert!((decayed(&a, 80.0, 10) - decayed(&b, 80.0, 10)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ancient_xp_decays_to_zero() {
+        let s = settings();
+        let weight = s.weight(s.epoch - Duration::days(365 * 1000));
+        assert!(weight > 0.0 && weight < 1e-300);
+    }
+}
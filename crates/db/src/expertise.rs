@@ -0,0 +1,57 @@
+//! Review comments by file, the input of the expertise map
+//!
+//! Aggregation into directories, extensions and languages, and the recency
+//! and quality weighting, live in `processor::expertise`.
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+/// A review comment on a file
+#[derive(Debug, Clone)]
+pub struct PathComment {
+    pub user_id: Uuid,
+    pub path: String,
+    /// AI quality score (1-10), None if not categorized yet
+    pub quality_score: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Comments on files of PRs the commenter didn't author, since `since`,
+/// optionally limited to one user and one repo. Bots are excluded.
+pub async fn path_comments(
+    pool: &PgPool,
+    user_id: Option<Uuid>,
+    repo_id: Option<Uuid>,
+    since: DateTime<Utc>,
+) -> Result<Vec<PathComment>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT rc.user_id, rc.path, rc.quality_score, rc.created_at
+        FROM review_comments rc
+        JOIN pull_requests p ON p.id = rc.pr_id
+        JOIN users u ON u.id = rc.user_id
+        WHERE rc.path IS NOT NULL
+          AND rc.user_id <> p.author_id
+          AND rc.created_at >= $1
+          AND ($2::uuid IS NULL OR rc.user_id = $2)
+          AND ($3::uuid IS NULL OR p.repo_id = $3)
+          AND u.login NOT LIKE '%[bot]'
+        "#,
+    )
+    .bind(since)
+    .bind(user_id)
+    .bind(repo_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| PathComment {
+            user_id: r.get("user_id"),
+            path: r.get("path"),
+            quality_score: r.get("quality_score"),
+            created_at: r.get("created_at"),
+        })
+        .collect())
+}
@@ -0,0 +1,567 @@
+//! Anti-gaming flags and XP exclusions
+//!
+//! The analysis in `processor::gaming` reads raw activity from here and stores
+//! what it finds as flags. Penalizing a flag excludes the (PR, reviewer) pairs
+//! involved from scoring and touches their reviews, so the next recalculation
+//! zeroes their XP. Exclusions are recorded per flag: dismissing a penalized
+//! flag lifts its own, and a pair stays excluded while any other penalized
+//! flag still covers it.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Kind of suspicious pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagKind {
+    /// A group of users mostly approving each other's PRs
+    MutualApprovals,
+    /// One user posting the same comment over and over
+    DuplicateComments,
+    /// Approval too soon after the PR was opened or pushed to be a real read
+    FastApproval,
+    /// A reviewer approving one author far more than the baseline
+    AuthorFavoritism,
+}
+
+impl FlagKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::MutualApprovals => "mutual_approvals",
+            Self::DuplicateComments => "duplicate_comments",
+            Self::FastApproval => "fast_approval",
+            Self::AuthorFavoritism => "author_favoritism",
+        }
+    }
+}
+
+/// Review state of a flag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagStatus {
+    Open,
+    Dismissed,
+    Penalized,
+}
+
+impl FlagStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Dismissed => "dismissed",
+            Self::Penalized => "penalized",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        [Self::Open, Self::Dismissed, Self::Penalized]
+            .into_iter()
+            .find(|status| status.as_str() == s)
+    }
+}
+
+/// Approvals one reviewer gave one author
+#[derive(Debug, Clone)]
+pub struct ApprovalCount {
+    pub reviewer_id: Uuid,
+    pub author_id: Uuid,
+    /// Distinct PRs approved
+    pub approvals: i64,
+    pub pr_ids: Vec<Uuid>,
+}
+
+/// Approvals between every (reviewer, author) pair, bots and self-approvals
+/// excluded
+pub async fn approval_counts(pool: &PgPool) -> Result<Vec<ApprovalCount>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            r.reviewer_id,
+            p.author_id,
+            COUNT(DISTINCT p.id) as approvals,
+            array_agg(DISTINCT p.id) as pr_ids
+        FROM reviews r
+        JOIN pull_requests p ON p.id = r.pr_id
+        JOIN users ru ON ru.id = r.reviewer_id
+        JOIN users au ON au.id = p.author_id
+        WHERE r.state = 'approved'
+          AND r.reviewer_id <> p.author_id
+          AND ru.login NOT LIKE '%[bot]'
+          AND au.login NOT LIKE '%[bot]'
+        GROUP BY r.reviewer_id, p.author_id
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| ApprovalCount {
+            reviewer_id: r.get("reviewer_id"),
+            author_id: r.get("author_id"),
+            approvals: r.get("approvals"),
+            pr_ids: r.get("pr_ids"),
+        })
+        .collect())
+}
+
+/// A top-level review comment body
+#[derive(Debug, Clone)]
+pub struct CommentBody {
+    pub user_id: Uuid,
+    pub pr_id: Uuid,
+    pub body: String,
+}
+
+/// Top-level review comments on other people's PRs, bots excluded. With
+/// `since`, only users who commented after it (all of their comments).
+pub async fn comment_bodies(
+    pool: &PgPool,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<CommentBody>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT rc.user_id, rc.pr_id, rc.body
+        FROM review_comments rc
+        JOIN pull_requests p ON p.id = rc.pr_id
+        JOIN users u ON u.id = rc.user_id
+        WHERE rc.in_reply_to_id IS NULL
+          AND rc.user_id <> p.author_id
+          AND u.login NOT LIKE '%[bot]'
+          AND ($1::timestamptz IS NULL OR rc.user_id IN (
+              SELECT user_id FROM review_comments WHERE touched_at > $1
+          ))
+        "#,
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| CommentBody {
+            user_id: r.get("user_id"),
+            pr_id: r.get("pr_id"),
+            body: r.get("body"),
+        })
+        .collect())
+}
+
+/// An approval without comments soon after the PR was opened or last pushed
+#[derive(Debug, Clone)]
+pub struct FastApproval {
+    pub review_id: Uuid,
+    pub pr_id: Uuid,
+    pub reviewer_id: Uuid,
+    pub author_id: Uuid,
+    pub submitted_at: DateTime<Utc>,
+    /// Seconds from the PR opening or the last earlier commit to the approval
+    pub seconds: i64,
+}
+
+/// Approvals without comments less than `max_secs` after the PR was opened or
+/// last pushed, bots and self-approvals excluded. With `since`, only reviewers
+/// with a review, or a push on a PR they reviewed, after it (all of their
+/// approvals).
+pub async fn fast_approvals(
+    pool: &PgPool,
+    max_secs: i64,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<FastApproval>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            r.id, r.pr_id, r.reviewer_id, p.author_id, r.submitted_at,
+            EXTRACT(EPOCH FROM (r.submitted_at - GREATEST(p.created_at, latest.last_commit_at)))::bigint as seconds
+        FROM reviews r
+        JOIN pull_requests p ON p.id = r.pr_id
+        JOIN users u ON u.id = r.reviewer_id
+        JOIN LATERAL (
+            SELECT MAX(committed_at) as last_commit_at
+            FROM commits c
+            WHERE c.pr_id = r.pr_id AND c.committed_at < r.submitted_at
+        ) latest ON true
+        WHERE r.state = 'approved'
+          AND r.comments_count = 0
+          AND r.reviewer_id <> p.author_id
+          AND u.login NOT LIKE '%[bot]'
+          AND r.submitted_at >= p.created_at
+          AND r.submitted_at < GREATEST(p.created_at, latest.last_commit_at) + make_interval(secs => $1)
+          AND ($2::timestamptz IS NULL OR r.reviewer_id IN (
+              SELECT reviewer_id FROM reviews WHERE touched_at > $2
+              UNION
+              SELECT r2.reviewer_id
+              FROM commits c
+              JOIN reviews r2 ON r2.pr_id = c.pr_id
+              WHERE c.touched_at > $2
+          ))
+        "#,
+    )
+    .bind(max_secs as f64)
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| FastApproval {
+            review_id: r.get("id"),
+            pr_id: r.get("pr_id"),
+            reviewer_id: r.get("reviewer_id"),
+            author_id: r.get("author_id"),
+            submitted_at: r.get("submitted_at"),
+            seconds: r.get("seconds"),
+        })
+        .collect())
+}
+
+/// Time of the last analysis run (None = never run)
+pub async fn get_last_run(pool: &PgPool) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    let row = sqlx::query("SELECT last_run_at FROM gaming_state WHERE id = TRUE")
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.and_then(|r| r.get("last_run_at")))
+}
+
+/// Record the time of the last analysis run
+pub async fn set_last_run(pool: &PgPool, run_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO gaming_state (id, last_run_at)
+        VALUES (TRUE, $1)
+        ON CONFLICT (id) DO UPDATE SET last_run_at = EXCLUDED.last_run_at
+        "#,
+    )
+    .bind(run_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// A suspicious pattern found by the analysis
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub kind: FlagKind,
+    /// Identifies the same finding across runs
+    pub fingerprint: String,
+    pub user_id: Uuid,
+    pub other_user_id: Option<Uuid>,
+    pub details: String,
+    /// (PR, user) pairs whose XP is involved
+    pub pairs: Vec<(Uuid, Uuid)>,
+}
+
+/// Store findings: new ones become open flags and open flags are refreshed.
+/// A penalized flag whose pattern continues gets the new pairs appended and
+/// excluded too; dismissed flags are left alone. Returns the number of new
+/// flags.
+pub async fn upsert_flags(pool: &PgPool, findings: &[Finding]) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut created = 0;
+
+    for finding in findings {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO gaming_flags (
+                kind, fingerprint, user_id, other_user_id, details, pair_pr_ids, pair_user_ids
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (fingerprint) DO UPDATE SET
+                details = EXCLUDED.details,
+                pair_pr_ids = EXCLUDED.pair_pr_ids,
+                pair_user_ids = EXCLUDED.pair_user_ids,
+                updated_at = NOW()
+            WHERE gaming_flags.status = 'open'
+            RETURNING (xmax = 0) as inserted
+            "#,
+        )
+        .bind(finding.kind.as_str())
+        .bind(&finding.fingerprint)
+        .bind(finding.user_id)
+        .bind(finding.other_user_id)
+        .bind(&finding.details)
+        .bind(finding.pairs.iter().map(|p| p.0).collect::<Vec<_>>())
+        .bind(finding.pairs.iter().map(|p| p.1).collect::<Vec<_>>())
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        match row {
+            Some(r) if r.get::<bool, _>("inserted") => created += 1,
+            Some(_) => {}
+            None => extend_penalized(&mut tx, finding).await?,
+        }
+    }
+
+    tx.commit().await?;
+    Ok(created)
+}
+
+/// Pairs of `found` not already in `stored`, in order
+pub fn added_pairs(stored: &[(Uuid, Uuid)], found: &[(Uuid, Uuid)]) -> Vec<(Uuid, Uuid)> {
+    let mut seen: HashSet<(Uuid, Uuid)> = stored.iter().copied().collect();
+    found.iter().filter(|p| seen.insert(**p)).copied().collect()
+}
+
+/// Append a finding's new pairs to its penalized flag and exclude them, so
+/// the penalty covers later activity matching the same pattern
+async fn extend_penalized(
+    tx: &mut Transaction<'_, Postgres>,
+    finding: &Finding,
+) -> Result<(), sqlx::Error> {
+    let Some(row) = sqlx::query(
+        r#"
+        SELECT id, pair_pr_ids, pair_user_ids FROM gaming_flags
+        WHERE fingerprint = $1 AND status = 'penalized'
+        FOR UPDATE
+        "#,
+    )
+    .bind(&finding.fingerprint)
+    .fetch_optional(&mut **tx)
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let id: i64 = row.get("id");
+    let stored: Vec<(Uuid, Uuid)> = row
+        .get::<Vec<Uuid>, _>("pair_pr_ids")
+        .into_iter()
+        .zip(row.get::<Vec<Uuid>, _>("pair_user_ids"))
+        .collect();
+    let (pr_ids, user_ids): (Vec<Uuid>, Vec<Uuid>) =
+        added_pairs(&stored, &finding.pairs).into_iter().unzip();
+    if pr_ids.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE gaming_flags SET
+            details = $2,
+            pair_pr_ids = pair_pr_ids || $3,
+            pair_user_ids = pair_user_ids || $4,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(&finding.details)
+    .bind(&pr_ids)
+    .bind(&user_ids)
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO xp_exclusions (pr_id, user_id, flag_id)
+        SELECT x.pr_id, x.user_id, $1
+        FROM UNNEST($2::uuid[], $3::uuid[]) AS x(pr_id, user_id)
+        ON CONFLICT (pr_id, user_id, flag_id) DO NOTHING
+        "#,
+    )
+    .bind(id)
+    .bind(&pr_ids)
+    .bind(&user_ids)
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE reviews r SET touched_at = NOW()
+        FROM UNNEST($1::uuid[], $2::uuid[]) AS x(pr_id, user_id)
+        WHERE r.pr_id = x.pr_id AND r.reviewer_id = x.user_id
+        "#,
+    )
+    .bind(&pr_ids)
+    .bind(&user_ids)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// A flag as shown to admins
+#[derive(Debug, Clone, Serialize)]
+pub struct GamingFlag {
+    pub id: i64,
+    pub kind: String,
+    pub user: String,
+    pub other_user: Option<String>,
+    pub details: String,
+    pub status: String,
+    /// (PR, user) pairs whose XP is involved
+    pub pairs: i32,
+    /// XP those pairs currently earn (0 once penalized and recalculated)
+    pub xp_involved: i64,
+    pub detected_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+const FLAG_SELECT: &str = r#"
+    SELECT
+        f.id, f.kind, u.login as user_login, o.login as other_login, f.details,
+        f.status, cardinality(f.pair_pr_ids) as pairs,
+        (
+            SELECT COALESCE(SUM(r.xp_earned), 0)::bigint
+            FROM UNNEST(f.pair_pr_ids, f.pair_user_ids) AS x(pr_id, user_id)
+            JOIN reviews r ON r.pr_id = x.pr_id AND r.reviewer_id = x.user_id
+        ) as xp_involved,
+        f.detected_at, f.updated_at, f.resolved_at
+    FROM gaming_flags f
+    JOIN users u ON u.id = f.user_id
+    LEFT JOIN users o ON o.id = f.other_user_id
+"#;
+
+fn row_to_flag(r: &sqlx::postgres::PgRow) -> GamingFlag {
+    GamingFlag {
+        id: r.get("id"),
+        kind: r.get("kind"),
+        user: r.get("user_login"),
+        other_user: r.get("other_login"),
+        details: r.get("details"),
+        status: r.get("status"),
+        pairs: r.get("pairs"),
+        xp_involved: r.get("xp_involved"),
+        detected_at: r.get("detected_at"),
+        updated_at: r.get("updated_at"),
+        resolved_at: r.get("resolved_at"),
+    }
+}
+
+/// List flags, newest first, optionally by status
+pub async fn list_flags(
+    pool: &PgPool,
+    status: Option<FlagStatus>,
+    limit: i64,
+) -> Result<Vec<GamingFlag>, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        "{} WHERE $1::text IS NULL OR f.status = $1 ORDER BY f.id DESC LIMIT $2",
+        FLAG_SELECT
+    ))
+    .bind(status.map(|s| s.as_str()))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(row_to_flag).collect())
+}
+
+/// Get a flag by ID
+pub async fn get_flag(pool: &PgPool, id: i64) -> Result<Option<GamingFlag>, sqlx::Error> {
+    let row = sqlx::query(&format!("{} WHERE f.id = $1", FLAG_SELECT))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.as_ref().map(row_to_flag))
+}
+
+/// Penalize a flag: exclude its pairs from scoring and touch their reviews.
+/// Returns the number of pairs newly excluded (not already excluded by
+/// another flag), None if the flag doesn't exist.
+pub async fn penalize(pool: &PgPool, id: i64) -> Result<Option<u64>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let updated = sqlx::query(
+        r#"
+        UPDATE gaming_flags SET status = 'penalized', resolved_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    // The outer query sees exclusions as they were before this insert
+    let row = sqlx::query(
+        r#"
+        WITH inserted AS (
+            INSERT INTO xp_exclusions (pr_id, user_id, flag_id)
+            SELECT DISTINCT x.pr_id, x.user_id, f.id
+            FROM gaming_flags f, UNNEST(f.pair_pr_ids, f.pair_user_ids) AS x(pr_id, user_id)
+            WHERE f.id = $1
+            ON CONFLICT (pr_id, user_id, flag_id) DO NOTHING
+            RETURNING pr_id, user_id
+        )
+        SELECT COUNT(*) as excluded
+        FROM inserted i
+        WHERE NOT EXISTS (
+            SELECT 1 FROM xp_exclusions e
+            WHERE e.pr_id = i.pr_id AND e.user_id = i.user_id
+        )
+        "#,
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    touch_flag_reviews(&mut tx, id).await?;
+    tx.commit().await?;
+    Ok(Some(row.get::<i64, _>("excluded") as u64))
+}
+
+/// Dismiss a flag, lifting its exclusions (pairs other penalized flags cover
+/// stay excluded). Returns false if the flag doesn't exist.
+pub async fn dismiss(pool: &PgPool, id: i64) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let updated = sqlx::query(
+        r#"
+        UPDATE gaming_flags SET status = 'dismissed', resolved_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    let lifted = sqlx::query("DELETE FROM xp_exclusions WHERE flag_id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    if lifted.rows_affected() > 0 {
+        touch_flag_reviews(&mut tx, id).await?;
+    }
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Every excluded (PR, reviewer) pair
+pub async fn excluded_pairs(pool: &PgPool) -> Result<HashSet<(Uuid, Uuid)>, sqlx::Error> {
+    let rows = sqlx::query("SELECT DISTINCT pr_id, user_id FROM xp_exclusions")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.get("pr_id"), r.get("user_id")))
+        .collect())
+}
+
+/// Bump touched_at on the reviews of a flag's pairs so the next incremental
+/// recalculation rescores them
+async fn touch_flag_reviews(
+    tx: &mut Transaction<'_, Postgres>,
+    id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE reviews r SET touched_at = NOW()
+        FROM gaming_flags f, UNNEST(f.pair_pr_ids, f.pair_user_ids) AS x(pr_id, user_id)
+        WHERE f.id = $1 AND r.pr_id = x.pr_id AND r.reviewer_id = x.user_id
+        "#,
+    )
+    .bind(id)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,30 @@
+//! Tests for anti-gaming flag storage
+
+#[cfg(test)]
+mod tests {
+    use crate::gaming::added_pairs;
+    use uuid::Uuid;
+
+    fn id(n: u128) -> Uuid {
+        Uuid::from_u128(n)
+    }
+
+    #[test]
+    fn test_penalized_flag_gains_only_new_pairs() {
+        // A penalized ring keeps approving: the same fingerprint, more pairs
+        let stored = vec![(id(1), id(10)), (id(2), id(11))];
+        let found = vec![
+            (id(1), id(10)),
+            (id(3), id(10)),
+            (id(2), id(11)),
+            (id(4), id(11)),
+            (id(3), id(10)),
+        ];
+
+        assert_eq!(
+            added_pairs(&stored, &found),
+            vec![(id(3), id(10)), (id(4), id(11))]
+        );
+        assert!(added_pairs(&found, &stored).is_empty());
+    }
+}
@@ -5,16 +5,52 @@ use common::models::{LeaderboardEntry, User, UserStats};
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
-/// Get the review leaderboard for a time period
+/// How a leaderboard scores users
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ranking {
+    /// XP earned since the given time
+    Since(DateTime<Utc>),
+    /// All session XP, decayed by age (see `db::decay`). Achievement rewards
+    /// don't count.
+    Decayed,
+}
+
+impl Ranking {
+    /// Start of the activity window the stats cover
+    pub fn since(&self) -> DateTime<Utc> {
+        match self {
+            Self::Since(since) => *since,
+            Self::Decayed => DateTime::UNIX_EPOCH,
+        }
+    }
+
+    /// Factor applied to `SUM(xp_earned * decay_weight)`, None when not decayed
+    pub(crate) async fn decay_scale(
+        &self,
+        pool: &PgPool,
+        at: DateTime<Utc>,
+    ) -> Result<Option<f64>, sqlx::Error> {
+        match self {
+            Self::Since(_) => Ok(None),
+            Self::Decayed => Ok(Some(crate::decay::get_settings(pool).await?.scale_at(at))),
+        }
+    }
+}
+
+/// Get the review leaderboard for a time period, or ranked by decayed XP
 pub async fn get_leaderboard(
     pool: &PgPool,
     repo_id: Option<Uuid>,
-    since: DateTime<Utc>,
+    ranking: Ranking,
     limit: i32,
 ) -> Result<Vec<LeaderboardEntry>, sqlx::Error> {
+    let decay_scale = ranking.decay_scale(pool, Utc::now()).await?;
+
     // First, get first reviews per PR (the reviewer who submitted first)
     // Then count how many times each user was first
-    // Sum xp_earned from reviews in the period for period-specific XP
+    // Sum xp_earned from reviews in the period for period-specific XP, plus
+    // achievement rewards unlocked in the period (global leaderboard only).
+    // Decayed ranking scores all reviews by weighted XP instead.
     let rows = sqlx::query(
         r#"
         WITH first_reviews AS (
@@ -35,7 +71,15 @@ pub async fn get_leaderboard(
                 COUNT(r.id)::int as reviews_given,
                 COUNT(DISTINCT r.pr_id)::int as prs_reviewed,
                 COALESCE(SUM(r.comments_count), 0)::int as comments_written,
-                COALESCE(SUM(r.xp_earned), 0)::bigint as period_xp,
+                CASE WHEN $4::float8 IS NULL THEN
+                    (COALESCE(SUM(r.xp_earned), 0) + COALESCE((
+                        SELECT SUM(ua.xp_awarded)
+                        FROM user_achievements ua
+                        WHERE ua.user_id = u.id AND ua.unlocked_at >= $1 AND $2::uuid IS NULL
+                    ), 0))::bigint
+                ELSE
+                    ROUND(COALESCE(SUM(r.xp_earned * r.decay_weight), 0) * $4)::bigint
+                END as period_xp,
                 COALESCE((SELECT COUNT(*) FROM first_reviews fr WHERE fr.reviewer_id = u.id), 0)::int as first_reviews
             FROM users u
             LEFT JOIN reviews r ON r.reviewer_id = u.id AND r.submitted_at >= $1
@@ -61,9 +105,10 @@ pub async fn get_leaderboard(
         LIMIT $3
         "#,
     )
-    .bind(since)
+    .bind(ranking.since())
     .bind(repo_id)
     .bind(limit as i64)
+    .bind(decay_scale)
     .fetch_all(pool)
     .await?;
 
@@ -4,19 +4,41 @@ use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
 use tracing::info;
 
+pub mod achievement_rules;
 pub mod achievements;
+pub mod analytics;
+pub mod calendars;
 pub mod commits;
+pub mod decay;
+pub mod expertise;
+pub mod gaming;
 pub mod leaderboard;
 pub mod prs;
+pub mod recalc;
+pub mod recommend;
 pub mod repos;
+pub mod responsiveness;
 pub mod review_comments;
+pub mod review_threads;
 pub mod reviews;
+pub mod scoring_profiles;
 pub mod seasons;
+pub mod stale;
 pub mod teams;
 pub mod users;
+pub mod workload;
+pub mod xp_ledger;
 
+#[cfg(test)]
+mod achievement_rules_test;
+#[cfg(test)]
+mod decay_test;
+#[cfg(test)]
+mod gaming_test;
 #[cfg(test)]
 mod leaderboard_test;
+#[cfg(test)]
+mod review_threads_test;
 
 /// Create a database connection pool
 pub async fn create_pool(database_url: &str) -> Result<PgPool, sqlx::Error> {
@@ -36,6 +58,11 @@ pub async fn run_migrations(pool: &PgPool) -> Result<(), sqlx::Error> {
     let schema = include_str!("../../../migrations/schema.sql");
     sqlx::raw_sql(schema).execute(pool).await?;
 
+    let classified = review_threads::classify_thanks(pool).await?;
+    if classified > 0 {
+        info!("Classified {} review comments for thanks", classified);
+    }
+
     info!("Schema setup complete");
     Ok(())
 }
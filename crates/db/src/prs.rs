@@ -3,8 +3,10 @@
 #![allow(clippy::too_many_arguments)]
 
 use chrono::{DateTime, Utc};
-use common::models::{PrState, PullRequest};
+use common::models::{PrSize, PrState, PullRequest};
+use serde::Serialize;
 use sqlx::{PgPool, Row};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 fn parse_pr_state(s: &str) -> PrState {
@@ -150,6 +152,174 @@ pub async fn get_by_number(
     }))
 }
 
+/// A PR as shown to users: `owner/name` and number
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PrRef {
+    pub repo: String,
+    pub number: i32,
+}
+
+/// Repo and number for the given PR IDs
+pub async fn refs_by_id(
+    pool: &PgPool,
+    pr_ids: &[Uuid],
+) -> Result<HashMap<Uuid, PrRef>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT p.id, r.owner || '/' || r.name as repo, p.number
+        FROM pull_requests p
+        JOIN repositories r ON r.id = p.repo_id
+        WHERE p.id = ANY($1)
+        "#,
+    )
+    .bind(pr_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            (
+                r.get("id"),
+                PrRef {
+                    repo: r.get("repo"),
+                    number: r.get("number"),
+                },
+            )
+        })
+        .collect())
+}
+
+/// When each PR was opened, for the given PRs or all if None
+pub async fn opened_at_by_id(
+    pool: &PgPool,
+    pr_ids: Option<&[Uuid]>,
+) -> Result<HashMap<Uuid, DateTime<Utc>>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, created_at FROM pull_requests WHERE $1::uuid[] IS NULL OR id = ANY($1)",
+    )
+    .bind(pr_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.get("id"), r.get("created_at")))
+        .collect())
+}
+
+/// Record a PR's size. If it changed, the PR's reviews are touched so the
+/// next incremental recalculation rescores them.
+pub async fn set_size(pool: &PgPool, pr_id: Uuid, size: PrSize) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let updated = sqlx::query(
+        r#"
+        UPDATE pull_requests
+        SET additions = $2, deletions = $3, changed_files = $4
+        WHERE id = $1
+          AND (additions, deletions, changed_files) IS DISTINCT FROM ($2, $3, $4)
+        "#,
+    )
+    .bind(pr_id)
+    .bind(size.additions)
+    .bind(size.deletions)
+    .bind(size.changed_files)
+    .execute(&mut *tx)
+    .await?;
+
+    if updated.rows_affected() > 0 {
+        sqlx::query("UPDATE reviews SET touched_at = NOW() WHERE pr_id = $1")
+            .bind(pr_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await
+}
+
+/// Size of each PR whose size is known, for the given PRs or all if None
+pub async fn sizes_by_id(
+    pool: &PgPool,
+    pr_ids: Option<&[Uuid]>,
+) -> Result<HashMap<Uuid, PrSize>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, additions, deletions, changed_files
+        FROM pull_requests
+        WHERE additions IS NOT NULL
+          AND ($1::uuid[] IS NULL OR id = ANY($1))
+        "#,
+    )
+    .bind(pr_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            (
+                r.get("id"),
+                PrSize {
+                    additions: r.get("additions"),
+                    deletions: r.get("deletions"),
+                    changed_files: r.get("changed_files"),
+                },
+            )
+        })
+        .collect())
+}
+
+/// Replace the files recorded for a PR
+pub async fn set_files(pool: &PgPool, pr_id: Uuid, paths: &[String]) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM pr_files WHERE pr_id = $1")
+        .bind(pr_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO pr_files (pr_id, path)
+        SELECT $1, path FROM UNNEST($2::text[]) AS path
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(pr_id)
+    .bind(paths)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+/// Files recorded for each of the given PRs
+pub async fn files_by_pr(
+    pool: &PgPool,
+    pr_ids: &[Uuid],
+) -> Result<HashMap<Uuid, Vec<String>>, sqlx::Error> {
+    let rows =
+        sqlx::query("SELECT pr_id, path FROM pr_files WHERE pr_id = ANY($1) ORDER BY pr_id, path")
+            .bind(pr_ids)
+            .fetch_all(pool)
+            .await?;
+
+    let mut files: HashMap<Uuid, Vec<String>> = HashMap::new();
+    for r in rows {
+        files.entry(r.get("pr_id")).or_default().push(r.get("path"));
+    }
+    Ok(files)
+}
+
+/// Distinct authors of the given PRs
+pub async fn author_ids(pool: &PgPool, pr_ids: &[Uuid]) -> Result<Vec<Uuid>, sqlx::Error> {
+    let rows = sqlx::query("SELECT DISTINCT author_id FROM pull_requests WHERE id = ANY($1)")
+        .bind(pr_ids)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.into_iter().map(|r| r.get("author_id")).collect())
+}
+
 /// List recent PRs for a repo
 pub async fn list_recent(
     pool: &PgPool,
@@ -188,44 +358,13 @@ pub async fn list_recent(
         .collect())
 }
 
-/// Count PRs authored by a user
-pub async fn count_by_author(pool: &PgPool, user_id: Uuid) -> Result<i64, sqlx::Error> {
-    let row = sqlx::query(
-        r#"
-        SELECT COUNT(*) as count
-        FROM pull_requests
-        WHERE author_id = $1
-        "#,
-    )
-    .bind(user_id)
-    .fetch_one(pool)
-    .await?;
-
-    Ok(row.get::<i64, _>("count"))
-}
-
-/// Count merged PRs authored by a user
-pub async fn count_merged_by_author(pool: &PgPool, user_id: Uuid) -> Result<i64, sqlx::Error> {
-    let row = sqlx::query(
-        r#"
-        SELECT COUNT(*) as count
-        FROM pull_requests
-        WHERE author_id = $1 AND state = 'merged'
-        "#,
-    )
-    .bind(user_id)
-    .fetch_one(pool)
-    .await?;
-
-    Ok(row.get::<i64, _>("count"))
-}
-
 /// Open PR with review statistics
 #[derive(Debug)]
 pub struct OpenPrWithStats {
     pub id: Uuid,
     pub number: i32,
     pub title: String,
+    pub author_id: Uuid,
     pub author_login: String,
     pub author_avatar: Option<String>,
     pub created_at: DateTime<Utc>,
@@ -249,6 +388,7 @@ pub async fn list_open_with_stats(
             pr.id,
             pr.number,
             pr.title,
+            pr.author_id,
             author.login as author_login,
             author.avatar_url as author_avatar,
             pr.created_at,
@@ -287,6 +427,7 @@ pub async fn list_open_with_stats(
             id: r.get("id"),
             number: r.get("number"),
             title: r.get("title"),
+            author_id: r.get("author_id"),
             author_login: r.get("author_login"),
             author_avatar: r.get("author_avatar"),
             created_at: r.get("created_at"),
@@ -0,0 +1,269 @@
+//! XP recalculation bookkeeping: watermark, touched pairs and batched writes
+
+use chrono::{DateTime, Utc};
+use common::models::XpBreakdown;
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use uuid::Uuid;
+
+/// Current database time (used as the watermark for the next run)
+pub async fn now(pool: &PgPool) -> Result<DateTime<Utc>, sqlx::Error> {
+    let row = sqlx::query("SELECT NOW() as now").fetch_one(pool).await?;
+    Ok(row.get("now"))
+}
+
+/// Get the time of the last recalculation (None = never run)
+pub async fn get_last_run(pool: &PgPool) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    let row = sqlx::query("SELECT last_run_at FROM recalc_state WHERE id = TRUE")
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.and_then(|r| r.get("last_run_at")))
+}
+
+/// Record the time of the last recalculation
+pub async fn set_last_run(pool: &PgPool, run_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO recalc_state (id, last_run_at)
+        VALUES (TRUE, $1)
+        ON CONFLICT (id) DO UPDATE SET last_run_at = EXCLUDED.last_run_at
+        "#,
+    )
+    .bind(run_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// List (pr_id, reviewer_id) pairs whose inputs changed since `since`.
+///
+/// A pair is touched when one of its reviews changed, a commit on the PR
+/// changed (session boundaries move), or the reviewer's comments on the PR
+/// were added or re-categorized.
+pub async fn list_touched_pairs(
+    pool: &PgPool,
+    since: DateTime<Utc>,
+) -> Result<Vec<(Uuid, Uuid)>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT DISTINCT r.pr_id, r.reviewer_id
+        FROM reviews r
+        WHERE r.touched_at > $1
+           OR r.pr_id IN (SELECT c.pr_id FROM commits c WHERE c.touched_at > $1)
+           OR (r.pr_id, r.reviewer_id) IN (
+                SELECT rc.pr_id, rc.user_id FROM review_comments rc WHERE rc.touched_at > $1
+           )
+        "#,
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.get("pr_id"), r.get("reviewer_id")))
+        .collect())
+}
+
+/// XP currently stored for a session (on its first review)
+#[derive(Debug, Clone, Copy)]
+pub struct StoredSession {
+    pub review_id: Uuid,
+    pub pr_id: Uuid,
+    pub reviewer_id: Uuid,
+    pub xp: i64,
+}
+
+/// Every session as last scored (reviews marked `session_start`)
+pub async fn list_stored_sessions(pool: &PgPool) -> Result<Vec<StoredSession>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, pr_id, reviewer_id, xp_earned
+        FROM reviews
+        WHERE session_start
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| StoredSession {
+            review_id: r.get("id"),
+            pr_id: r.get("pr_id"),
+            reviewer_id: r.get("reviewer_id"),
+            xp: r.get::<i32, _>("xp_earned") as i64,
+        })
+        .collect())
+}
+
+/// XP for the first review of a session
+#[derive(Debug, Clone, Copy)]
+pub struct SessionAward {
+    pub review_id: Uuid,
+    pub pr_id: Uuid,
+    pub reviewer_id: Uuid,
+    pub breakdown: XpBreakdown,
+}
+
+impl SessionAward {
+    pub fn xp(&self) -> i32 {
+        self.breakdown.total() as i32
+    }
+}
+
+/// Replace session XP for the given (pr_id, reviewer_id) pairs in one transaction.
+///
+/// Clears `xp_earned`/`session_start` on every review of those pairs, marks the
+/// first review of each session with its XP, records changed sessions in the
+/// XP ledger, then rebuilds `xp`, `level` and `review_sessions` for the
+/// affected reviewers from reviews and unlocked achievements.
+pub async fn apply_pair_xp(
+    pool: &PgPool,
+    pairs: &[(Uuid, Uuid)],
+    awards: &[SessionAward],
+    rule_version: i32,
+) -> Result<(), sqlx::Error> {
+    let pair_prs: Vec<Uuid> = pairs.iter().map(|p| p.0).collect();
+    let pair_reviewers: Vec<Uuid> = pairs.iter().map(|p| p.1).collect();
+    let award_ids: Vec<Uuid> = awards.iter().map(|a| a.review_id).collect();
+    let award_xp: Vec<i32> = awards.iter().map(SessionAward::xp).collect();
+
+    let mut reviewers = pair_reviewers.clone();
+    reviewers.sort();
+    reviewers.dedup();
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        UPDATE reviews r
+        SET xp_earned = 0, session_start = FALSE
+        FROM UNNEST($1::uuid[], $2::uuid[]) AS p(pr_id, reviewer_id)
+        WHERE r.pr_id = p.pr_id AND r.reviewer_id = p.reviewer_id
+        "#,
+    )
+    .bind(&pair_prs)
+    .bind(&pair_reviewers)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE reviews r
+        SET xp_earned = a.xp, session_start = TRUE
+        FROM UNNEST($1::uuid[], $2::int[]) AS a(id, xp)
+        WHERE r.id = a.id
+        "#,
+    )
+    .bind(&award_ids)
+    .bind(&award_xp)
+    .execute(&mut *tx)
+    .await?;
+
+    crate::xp_ledger::record_sessions(
+        &mut tx,
+        awards,
+        rule_version,
+        Some((&pair_prs, &pair_reviewers)),
+    )
+    .await?;
+    refresh_user_totals(&mut tx, Some(&reviewers)).await?;
+
+    tx.commit().await
+}
+
+/// Replace all session XP in one transaction.
+///
+/// The new values are staged in a temporary table, then every review and
+/// every user total is swapped over before commit, so readers never see a
+/// half-recalculated leaderboard. Changed sessions are recorded in the XP ledger.
+pub async fn replace_all_xp(
+    pool: &PgPool,
+    awards: &[SessionAward],
+    rule_version: i32,
+) -> Result<(), sqlx::Error> {
+    let award_ids: Vec<Uuid> = awards.iter().map(|a| a.review_id).collect();
+    let award_xp: Vec<i32> = awards.iter().map(SessionAward::xp).collect();
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        CREATE TEMP TABLE recalc_staging (
+            review_id UUID PRIMARY KEY,
+            xp INTEGER NOT NULL
+        ) ON COMMIT DROP
+        "#,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO recalc_staging (review_id, xp)
+        SELECT * FROM UNNEST($1::uuid[], $2::int[])
+        "#,
+    )
+    .bind(&award_ids)
+    .bind(&award_xp)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE reviews r
+        SET xp_earned = COALESCE(s.xp, 0),
+            session_start = (s.review_id IS NOT NULL)
+        FROM reviews r2
+        LEFT JOIN recalc_staging s ON s.review_id = r2.id
+        WHERE r.id = r2.id
+          AND (r.xp_earned IS DISTINCT FROM COALESCE(s.xp, 0)
+               OR r.session_start IS DISTINCT FROM (s.review_id IS NOT NULL))
+        "#,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    crate::xp_ledger::record_sessions(&mut tx, awards, rule_version, None).await?;
+    refresh_user_totals(&mut tx, None).await?;
+
+    tx.commit().await
+}
+
+/// Rebuild `xp`, `level` and `review_sessions` from reviews and unlocked
+/// achievements (all users if `user_ids` is None)
+pub(crate) async fn refresh_user_totals(
+    tx: &mut Transaction<'_, Postgres>,
+    user_ids: Option<&[Uuid]>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE users u
+        SET xp = t.xp,
+            level = FLOOR(SQRT(t.xp / 100.0))::int + 1,
+            review_sessions = t.sessions,
+            updated_at = NOW()
+        FROM (
+            SELECT u2.id,
+                   (COALESCE(SUM(r.xp_earned), 0) + COALESCE((
+                       SELECT SUM(ua.xp_awarded)
+                       FROM user_achievements ua
+                       WHERE ua.user_id = u2.id
+                   ), 0))::bigint as xp,
+                   (COUNT(r.id) FILTER (WHERE r.session_start))::int as sessions
+            FROM users u2
+            LEFT JOIN reviews r ON r.reviewer_id = u2.id
+            WHERE $1::uuid[] IS NULL OR u2.id = ANY($1)
+            GROUP BY u2.id
+        ) t
+        WHERE u.id = t.id
+          AND (u.xp, u.level, u.review_sessions)
+              IS DISTINCT FROM (t.xp, FLOOR(SQRT(t.xp / 100.0))::int + 1, t.sessions)
+        "#,
+    )
+    .bind(user_ids)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,148 @@
+//! Reviewer recommendation inputs
+//!
+//! Raw per-reviewer signals for ranking who should review an open PR:
+//! candidates, their open review load, who they usually review and how long
+//! PRs wait for them. Ranking lives in `processor::recommend`.
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Reviewers active in a repo since `since`, as (id, login). Bots are
+/// excluded.
+pub async fn candidates(
+    pool: &PgPool,
+    repo_id: Uuid,
+    since: DateTime<Utc>,
+) -> Result<Vec<(Uuid, String)>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT DISTINCT u.id, u.login
+        FROM reviews r
+        JOIN pull_requests p ON p.id = r.pr_id
+        JOIN users u ON u.id = r.reviewer_id
+        WHERE p.repo_id = $1
+          AND r.submitted_at >= $2
+          AND r.reviewer_id <> p.author_id
+          AND u.login NOT LIKE '%[bot]'
+        ORDER BY u.login
+        "#,
+    )
+    .bind(repo_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.get("id"), r.get("login")))
+        .collect())
+}
+
+/// Open PRs, in any repo, each user is reviewing: they reviewed it and their
+/// latest review isn't an approval
+pub async fn open_review_load(
+    pool: &PgPool,
+    user_ids: &[Uuid],
+) -> Result<HashMap<Uuid, i64>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT reviewer_id, COUNT(*)::bigint as open_reviews
+        FROM (
+            SELECT DISTINCT ON (r.pr_id, r.reviewer_id) r.reviewer_id, r.state
+            FROM reviews r
+            JOIN pull_requests p ON p.id = r.pr_id
+            WHERE p.state = 'open'
+              AND r.reviewer_id = ANY($1)
+              AND r.reviewer_id <> p.author_id
+            ORDER BY r.pr_id, r.reviewer_id, r.submitted_at DESC
+        ) latest
+        WHERE state <> 'approved'
+        GROUP BY reviewer_id
+        "#,
+    )
+    .bind(user_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.get("reviewer_id"), r.get("open_reviews")))
+        .collect())
+}
+
+/// PRs of each author each reviewer reviewed in a repo since `since`, keyed
+/// by (reviewer, author)
+pub async fn author_reviews(
+    pool: &PgPool,
+    repo_id: Uuid,
+    since: DateTime<Utc>,
+) -> Result<HashMap<(Uuid, Uuid), i64>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT r.reviewer_id, p.author_id, COUNT(DISTINCT p.id)::bigint as prs
+        FROM reviews r
+        JOIN pull_requests p ON p.id = r.pr_id
+        WHERE p.repo_id = $1
+          AND r.submitted_at >= $2
+          AND r.reviewer_id <> p.author_id
+        GROUP BY r.reviewer_id, p.author_id
+        "#,
+    )
+    .bind(repo_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| ((r.get("reviewer_id"), r.get("author_id")), r.get("prs")))
+        .collect())
+}
+
+/// Per reviewer, for each PR in the repo they first reviewed since `since`:
+/// when it became ready (opened, or the last push before their first review)
+/// and when they reviewed it. Same rules as `users::first_review_waits`.
+pub async fn first_review_waits(
+    pool: &PgPool,
+    repo_id: Uuid,
+    since: DateTime<Utc>,
+) -> Result<HashMap<Uuid, Vec<(DateTime<Utc>, DateTime<Utc>)>>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        WITH firsts AS (
+            SELECT r.reviewer_id, r.pr_id, MIN(r.submitted_at) as reviewed_at
+            FROM reviews r
+            JOIN pull_requests p ON p.id = r.pr_id
+            WHERE p.repo_id = $1
+              AND r.submitted_at >= $2
+              AND r.reviewer_id <> p.author_id
+            GROUP BY r.reviewer_id, r.pr_id
+        )
+        SELECT
+            f.reviewer_id,
+            GREATEST(p.created_at, (
+                SELECT MAX(c.committed_at)
+                FROM commits c
+                WHERE c.pr_id = f.pr_id AND c.committed_at < f.reviewed_at
+            )) as ready_at,
+            f.reviewed_at
+        FROM firsts f
+        JOIN pull_requests p ON p.id = f.pr_id
+        "#,
+    )
+    .bind(repo_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+
+    let mut waits: HashMap<Uuid, Vec<_>> = HashMap::new();
+    for r in rows {
+        waits
+            .entry(r.get("reviewer_id"))
+            .or_default()
+            .push((r.get("ready_at"), r.get("reviewed_at")));
+    }
+    Ok(waits)
+}
@@ -1,7 +1,7 @@
 //! Repository queries
 
 use chrono::{DateTime, Utc};
-use common::models::Repository;
+use common::models::{RepoSyncSettings, Repository};
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
@@ -18,7 +18,7 @@ pub async fn upsert(
         INSERT INTO repositories (id, github_id, owner, name, created_at)
         VALUES ($1, $2, $3, $4, NOW())
         ON CONFLICT (github_id) DO UPDATE
-        SET owner = EXCLUDED.owner, name = EXCLUDED.name
+        SET owner = EXCLUDED.owner, name = EXCLUDED.name, untracked_at = NULL
         RETURNING id, github_id, owner, name, created_at
         "#,
     )
@@ -61,10 +61,10 @@ pub async fn get_by_name(
     }))
 }
 
-/// List all tracked repositories
+/// List all tracked repositories (untracked repos are excluded)
 pub async fn list(pool: &PgPool) -> Result<Vec<Repository>, sqlx::Error> {
     let rows = sqlx::query(
-        "SELECT id, github_id, owner, name, created_at FROM repositories ORDER BY owner, name",
+        "SELECT id, github_id, owner, name, created_at FROM repositories WHERE untracked_at IS NULL ORDER BY owner, name",
     )
     .fetch_all(pool)
     .await?;
@@ -160,3 +160,198 @@ pub async fn get_oldest_pr_date(
 
     Ok(row.and_then(|r| r.get("oldest")))
 }
+
+fn row_to_sync_settings(row: &sqlx::postgres::PgRow) -> RepoSyncSettings {
+    RepoSyncSettings {
+        enabled: row.get("sync_enabled"),
+        interval_hours: row.get("sync_interval_hours"),
+        max_age_days: row.get("sync_max_age_days"),
+        priority: row.get("sync_priority"),
+        fetch_commits: row.get("fetch_commits"),
+        fetch_comments: row.get("fetch_comments"),
+        stale_after_days: row.get("stale_after_days"),
+    }
+}
+
+/// Get sync settings for a repository
+pub async fn get_sync_settings(
+    pool: &PgPool,
+    repo_id: Uuid,
+) -> Result<Option<RepoSyncSettings>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT sync_enabled, sync_interval_hours, sync_max_age_days, sync_priority,
+               fetch_commits, fetch_comments, stale_after_days
+        FROM repositories
+        WHERE id = $1
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.as_ref().map(row_to_sync_settings))
+}
+
+/// Replace sync settings for a repository
+pub async fn update_sync_settings(
+    pool: &PgPool,
+    repo_id: Uuid,
+    settings: &RepoSyncSettings,
+) -> Result<RepoSyncSettings, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        UPDATE repositories
+        SET sync_enabled = $2,
+            sync_interval_hours = $3,
+            sync_max_age_days = $4,
+            sync_priority = $5,
+            fetch_commits = $6,
+            fetch_comments = $7,
+            stale_after_days = $8
+        WHERE id = $1
+        RETURNING sync_enabled, sync_interval_hours, sync_max_age_days, sync_priority,
+                  fetch_commits, fetch_comments, stale_after_days
+        "#,
+    )
+    .bind(repo_id)
+    .bind(settings.enabled)
+    .bind(settings.interval_hours)
+    .bind(settings.max_age_days)
+    .bind(settings.priority)
+    .bind(settings.fetch_commits)
+    .bind(settings.fetch_comments)
+    .bind(settings.stale_after_days)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row_to_sync_settings(&row))
+}
+
+/// A repository with its sync settings and cursor (for the scheduler)
+#[derive(Debug, Clone)]
+pub struct RepoSyncState {
+    pub repo: Repository,
+    pub settings: RepoSyncSettings,
+    pub last_synced_at: Option<DateTime<Utc>>,
+}
+
+/// List all tracked repositories with sync settings, highest priority first.
+/// Within a priority, repos that were synced longest ago (or never) come first.
+pub async fn list_sync_states(pool: &PgPool) -> Result<Vec<RepoSyncState>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, github_id, owner, name, created_at, last_synced_at,
+               sync_enabled, sync_interval_hours, sync_max_age_days, sync_priority,
+               fetch_commits, fetch_comments, stale_after_days
+        FROM repositories
+        WHERE untracked_at IS NULL
+        ORDER BY sync_priority DESC, last_synced_at ASC NULLS FIRST, owner, name
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| RepoSyncState {
+            settings: row_to_sync_settings(&r),
+            last_synced_at: r.get("last_synced_at"),
+            repo: Repository {
+                id: r.get("id"),
+                github_id: r.get("github_id"),
+                owner: r.get("owner"),
+                name: r.get("name"),
+                created_at: r.get("created_at"),
+            },
+        })
+        .collect())
+}
+
+/// Stop tracking a repository while keeping its history.
+/// The scheduler skips untracked repos; a manual backfill re-tracks it.
+pub async fn mark_untracked(pool: &PgPool, repo_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE repositories SET untracked_at = COALESCE(untracked_at, NOW()) WHERE id = $1",
+    )
+    .bind(repo_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Users with any activity in a repository (authors, reviewers, commenters,
+/// committers)
+pub async fn list_affected_users(pool: &PgPool, repo_id: Uuid) -> Result<Vec<Uuid>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT pr.author_id as user_id FROM pull_requests pr WHERE pr.repo_id = $1
+        UNION
+        SELECT r.reviewer_id FROM reviews r
+        JOIN pull_requests pr ON pr.id = r.pr_id
+        WHERE pr.repo_id = $1
+        UNION
+        SELECT rc.user_id FROM review_comments rc
+        JOIN pull_requests pr ON pr.id = rc.pr_id
+        WHERE pr.repo_id = $1
+        UNION
+        SELECT c.author_id FROM commits c
+        JOIN pull_requests pr ON pr.id = c.pr_id
+        WHERE pr.repo_id = $1 AND c.author_id IS NOT NULL
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| r.get("user_id")).collect())
+}
+
+/// Row counts removed by a purge
+#[derive(Debug, Clone, Default)]
+pub struct PurgeCounts {
+    pub prs: i64,
+    pub reviews: i64,
+    pub comments: i64,
+    pub commits: i64,
+}
+
+/// Delete a repository and all of its PRs, reviews, comments and commits.
+/// Runs in a single transaction; child rows go via ON DELETE CASCADE. XP ledger
+/// entries are kept, with the repo's sessions reversed.
+pub async fn purge(pool: &PgPool, repo_id: Uuid) -> Result<PurgeCounts, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let row = sqlx::query(
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM pull_requests WHERE repo_id = $1) as prs,
+            (SELECT COUNT(*) FROM reviews r
+             JOIN pull_requests pr ON pr.id = r.pr_id WHERE pr.repo_id = $1) as reviews,
+            (SELECT COUNT(*) FROM review_comments rc
+             JOIN pull_requests pr ON pr.id = rc.pr_id WHERE pr.repo_id = $1) as comments,
+            (SELECT COUNT(*) FROM commits c
+             JOIN pull_requests pr ON pr.id = c.pr_id WHERE pr.repo_id = $1) as commits
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    // The ledger keeps its history; cancel the repo's sessions before they go
+    crate::xp_ledger::reverse_repo(&mut tx, repo_id).await?;
+
+    sqlx::query("DELETE FROM repositories WHERE id = $1")
+        .bind(repo_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(PurgeCounts {
+        prs: row.get("prs"),
+        reviews: row.get("reviews"),
+        comments: row.get("comments"),
+        commits: row.get("commits"),
+    })
+}
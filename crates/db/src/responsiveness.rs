@@ -0,0 +1,116 @@
+//! Author responsiveness data
+//!
+//! Raw per-author inputs for the responsiveness score: how long each change
+//! request on their PRs waited for the next push, and how many review
+//! threads others started on their PRs that they replied to. Scoring lives in
+//! `processor::responsiveness`.
+
+use chrono::{DateTime, Utc};
+use common::models::User;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+/// A change request followed by a push within this many hours counts as a
+/// quick fix (see the `quick_fixes` achievement metric)
+pub const QUICK_FIX_HOURS: f64 = 24.0;
+
+/// Responsiveness inputs for one PR author
+#[derive(Debug, Clone)]
+pub struct AuthorResponseData {
+    pub user: User,
+    /// Hours from each change request to the next push, None if no push
+    /// followed it
+    pub response_hours: Vec<Option<f64>>,
+    /// Review threads started by others on the author's PRs
+    pub threads: i64,
+    /// Of those, threads the author replied to
+    pub threads_replied: i64,
+}
+
+/// Responsiveness inputs for every author who received a change request or a
+/// review thread since `since`, optionally limited to one repo. Bots are
+/// excluded on both sides.
+pub async fn get_author_data(
+    pool: &PgPool,
+    repo_id: Option<Uuid>,
+    since: DateTime<Utc>,
+) -> Result<Vec<AuthorResponseData>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        WITH change_requests AS (
+            SELECT
+                p.author_id,
+                EXTRACT(EPOCH FROM (MIN(c.committed_at) - r.submitted_at))::float8 / 3600.0 as hours
+            FROM reviews r
+            JOIN pull_requests p ON p.id = r.pr_id
+            JOIN users rv ON rv.id = r.reviewer_id
+            LEFT JOIN commits c ON c.pr_id = r.pr_id AND c.committed_at > r.submitted_at
+            WHERE r.state = 'changes_requested'
+              AND r.reviewer_id <> p.author_id
+              AND r.submitted_at >= $1
+              AND ($2::uuid IS NULL OR p.repo_id = $2)
+              AND rv.login NOT LIKE '%[bot]'
+            GROUP BY r.id, p.author_id, r.submitted_at
+        ),
+        author_requests AS (
+            SELECT author_id, array_agg(hours) as response_hours
+            FROM change_requests
+            GROUP BY author_id
+        ),
+        author_threads AS (
+            SELECT
+                p.author_id,
+                COUNT(*)::bigint as threads,
+                COUNT(*) FILTER (WHERE EXISTS (
+                    SELECT 1 FROM review_comments reply
+                    WHERE reply.in_reply_to_id = root.github_id
+                      AND reply.user_id = p.author_id
+                ))::bigint as threads_replied
+            FROM review_comments root
+            JOIN pull_requests p ON p.id = root.pr_id
+            JOIN users cu ON cu.id = root.user_id
+            WHERE root.in_reply_to_id IS NULL
+              AND root.user_id <> p.author_id
+              AND root.created_at >= $1
+              AND ($2::uuid IS NULL OR p.repo_id = $2)
+              AND cu.login NOT LIKE '%[bot]'
+            GROUP BY p.author_id
+        )
+        SELECT
+            u.id, u.github_id, u.login, u.avatar_url,
+            u.xp, u.level,
+            u.created_at, u.updated_at,
+            COALESCE(ar.response_hours, '{}') as response_hours,
+            COALESCE(at.threads, 0) as threads,
+            COALESCE(at.threads_replied, 0) as threads_replied
+        FROM users u
+        LEFT JOIN author_requests ar ON ar.author_id = u.id
+        LEFT JOIN author_threads at ON at.author_id = u.id
+        WHERE (ar.author_id IS NOT NULL OR at.author_id IS NOT NULL)
+          AND u.login NOT LIKE '%[bot]'
+        "#,
+    )
+    .bind(since)
+    .bind(repo_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| AuthorResponseData {
+            user: User {
+                id: row.get("id"),
+                github_id: row.get("github_id"),
+                login: row.get("login"),
+                avatar_url: row.get("avatar_url"),
+                xp: row.get("xp"),
+                level: row.get("level"),
+                created_at: row.get("created_at"),
+                updated_at: row.get("updated_at"),
+            },
+            response_hours: row.get("response_hours"),
+            threads: row.get("threads"),
+            threads_replied: row.get("threads_replied"),
+        })
+        .collect())
+}
@@ -4,6 +4,7 @@
 
 use chrono::{DateTime, Utc};
 use sqlx::{PgPool, Row};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 /// A stored review comment
@@ -17,6 +18,7 @@ pub struct ReviewComment {
     pub body: String,
     pub path: Option<String>,
     pub diff_hunk: Option<String>,
+    /// Line in the commit the comment was made on
     pub line: Option<i32>,
     pub in_reply_to_id: Option<i64>,
     pub created_at: DateTime<Utc>,
@@ -42,10 +44,11 @@ pub async fn insert(
     let row = sqlx::query(
         r#"
         INSERT INTO review_comments 
-            (id, review_id, pr_id, user_id, github_id, body, path, diff_hunk, line, in_reply_to_id, created_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            (id, review_id, pr_id, user_id, github_id, body, path, diff_hunk, line, in_reply_to_id, created_at, thanks)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
         ON CONFLICT (github_id) DO UPDATE
         SET body = EXCLUDED.body,
+            thanks = EXCLUDED.thanks,
             path = EXCLUDED.path,
             diff_hunk = EXCLUDED.diff_hunk,
             line = EXCLUDED.line
@@ -64,6 +67,7 @@ pub async fn insert(
     .bind(line)
     .bind(in_reply_to_id)
     .bind(created_at)
+    .bind(crate::review_threads::is_thanks(body))
     .fetch_one(pool)
     .await?;
 
@@ -172,7 +176,7 @@ pub async fn set_category(
     sqlx::query(
         r#"
         UPDATE review_comments
-        SET category = $2, quality_score = $3
+        SET category = $2, quality_score = $3, touched_at = NOW()
         WHERE id = $1
         "#,
     )
@@ -185,6 +189,49 @@ pub async fn set_category(
     Ok(())
 }
 
+/// Mark review comments on the given PRs as addressed by the first later
+/// commit (before merge) that changed the commented line, and clear marks
+/// that no longer hold. Only top-level comments by someone other than the PR
+/// author count. Changed comments are touched for rescoring. Returns the
+/// number of comments whose mark changed.
+///
+/// Lines are compared on the old side of each commit's diff, so a comment is
+/// matched reliably against the commit right after it and approximately
+/// against later ones.
+pub async fn mark_addressed(pool: &PgPool, pr_ids: &[Uuid]) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        WITH matches AS (
+            SELECT DISTINCT ON (rc.id) rc.id, c.id as commit_id
+            FROM review_comments rc
+            JOIN pull_requests p ON p.id = rc.pr_id
+            JOIN commits c ON c.pr_id = rc.pr_id
+                AND c.committed_at > rc.created_at
+                AND (p.merged_at IS NULL OR c.committed_at <= p.merged_at)
+            JOIN commit_hunks h ON h.commit_id = c.id
+                AND h.path = rc.path
+                AND rc.line BETWEEN h.old_start AND h.old_start + GREATEST(h.old_lines, 1) - 1
+            WHERE rc.pr_id = ANY($1)
+              AND rc.in_reply_to_id IS NULL
+              AND rc.user_id <> p.author_id
+            ORDER BY rc.id, c.committed_at ASC
+        )
+        UPDATE review_comments rc
+        SET addressed_by = m.commit_id, touched_at = NOW()
+        FROM review_comments x
+        LEFT JOIN matches m ON m.id = x.id
+        WHERE x.id = rc.id
+          AND rc.pr_id = ANY($1)
+          AND rc.addressed_by IS DISTINCT FROM m.commit_id
+        "#,
+    )
+    .bind(pr_ids)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
 fn row_to_comment(row: sqlx::postgres::PgRow) -> ReviewComment {
     ReviewComment {
         id: row.get("id"),
@@ -204,6 +251,9 @@ fn row_to_comment(row: sqlx::postgres::PgRow) -> ReviewComment {
 }
 
 /// Quality data for XP calculation
+///
+/// Tier and category counts only include substantive comments (at least the
+/// repo's scoring profile `min_comment_chars`); shorter ones are in `short_count`.
 #[derive(Debug, Clone, Default)]
 pub struct CommentQualityData {
     /// Count by quality tier: (low, medium, high)
@@ -212,6 +262,25 @@ pub struct CommentQualityData {
     pub by_category: (i32, i32, i32),
     /// Total categorized comments
     pub categorized_count: i32,
+    /// Comments below the substantive length threshold
+    pub short_count: i32,
+    /// Substantive comments addressed by a follow-up commit
+    pub addressed_count: i32,
+}
+
+impl CommentQualityData {
+    /// Add another set of comments' counts to this one
+    pub fn add(&mut self, other: &CommentQualityData) {
+        self.by_tier.0 += other.by_tier.0;
+        self.by_tier.1 += other.by_tier.1;
+        self.by_tier.2 += other.by_tier.2;
+        self.by_category.0 += other.by_category.0;
+        self.by_category.1 += other.by_category.1;
+        self.by_category.2 += other.by_category.2;
+        self.categorized_count += other.categorized_count;
+        self.short_count += other.short_count;
+        self.addressed_count += other.addressed_count;
+    }
 }
 
 /// Get aggregated quality data for comments by PR and user
@@ -220,36 +289,124 @@ pub async fn get_quality_data_for_pr_user(
     pr_id: Uuid,
     user_id: Uuid,
 ) -> Result<CommentQualityData, sqlx::Error> {
-    let row = sqlx::query(
+    let mut data = get_quality_data_by_pr_user(pool, Some(&[pr_id])).await?;
+    Ok(data.remove(&(pr_id, user_id)).unwrap_or_default())
+}
+
+/// Get quality data for every (PR, user) pair in one query.
+/// Restricted to the given PRs if `pr_ids` is Some, otherwise covers all comments.
+pub async fn get_quality_data_by_pr_user(
+    pool: &PgPool,
+    pr_ids: Option<&[Uuid]>,
+) -> Result<HashMap<(Uuid, Uuid), CommentQualityData>, sqlx::Error> {
+    let mut data: HashMap<(Uuid, Uuid), CommentQualityData> = HashMap::new();
+    for row in quality_rows(pool, pr_ids, &HashMap::new()).await? {
+        data.entry((row.pr_id, row.user_id))
+            .or_default()
+            .add(&row.data);
+    }
+    Ok(data)
+}
+
+/// Get quality data per review (the comments submitted with it), so XP can be
+/// scored per session. Comments not attached to a review are left out.
+///
+/// `min_chars` overrides the substantive length threshold of the PRs it
+/// contains (keyed by PR); other PRs use their profile's.
+pub async fn get_quality_data_by_review(
+    pool: &PgPool,
+    pr_ids: Option<&[Uuid]>,
+    min_chars: &HashMap<Uuid, i32>,
+) -> Result<HashMap<Uuid, CommentQualityData>, sqlx::Error> {
+    let mut data: HashMap<Uuid, CommentQualityData> = HashMap::new();
+    for row in quality_rows(pool, pr_ids, min_chars).await? {
+        if let Some(review_id) = row.review_id {
+            data.entry(review_id).or_default().add(&row.data);
+        }
+    }
+    Ok(data)
+}
+
+struct QualityRow {
+    pr_id: Uuid,
+    user_id: Uuid,
+    review_id: Option<Uuid>,
+    data: CommentQualityData,
+}
+
+/// Quality data per (PR, user, review)
+async fn quality_rows(
+    pool: &PgPool,
+    pr_ids: Option<&[Uuid]>,
+    min_chars: &HashMap<Uuid, i32>,
+) -> Result<Vec<QualityRow>, sqlx::Error> {
+    let (override_prs, override_chars): (Vec<Uuid>, Vec<i32>) = min_chars.iter().unzip();
+    let rows = sqlx::query(
         r#"
-        SELECT 
-            COUNT(*) FILTER (WHERE quality_score IS NOT NULL AND quality_score <= 3) as low_quality,
-            COUNT(*) FILTER (WHERE quality_score IS NOT NULL AND quality_score >= 4 AND quality_score <= 6) as medium_quality,
-            COUNT(*) FILTER (WHERE quality_score IS NOT NULL AND quality_score >= 7) as high_quality,
-            COUNT(*) FILTER (WHERE category = 'logic') as logic_count,
-            COUNT(*) FILTER (WHERE category = 'structural') as structural_count,
-            COUNT(*) FILTER (WHERE category IS NOT NULL AND category NOT IN ('logic', 'structural')) as other_count,
-            COUNT(*) FILTER (WHERE category IS NOT NULL) as categorized_count
-        FROM review_comments
-        WHERE pr_id = $1 AND user_id = $2
+        WITH comments AS (
+            SELECT
+                rc.pr_id,
+                rc.user_id,
+                rc.review_id,
+                rc.category,
+                rc.quality_score,
+                rc.addressed_by IS NOT NULL as addressed,
+                char_length(btrim(rc.body))
+                    < COALESCE(o.min_chars, sp.min_comment_chars) as short
+            FROM review_comments rc
+            LEFT JOIN UNNEST($2::uuid[], $3::int[]) AS o(pr_id, min_chars) ON o.pr_id = rc.pr_id
+            JOIN pull_requests p ON p.id = rc.pr_id
+            JOIN repositories r ON r.id = p.repo_id
+            JOIN scoring_profiles sp ON sp.id = COALESCE(
+                r.scoring_profile_id,
+                (SELECT id FROM scoring_profiles WHERE name = 'default')
+            )
+            WHERE $1::uuid[] IS NULL OR rc.pr_id = ANY($1)
+        )
+        SELECT
+            pr_id,
+            user_id,
+            review_id,
+            COUNT(*) FILTER (WHERE NOT short AND quality_score IS NOT NULL AND quality_score <= 3) as low_quality,
+            COUNT(*) FILTER (WHERE NOT short AND quality_score IS NOT NULL AND quality_score >= 4 AND quality_score <= 6) as medium_quality,
+            COUNT(*) FILTER (WHERE NOT short AND quality_score IS NOT NULL AND quality_score >= 7) as high_quality,
+            COUNT(*) FILTER (WHERE NOT short AND category = 'logic') as logic_count,
+            COUNT(*) FILTER (WHERE NOT short AND category = 'structural') as structural_count,
+            COUNT(*) FILTER (WHERE NOT short AND category IS NOT NULL AND category NOT IN ('logic', 'structural')) as other_count,
+            COUNT(*) FILTER (WHERE NOT short AND category IS NOT NULL) as categorized_count,
+            COUNT(*) FILTER (WHERE short) as short_count,
+            COUNT(*) FILTER (WHERE NOT short AND addressed) as addressed_count
+        FROM comments
+        GROUP BY pr_id, user_id, review_id
         "#,
     )
-    .bind(pr_id)
-    .bind(user_id)
-    .fetch_one(pool)
+    .bind(pr_ids)
+    .bind(&override_prs)
+    .bind(&override_chars)
+    .fetch_all(pool)
     .await?;
 
-    Ok(CommentQualityData {
-        by_tier: (
-            row.get::<i64, _>("low_quality") as i32,
-            row.get::<i64, _>("medium_quality") as i32,
-            row.get::<i64, _>("high_quality") as i32,
-        ),
-        by_category: (
-            row.get::<i64, _>("logic_count") as i32,
-            row.get::<i64, _>("structural_count") as i32,
-            row.get::<i64, _>("other_count") as i32,
-        ),
-        categorized_count: row.get::<i64, _>("categorized_count") as i32,
-    })
+    Ok(rows
+        .into_iter()
+        .map(|row| QualityRow {
+            pr_id: row.get("pr_id"),
+            user_id: row.get("user_id"),
+            review_id: row.get("review_id"),
+            data: CommentQualityData {
+                by_tier: (
+                    row.get::<i64, _>("low_quality") as i32,
+                    row.get::<i64, _>("medium_quality") as i32,
+                    row.get::<i64, _>("high_quality") as i32,
+                ),
+                by_category: (
+                    row.get::<i64, _>("logic_count") as i32,
+                    row.get::<i64, _>("structural_count") as i32,
+                    row.get::<i64, _>("other_count") as i32,
+                ),
+                categorized_count: row.get::<i64, _>("categorized_count") as i32,
+                short_count: row.get::<i64, _>("short_count") as i32,
+                addressed_count: row.get::<i64, _>("addressed_count") as i32,
+            },
+        })
+        .collect())
 }
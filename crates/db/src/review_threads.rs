@@ -0,0 +1,113 @@
+//! Review thread queries
+//!
+//! Threads are rebuilt from `review_comments.in_reply_to_id` by the
+//! `review_threads` view, which also decides acknowledgement. Whether a reply
+//! thanks the reviewer is decided here ([`is_thanks`]) and stored on the
+//! comment when it is saved.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+/// Phrases that make a reply a thank-you (matched case-insensitively)
+const THANKS_PHRASES: [&str; 7] = [
+    "thank",
+    "thx",
+    "good catch",
+    "nice catch",
+    "great catch",
+    "good find",
+    "nice find",
+];
+
+/// Whether a comment thanks the reviewer ("thanks", "good catch", ...)
+pub fn is_thanks(body: &str) -> bool {
+    let body = body.to_lowercase();
+    THANKS_PHRASES.iter().any(|phrase| body.contains(phrase))
+}
+
+/// Classify comments saved before thanks were stored on them. Runs once per
+/// comment; returns how many were classified.
+pub async fn classify_thanks(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let rows = sqlx::query("SELECT id, body FROM review_comments WHERE thanks IS NULL")
+        .fetch_all(pool)
+        .await?;
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    let (ids, thanks): (Vec<Uuid>, Vec<bool>) = rows
+        .iter()
+        .map(|r| (r.get::<Uuid, _>("id"), is_thanks(r.get("body"))))
+        .unzip();
+    let result = sqlx::query(
+        r#"
+        UPDATE review_comments rc
+        SET thanks = t.thanks
+        FROM UNNEST($1::uuid[], $2::bool[]) AS t(id, thanks)
+        WHERE rc.id = t.id
+        "#,
+    )
+    .bind(&ids)
+    .bind(&thanks)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// A review thread with its metrics
+#[derive(Debug, Clone, Serialize)]
+pub struct ReviewThread {
+    /// GitHub ID of the root comment
+    pub github_id: i64,
+    /// Login of the user who started the thread
+    pub starter: String,
+    pub path: Option<String>,
+    pub started_at: DateTime<Utc>,
+    /// Comments in the thread, including the root
+    pub depth: i32,
+    /// Distinct commenters, including the starter
+    pub participants: i32,
+    /// Seconds until someone other than the starter replied
+    pub time_to_first_reply_secs: Option<i64>,
+    /// The PR author replied to a thread someone else started
+    pub author_acknowledged: bool,
+    /// ...with thanks or "good catch"
+    pub author_thanked: bool,
+}
+
+/// Threads on a PR, oldest first
+pub async fn list_for_pr(pool: &PgPool, pr_id: Uuid) -> Result<Vec<ReviewThread>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT t.*, u.login as starter
+        FROM review_threads t
+        JOIN users u ON u.id = t.starter_id
+        WHERE t.pr_id = $1
+        ORDER BY t.started_at ASC
+        "#,
+    )
+    .bind(pr_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            let started_at: DateTime<Utc> = r.get("started_at");
+            let first_reply_at: Option<DateTime<Utc>> = r.get("first_reply_at");
+            ReviewThread {
+                github_id: r.get("github_id"),
+                starter: r.get("starter"),
+                path: r.get("path"),
+                started_at,
+                depth: r.get("depth"),
+                participants: r.get("participants"),
+                time_to_first_reply_secs: first_reply_at.map(|t| (t - started_at).num_seconds()),
+                author_acknowledged: r.get("author_acknowledged"),
+                author_thanked: r.get("author_thanked"),
+            }
+        })
+        .collect())
+}
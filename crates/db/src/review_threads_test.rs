@@ -0,0 +1,31 @@
+//! Tests for review thread acknowledgement
+
+#[cfg(test)]
+mod tests {
+    use crate::review_threads::is_thanks;
+
+    #[test]
+    fn test_thanks_phrases() {
+        assert!(is_thanks("Thanks, fixed"));
+        assert!(is_thanks("thank you!"));
+        assert!(is_thanks("thx"));
+        assert!(is_thanks("Good catch, will update"));
+        assert!(is_thanks("NICE FIND"));
+        assert!(is_thanks("great catch 🙏"));
+    }
+
+    #[test]
+    fn test_plain_replies_are_not_thanks() {
+        assert!(!is_thanks("Fixed"));
+        assert!(!is_thanks("I don't think this is needed"));
+        assert!(!is_thanks("Good point, done"));
+        assert!(!is_thanks(""));
+    }
+
+    #[test]
+    fn test_thanks_inside_longer_reply() {
+        assert!(is_thanks(
+            "Done in abc123. Thanks for spotting the off-by-one"
+        ));
+    }
+}
@@ -7,7 +7,7 @@ use common::models::{Review, ReviewState};
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
-fn parse_review_state(s: &str) -> ReviewState {
+pub(crate) fn parse_review_state(s: &str) -> ReviewState {
     match s {
         "approved" => ReviewState::Approved,
         "changes_requested" => ReviewState::ChangesRequested,
@@ -44,7 +44,13 @@ pub async fn insert(
         ON CONFLICT (github_id) DO UPDATE
         SET state = EXCLUDED.state,
             body = EXCLUDED.body,
-            comments_count = EXCLUDED.comments_count
+            comments_count = EXCLUDED.comments_count,
+            touched_at = CASE
+                WHEN reviews.state IS DISTINCT FROM EXCLUDED.state
+                  OR reviews.comments_count IS DISTINCT FROM EXCLUDED.comments_count
+                THEN NOW()
+                ELSE reviews.touched_at
+            END
         RETURNING id, pr_id, reviewer_id, github_id, state, body, comments_count, submitted_at
         "#,
     )
@@ -121,71 +127,44 @@ pub async fn count_by_user(
     Ok(row.get::<i64, _>("count"))
 }
 
-/// Count fast reviews by a user (submitted within 1 hour of the latest commit on the PR)
-pub async fn count_fast_reviews(pool: &PgPool, user_id: Uuid) -> Result<i64, sqlx::Error> {
-    let row = sqlx::query(
-        r#"
-        SELECT COUNT(DISTINCT r.id) as count
-        FROM reviews r
-        JOIN pull_requests pr ON r.pr_id = pr.id
-        JOIN LATERAL (
-            SELECT MAX(committed_at) as last_commit_at
-            FROM commits c
-            WHERE c.pr_id = pr.id AND c.committed_at < r.submitted_at
-        ) latest ON true
-        WHERE r.reviewer_id = $1
-          AND latest.last_commit_at IS NOT NULL
-          AND r.submitted_at <= latest.last_commit_at + INTERVAL '1 hour'
-        "#,
-    )
-    .bind(user_id)
-    .fetch_one(pool)
-    .await?;
-
-    Ok(row.get::<i64, _>("count"))
-}
-
-/// Check if user has a 7-day review streak (at least one review on 7 consecutive days)
-pub async fn has_7_day_streak(pool: &PgPool, user_id: Uuid) -> Result<bool, sqlx::Error> {
-    // Get distinct review dates, find max consecutive streak
-    let row = sqlx::query(
+/// List all reviews (for recalculation)
+pub async fn list_all(pool: &PgPool) -> Result<Vec<Review>, sqlx::Error> {
+    let rows = sqlx::query(
         r#"
-        WITH review_dates AS (
-            SELECT DISTINCT DATE(submitted_at) as review_date
-            FROM reviews
-            WHERE reviewer_id = $1
-        ),
-        streaks AS (
-            SELECT review_date,
-                   review_date - (ROW_NUMBER() OVER (ORDER BY review_date))::int AS streak_group
-            FROM review_dates
-        ),
-        streak_lengths AS (
-            SELECT streak_group, COUNT(*) as streak_len
-            FROM streaks
-            GROUP BY streak_group
-        )
-        SELECT COALESCE(MAX(streak_len), 0) as max_streak
-        FROM streak_lengths
+        SELECT id, pr_id, reviewer_id, github_id, state, body, comments_count, submitted_at
+        FROM reviews
+        ORDER BY submitted_at ASC
         "#,
     )
-    .bind(user_id)
-    .fetch_one(pool)
+    .fetch_all(pool)
     .await?;
 
-    let max_streak: i64 = row.get("max_streak");
-    Ok(max_streak >= 7)
+    Ok(rows
+        .into_iter()
+        .map(|r| Review {
+            id: r.get("id"),
+            pr_id: r.get("pr_id"),
+            reviewer_id: r.get("reviewer_id"),
+            github_id: r.get("github_id"),
+            state: parse_review_state(r.get("state")),
+            body: r.get("body"),
+            comments_count: r.get("comments_count"),
+            submitted_at: r.get("submitted_at"),
+        })
+        .collect())
 }
 
-/// List all reviews (for recalculation)
-pub async fn list_all(pool: &PgPool) -> Result<Vec<Review>, sqlx::Error> {
+/// List reviews on the given PRs (for incremental recalculation)
+pub async fn list_for_prs(pool: &PgPool, pr_ids: &[Uuid]) -> Result<Vec<Review>, sqlx::Error> {
     let rows = sqlx::query(
         r#"
         SELECT id, pr_id, reviewer_id, github_id, state, body, comments_count, submitted_at
         FROM reviews
+        WHERE pr_id = ANY($1)
         ORDER BY submitted_at ASC
         "#,
     )
+    .bind(pr_ids)
     .fetch_all(pool)
     .await?;
 
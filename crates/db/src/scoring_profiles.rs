@@ -0,0 +1,303 @@
+//! Scoring profile storage and per-repository assignment
+
+use common::models::{ScoringProfile, ScoringWeights};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Name of the profile used by repos without an explicit assignment
+pub const DEFAULT_PROFILE: &str = "default";
+
+fn row_to_weights(row: &sqlx::postgres::PgRow) -> ScoringWeights {
+    ScoringWeights {
+        base_xp: row.get("base_xp"),
+        low_quality_xp: row.get("low_quality_xp"),
+        medium_quality_xp: row.get("medium_quality_xp"),
+        high_quality_xp: row.get("high_quality_xp"),
+        uncategorized_xp: row.get("uncategorized_xp"),
+        logic_bonus: row.get("logic_bonus"),
+        structural_bonus: row.get("structural_bonus"),
+        addressed_bonus: row.get("addressed_bonus"),
+        fast_review_bonus: row.get("fast_review_bonus"),
+        fast_review_minutes: row.get("fast_review_minutes"),
+        thorough_threshold: row.get("thorough_threshold"),
+        thorough_bonus: row.get("thorough_bonus"),
+        deep_threshold: row.get("deep_threshold"),
+        deep_bonus: row.get("deep_bonus"),
+        session_gap_hours: row.get("session_gap_hours"),
+        min_comment_chars: row.get("min_comment_chars"),
+        difficulty_max_pct: row.get("difficulty_max_pct"),
+        difficulty_lines: row.get("difficulty_lines"),
+        difficulty_files: row.get("difficulty_files"),
+    }
+}
+
+fn row_to_profile(row: &sqlx::postgres::PgRow) -> ScoringProfile {
+    ScoringProfile {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: row.get("description"),
+        weights: row_to_weights(row),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// List all scoring profiles
+pub async fn list(pool: &PgPool) -> Result<Vec<ScoringProfile>, sqlx::Error> {
+    let rows = sqlx::query("SELECT * FROM scoring_profiles ORDER BY name")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.iter().map(row_to_profile).collect())
+}
+
+/// Get a scoring profile by name
+pub async fn get_by_name(pool: &PgPool, name: &str) -> Result<Option<ScoringProfile>, sqlx::Error> {
+    let row = sqlx::query("SELECT * FROM scoring_profiles WHERE name = $1")
+        .bind(name)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.as_ref().map(row_to_profile))
+}
+
+/// Get the profile a repository scores with (its assignment, or the default)
+pub async fn get_for_repo(
+    pool: &PgPool,
+    repo_id: Uuid,
+) -> Result<Option<ScoringProfile>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT sp.*
+        FROM repositories r
+        JOIN scoring_profiles sp ON sp.id = COALESCE(
+            r.scoring_profile_id,
+            (SELECT id FROM scoring_profiles WHERE name = 'default')
+        )
+        WHERE r.id = $1
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.as_ref().map(row_to_profile))
+}
+
+/// Weights a repository scores with, if any profile applies
+pub async fn weights_for_repo(
+    pool: &PgPool,
+    repo_id: Uuid,
+) -> Result<Option<ScoringWeights>, sqlx::Error> {
+    Ok(get_for_repo(pool, repo_id).await?.map(|p| p.weights))
+}
+
+/// Create a scoring profile
+pub async fn create(
+    pool: &PgPool,
+    name: &str,
+    description: Option<&str>,
+    weights: &ScoringWeights,
+) -> Result<ScoringProfile, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO scoring_profiles (
+            name, description, base_xp, low_quality_xp, medium_quality_xp, high_quality_xp,
+            uncategorized_xp, logic_bonus, structural_bonus, fast_review_bonus,
+            fast_review_minutes, thorough_threshold, thorough_bonus, deep_threshold,
+            deep_bonus, session_gap_hours, min_comment_chars, addressed_bonus,
+            difficulty_max_pct, difficulty_lines, difficulty_files
+        )
+        VALUES (
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18,
+            $19, $20, $21
+        )
+        RETURNING *
+        "#,
+    )
+    .bind(name)
+    .bind(description)
+    .bind(weights.base_xp)
+    .bind(weights.low_quality_xp)
+    .bind(weights.medium_quality_xp)
+    .bind(weights.high_quality_xp)
+    .bind(weights.uncategorized_xp)
+    .bind(weights.logic_bonus)
+    .bind(weights.structural_bonus)
+    .bind(weights.fast_review_bonus)
+    .bind(weights.fast_review_minutes)
+    .bind(weights.thorough_threshold)
+    .bind(weights.thorough_bonus)
+    .bind(weights.deep_threshold)
+    .bind(weights.deep_bonus)
+    .bind(weights.session_gap_hours)
+    .bind(weights.min_comment_chars)
+    .bind(weights.addressed_bonus)
+    .bind(weights.difficulty_max_pct)
+    .bind(weights.difficulty_lines)
+    .bind(weights.difficulty_files)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row_to_profile(&row))
+}
+
+/// Update a scoring profile and mark reviews in repos using it for rescoring
+pub async fn update(
+    pool: &PgPool,
+    profile_id: Uuid,
+    description: Option<&str>,
+    weights: &ScoringWeights,
+) -> Result<ScoringProfile, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let row = sqlx::query(
+        r#"
+        UPDATE scoring_profiles SET
+            description = $2,
+            base_xp = $3,
+            low_quality_xp = $4,
+            medium_quality_xp = $5,
+            high_quality_xp = $6,
+            uncategorized_xp = $7,
+            logic_bonus = $8,
+            structural_bonus = $9,
+            fast_review_bonus = $10,
+            fast_review_minutes = $11,
+            thorough_threshold = $12,
+            thorough_bonus = $13,
+            deep_threshold = $14,
+            deep_bonus = $15,
+            session_gap_hours = $16,
+            min_comment_chars = $17,
+            addressed_bonus = $18,
+            difficulty_max_pct = $19,
+            difficulty_lines = $20,
+            difficulty_files = $21,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(profile_id)
+    .bind(description)
+    .bind(weights.base_xp)
+    .bind(weights.low_quality_xp)
+    .bind(weights.medium_quality_xp)
+    .bind(weights.high_quality_xp)
+    .bind(weights.uncategorized_xp)
+    .bind(weights.logic_bonus)
+    .bind(weights.structural_bonus)
+    .bind(weights.fast_review_bonus)
+    .bind(weights.fast_review_minutes)
+    .bind(weights.thorough_threshold)
+    .bind(weights.thorough_bonus)
+    .bind(weights.deep_threshold)
+    .bind(weights.deep_bonus)
+    .bind(weights.session_gap_hours)
+    .bind(weights.min_comment_chars)
+    .bind(weights.addressed_bonus)
+    .bind(weights.difficulty_max_pct)
+    .bind(weights.difficulty_lines)
+    .bind(weights.difficulty_files)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    touch_profile_reviews(&mut tx, profile_id).await?;
+    tx.commit().await?;
+
+    Ok(row_to_profile(&row))
+}
+
+/// Delete a scoring profile; repos using it fall back to the default
+pub async fn delete(pool: &PgPool, profile_id: Uuid) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    touch_profile_reviews(&mut tx, profile_id).await?;
+    sqlx::query("DELETE FROM scoring_profiles WHERE id = $1")
+        .bind(profile_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await
+}
+
+/// Assign a profile to a repository (None = default) and mark its reviews for rescoring
+pub async fn assign_to_repo(
+    pool: &PgPool,
+    repo_id: Uuid,
+    profile_id: Option<Uuid>,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("UPDATE repositories SET scoring_profile_id = $2 WHERE id = $1")
+        .bind(repo_id)
+        .bind(profile_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE reviews SET touched_at = NOW()
+        WHERE pr_id IN (SELECT id FROM pull_requests WHERE repo_id = $1)
+        "#,
+    )
+    .bind(repo_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+/// Get scoring weights for each PR (restricted to `pr_ids` if Some)
+pub async fn weights_by_pr(
+    pool: &PgPool,
+    pr_ids: Option<&[Uuid]>,
+) -> Result<HashMap<Uuid, ScoringWeights>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT p.id as pr_id, sp.*
+        FROM pull_requests p
+        JOIN repositories r ON r.id = p.repo_id
+        JOIN scoring_profiles sp ON sp.id = COALESCE(
+            r.scoring_profile_id,
+            (SELECT id FROM scoring_profiles WHERE name = 'default')
+        )
+        WHERE $1::uuid[] IS NULL OR p.id = ANY($1)
+        "#,
+    )
+    .bind(pr_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| (r.get("pr_id"), row_to_weights(r)))
+        .collect())
+}
+
+/// Bump touched_at on every review scored with this profile so the next
+/// incremental recalculation picks them up
+async fn touch_profile_reviews(
+    tx: &mut Transaction<'_, Postgres>,
+    profile_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE reviews SET touched_at = NOW()
+        WHERE pr_id IN (
+            SELECT p.id
+            FROM pull_requests p
+            JOIN repositories r ON r.id = p.repo_id
+            WHERE COALESCE(
+                r.scoring_profile_id,
+                (SELECT id FROM scoring_profiles WHERE name = 'default')
+            ) = $1
+        )
+        "#,
+    )
+    .bind(profile_id)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
@@ -5,6 +5,8 @@ use common::models::{LeaderboardEntry, Season, User, UserStats};
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
+use crate::leaderboard::Ranking;
+
 /// Get all seasons ordered by number descending
 pub async fn get_all_seasons(pool: &PgPool) -> Result<Vec<Season>, sqlx::Error> {
     let rows = sqlx::query(
@@ -143,6 +145,7 @@ pub async fn get_season_leaderboard(
     pool: &PgPool,
     season_id: Uuid,
     repo_id: Option<Uuid>,
+    decayed: bool,
     limit: i32,
 ) -> Result<Vec<LeaderboardEntry>, sqlx::Error> {
     // Get the season's date range
@@ -158,6 +161,14 @@ pub async fn get_season_leaderboard(
     let starts_at: DateTime<Utc> = season_row.get("starts_at");
     let ends_at: DateTime<Utc> = season_row.get("ends_at");
 
+    // Decayed ranking weighs the season's XP by age at the season's end (or now)
+    let ranking = if decayed {
+        Ranking::Decayed
+    } else {
+        Ranking::Since(starts_at)
+    };
+    let decay_scale = ranking.decay_scale(pool, ends_at.min(Utc::now())).await?;
+
     // Use the existing leaderboard logic but with season date bounds
     let rows = sqlx::query(
         r#"
@@ -179,7 +190,17 @@ pub async fn get_season_leaderboard(
                 COUNT(r.id)::int as reviews_given,
                 COUNT(DISTINCT r.pr_id)::int as prs_reviewed,
                 COALESCE(SUM(r.comments_count), 0)::int as comments_written,
-                COALESCE(SUM(r.xp_earned), 0)::bigint as period_xp,
+                CASE WHEN $5::float8 IS NULL THEN
+                    (COALESCE(SUM(r.xp_earned), 0) + COALESCE((
+                        SELECT SUM(ua.xp_awarded)
+                        FROM user_achievements ua
+                        WHERE ua.user_id = u.id
+                          AND ua.unlocked_at >= $1 AND ua.unlocked_at < $2
+                          AND $3::uuid IS NULL
+                    ), 0))::bigint
+                ELSE
+                    ROUND(COALESCE(SUM(r.xp_earned * r.decay_weight), 0) * $5)::bigint
+                END as period_xp,
                 COALESCE((SELECT COUNT(*) FROM first_reviews fr WHERE fr.reviewer_id = u.id), 0)::int as first_reviews
             FROM users u
             LEFT JOIN reviews r ON r.reviewer_id = u.id 
@@ -210,6 +231,7 @@ pub async fn get_season_leaderboard(
     .bind(ends_at)
     .bind(repo_id)
     .bind(limit as i64)
+    .bind(decay_scale)
     .fetch_all(pool)
     .await?;
 
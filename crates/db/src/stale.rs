@@ -0,0 +1,119 @@
+//! Stale PR queries
+//!
+//! Latest activity on open PRs, split by author and reviewers, and when the
+//! bot last posted about each. Staleness lives in `processor::stale`.
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Latest activity on an open PR
+#[derive(Debug, Clone)]
+pub struct OpenPrActivity {
+    pub pr_id: Uuid,
+    pub number: i32,
+    pub title: String,
+    pub author_login: String,
+    pub created_at: DateTime<Utc>,
+    /// Latest commit, or comment by the author
+    pub author_active_at: Option<DateTime<Utc>>,
+    /// Latest review or comment by anyone else (bots excluded)
+    pub reviewer_active_at: Option<DateTime<Utc>>,
+    /// Who that was
+    pub last_reviewer: Option<String>,
+}
+
+/// Latest activity on each of a repo's open PRs, oldest PR first
+pub async fn open_pr_activity(
+    pool: &PgPool,
+    repo_id: Uuid,
+) -> Result<Vec<OpenPrActivity>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            p.id,
+            p.number,
+            p.title,
+            author.login as author_login,
+            p.created_at,
+            GREATEST(
+                (SELECT MAX(c.committed_at) FROM commits c WHERE c.pr_id = p.id),
+                (SELECT MAX(rc.created_at) FROM review_comments rc
+                 WHERE rc.pr_id = p.id AND rc.user_id = p.author_id)
+            ) as author_active_at,
+            latest.at as reviewer_active_at,
+            latest.login as last_reviewer
+        FROM pull_requests p
+        JOIN users author ON author.id = p.author_id
+        LEFT JOIN LATERAL (
+            SELECT a.at, u.login
+            FROM (
+                SELECT reviewer_id as user_id, submitted_at as at FROM reviews WHERE pr_id = p.id
+                UNION ALL
+                SELECT user_id, created_at FROM review_comments WHERE pr_id = p.id
+            ) a
+            JOIN users u ON u.id = a.user_id
+            WHERE a.user_id <> p.author_id
+              AND u.login NOT LIKE '%[bot]'
+            ORDER BY a.at DESC
+            LIMIT 1
+        ) latest ON true
+        WHERE p.repo_id = $1 AND p.state = 'open'
+        ORDER BY p.created_at ASC
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| OpenPrActivity {
+            pr_id: r.get("id"),
+            number: r.get("number"),
+            title: r.get("title"),
+            author_login: r.get("author_login"),
+            created_at: r.get("created_at"),
+            author_active_at: r.get("author_active_at"),
+            reviewer_active_at: r.get("reviewer_active_at"),
+            last_reviewer: r.get("last_reviewer"),
+        })
+        .collect())
+}
+
+/// When the bot last posted about each of the given PRs
+pub async fn alerted_at(
+    pool: &PgPool,
+    pr_ids: &[Uuid],
+) -> Result<HashMap<Uuid, DateTime<Utc>>, sqlx::Error> {
+    let rows = sqlx::query("SELECT pr_id, alerted_at FROM stale_pr_alerts WHERE pr_id = ANY($1)")
+        .bind(pr_ids)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.get("pr_id"), r.get("alerted_at")))
+        .collect())
+}
+
+/// Record that the bot posted about a stale PR
+pub async fn mark_alerted(
+    pool: &PgPool,
+    pr_id: Uuid,
+    alerted_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO stale_pr_alerts (pr_id, alerted_at)
+        VALUES ($1, $2)
+        ON CONFLICT (pr_id) DO UPDATE SET alerted_at = EXCLUDED.alerted_at
+        "#,
+    )
+    .bind(pr_id)
+    .bind(alerted_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -1,10 +1,12 @@
 //! Team management and team leaderboard queries
 
-use chrono::{DateTime, Utc};
+use chrono::Utc;
 use common::models::{Team, TeamLeaderboardEntry};
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
+use crate::leaderboard::Ranking;
+
 /// Create a new team
 pub async fn create_team(
     pool: &PgPool,
@@ -143,9 +145,11 @@ pub async fn remove_member(pool: &PgPool, team_id: Uuid, user_id: Uuid) -> Resul
 pub async fn get_team_leaderboard(
     pool: &PgPool,
     repo_id: Option<Uuid>,
-    since: DateTime<Utc>,
+    ranking: Ranking,
     limit: i32,
 ) -> Result<Vec<TeamLeaderboardEntry>, sqlx::Error> {
+    let decay_scale = ranking.decay_scale(pool, Utc::now()).await?;
+
     let rows = sqlx::query(
         r#"
         WITH team_stats AS (
@@ -156,7 +160,20 @@ pub async fn get_team_leaderboard(
                 t.color,
                 t.created_at,
                 COUNT(DISTINCT tm.user_id)::int as member_count,
-                COALESCE(SUM(r.xp_earned), 0)::bigint as total_xp,
+                CASE WHEN $4::float8 IS NULL THEN
+                    (COALESCE(SUM(r.xp_earned), 0) + COALESCE((
+                        SELECT SUM(ua.xp_awarded)
+                        FROM user_achievements ua
+                        JOIN team_members tm2 ON tm2.user_id = ua.user_id
+                        JOIN users u2 ON u2.id = ua.user_id
+                        WHERE tm2.team_id = t.id
+                          AND u2.login NOT LIKE '%[bot]'
+                          AND ua.unlocked_at >= $1
+                          AND $2::uuid IS NULL
+                    ), 0))::bigint
+                ELSE
+                    ROUND(COALESCE(SUM(r.xp_earned * r.decay_weight), 0) * $4)::bigint
+                END as total_xp,
                 COUNT(r.id)::int as reviews_count
             FROM teams t
             LEFT JOIN team_members tm ON tm.team_id = t.id
@@ -182,9 +199,10 @@ pub async fn get_team_leaderboard(
         LIMIT $3
         "#,
     )
-    .bind(since)
+    .bind(ranking.since())
     .bind(repo_id)
     .bind(limit as i64)
+    .bind(decay_scale)
     .fetch_all(pool)
     .await?;
 
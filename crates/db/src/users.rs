@@ -3,6 +3,7 @@
 use chrono::{DateTime, Utc};
 use common::models::{User, UserStats};
 use sqlx::{PgPool, Row};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 /// Get or create a user from GitHub data
@@ -103,6 +104,40 @@ pub async fn upsert_returning_created(
     Ok((user, created))
 }
 
+/// IDs of every user who has reviewed or authored a PR, or holds an
+/// achievement
+pub async fn list_active_ids(pool: &PgPool) -> Result<Vec<Uuid>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT reviewer_id as id FROM reviews
+        UNION
+        SELECT author_id as id FROM pull_requests
+        UNION
+        SELECT user_id as id FROM user_achievements
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| r.get("id")).collect())
+}
+
+/// Logins for the given user IDs
+pub async fn logins_by_id(
+    pool: &PgPool,
+    ids: &[Uuid],
+) -> Result<HashMap<Uuid, String>, sqlx::Error> {
+    let rows = sqlx::query("SELECT id, login FROM users WHERE id = ANY($1)")
+        .bind(ids)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.get("id"), r.get("login")))
+        .collect())
+}
+
 /// Add XP to a user and potentially level up
 pub async fn add_xp(pool: &PgPool, user_id: Uuid, xp: i64) -> Result<User, sqlx::Error> {
     // Simple leveling: level = floor(sqrt(xp / 100)) + 1
@@ -167,8 +202,21 @@ pub async fn get_stats_for_repo(
             COALESCE((SELECT COUNT(*) FROM first_reviews fr WHERE fr.reviewer_id = $1), 0)::int as first_reviews,
             COUNT(DISTINCT pr.id) FILTER (WHERE pr.author_id = $1)::int as prs_authored,
             COUNT(DISTINCT pr.id) FILTER (WHERE pr.author_id = $1 AND pr.merged_at IS NOT NULL)::int as prs_merged,
-            COALESCE(SUM(r.xp_earned), 0)::bigint as period_xp,
-            COUNT(r.id) FILTER (WHERE r.xp_earned > 0)::int as sessions
+            (COALESCE(SUM(r.xp_earned), 0) + COALESCE((
+                SELECT SUM(ua.xp_awarded)
+                FROM user_achievements ua
+                WHERE ua.user_id = $1 AND ua.unlocked_at >= $2 AND $3::uuid IS NULL
+            ), 0))::bigint as period_xp,
+            COUNT(r.id) FILTER (WHERE r.xp_earned > 0)::int as sessions,
+            (
+                SELECT COUNT(*)
+                FROM review_threads t
+                JOIN pull_requests tp ON tp.id = t.pr_id
+                WHERE t.starter_id = $1
+                  AND t.first_reply_at IS NOT NULL
+                  AND t.started_at >= $2
+                  AND ($3::uuid IS NULL OR tp.repo_id = $3)
+            )::int as conversations_started
         FROM users u
         LEFT JOIN reviews r ON r.reviewer_id = u.id AND r.submitted_at >= $2
         LEFT JOIN pull_requests pr ON pr.id = r.pr_id
@@ -191,10 +239,55 @@ pub async fn get_stats_for_repo(
         prs_merged: row.get("prs_merged"),
         period_xp: row.get("period_xp"),
         sessions: row.get("sessions"),
+        conversations_started: row.get("conversations_started"),
         ..Default::default()
     })
 }
 
+/// For each PR the user reviewed since `since` (optionally in one repo), when
+/// it became ready for them (opened, or the last push before their first
+/// review) and when they first reviewed it. Their own PRs are skipped.
+pub async fn first_review_waits(
+    pool: &PgPool,
+    user_id: Uuid,
+    repo_id: Option<Uuid>,
+    since: DateTime<Utc>,
+) -> Result<Vec<(DateTime<Utc>, DateTime<Utc>)>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        WITH firsts AS (
+            SELECT r.pr_id, MIN(r.submitted_at) as reviewed_at
+            FROM reviews r
+            JOIN pull_requests p ON p.id = r.pr_id
+            WHERE r.reviewer_id = $1
+              AND p.author_id <> $1
+              AND r.submitted_at >= $2
+              AND ($3::uuid IS NULL OR p.repo_id = $3)
+            GROUP BY r.pr_id
+        )
+        SELECT
+            GREATEST(p.created_at, (
+                SELECT MAX(c.committed_at)
+                FROM commits c
+                WHERE c.pr_id = f.pr_id AND c.committed_at < f.reviewed_at
+            )) as ready_at,
+            f.reviewed_at
+        FROM firsts f
+        JOIN pull_requests p ON p.id = f.pr_id
+        "#,
+    )
+    .bind(user_id)
+    .bind(since)
+    .bind(repo_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.get("ready_at"), r.get("reviewed_at")))
+        .collect())
+}
+
 /// Get weekly activity for a user (reviews per week)
 pub async fn get_weekly_activity(
     pool: &PgPool,
@@ -0,0 +1,99 @@
+//! Reviewer workload queries
+//!
+//! Who is reviewing which open PRs and what they reviewed recently. Per
+//! reviewer totals live in `processor::workload`.
+
+use chrono::{DateTime, Utc};
+use common::models::Review;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::reviews::parse_review_state;
+
+/// A reviewer on an open PR
+#[derive(Debug, Clone)]
+pub struct OpenPrReviewer {
+    pub pr_id: Uuid,
+    pub reviewer_id: Uuid,
+    pub last_review_at: DateTime<Utc>,
+    /// Latest commit on the PR, if any
+    pub last_push_at: Option<DateTime<Utc>>,
+}
+
+/// Everyone who reviewed each of a repo's open PRs, other than its author
+/// (bots excluded)
+pub async fn open_pr_reviewers(
+    pool: &PgPool,
+    repo_id: Uuid,
+) -> Result<Vec<OpenPrReviewer>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT DISTINCT ON (r.pr_id, r.reviewer_id)
+            r.pr_id,
+            r.reviewer_id,
+            r.submitted_at as last_review_at,
+            (SELECT MAX(c.committed_at) FROM commits c WHERE c.pr_id = r.pr_id) as last_push_at
+        FROM reviews r
+        JOIN pull_requests p ON p.id = r.pr_id
+        JOIN users u ON u.id = r.reviewer_id
+        WHERE p.repo_id = $1
+          AND p.state = 'open'
+          AND r.reviewer_id <> p.author_id
+          AND u.login NOT LIKE '%[bot]'
+        ORDER BY r.pr_id, r.reviewer_id, r.submitted_at DESC
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| OpenPrReviewer {
+            pr_id: r.get("pr_id"),
+            reviewer_id: r.get("reviewer_id"),
+            last_review_at: r.get("last_review_at"),
+            last_push_at: r.get("last_push_at"),
+        })
+        .collect())
+}
+
+/// Reviews submitted on a repo's PRs since `since`, other than by the PR's
+/// author (bots excluded)
+pub async fn reviews_since(
+    pool: &PgPool,
+    repo_id: Uuid,
+    since: DateTime<Utc>,
+) -> Result<Vec<Review>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT r.id, r.pr_id, r.reviewer_id, r.github_id, r.state, r.body, r.comments_count, r.submitted_at
+        FROM reviews r
+        JOIN pull_requests p ON p.id = r.pr_id
+        JOIN users u ON u.id = r.reviewer_id
+        WHERE p.repo_id = $1
+          AND r.submitted_at >= $2
+          AND r.reviewer_id <> p.author_id
+          AND u.login NOT LIKE '%[bot]'
+        ORDER BY r.submitted_at ASC
+        "#,
+    )
+    .bind(repo_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| Review {
+            id: r.get("id"),
+            pr_id: r.get("pr_id"),
+            reviewer_id: r.get("reviewer_id"),
+            github_id: r.get("github_id"),
+            state: parse_review_state(r.get("state")),
+            body: r.get("body"),
+            comments_count: r.get("comments_count"),
+            submitted_at: r.get("submitted_at"),
+        })
+        .collect())
+}
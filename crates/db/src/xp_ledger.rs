@@ -0,0 +1,381 @@
+//! Append-only XP ledger
+//!
+//! Every award is a row with itemized components. When a session is rescored
+//! with a different result, a `reversal` row cancels its previous entries and
+//! a new `session` row records the new breakdown, so the sum of a user's
+//! ledger always equals their XP. Rows keep their repo and PR labels, so the
+//! history stays readable after a repo is purged.
+
+use chrono::{DateTime, Utc};
+use common::models::{UserAchievement, XpBreakdown};
+use serde::Serialize;
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use crate::recalc::SessionAward;
+
+/// Component columns recorded for sessions, in XpBreakdown order, then total
+const SESSION_COLUMNS: [&str; 14] = [
+    "base",
+    "comments_low",
+    "comments_medium",
+    "comments_high",
+    "comments_uncategorized",
+    "logic_bonus",
+    "structural_bonus",
+    "addressed_bonus",
+    "fast_bonus",
+    "thorough_bonus",
+    "deep_bonus",
+    "difficulty_bonus",
+    "difficulty_pct",
+    "total",
+];
+
+/// A ledger row, with PR and repo resolved for display
+#[derive(Debug, Clone, Serialize)]
+pub struct LedgerEntry {
+    pub id: i64,
+    /// `session`, `reversal`, `achievement` or `revocation`
+    pub kind: String,
+    pub session_review_id: Option<Uuid>,
+    pub repo: Option<String>,
+    pub pr_number: Option<i32>,
+    pub pr_title: Option<String>,
+    pub achievement_id: Option<String>,
+    pub rule_version: i32,
+    pub scoring_profile: Option<String>,
+    pub components: XpBreakdown,
+    pub total: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Filter for listing a user's ledger
+#[derive(Debug, Clone, Default)]
+pub struct LedgerFilter {
+    pub repo_id: Option<Uuid>,
+    pub pr_number: Option<i32>,
+    pub limit: i64,
+}
+
+/// Record rescored sessions: reverse entries whose breakdown changed (or whose
+/// session no longer exists) and append the new ones.
+///
+/// `scope` limits the sessions considered to the given (pr_ids, reviewer_ids)
+/// pairs; None means every session in the ledger.
+pub(crate) async fn record_sessions(
+    tx: &mut Transaction<'_, Postgres>,
+    awards: &[SessionAward],
+    rule_version: i32,
+    scope: Option<(&[Uuid], &[Uuid])>,
+) -> Result<(), sqlx::Error> {
+    let cols = SESSION_COLUMNS.join(", ");
+    let zeros = vec!["0"; SESSION_COLUMNS.len()].join(", ");
+    let prefixed = |alias: &str| {
+        SESSION_COLUMNS
+            .iter()
+            .map(|c| format!("{}.{}", alias, c))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let sums = SESSION_COLUMNS
+        .iter()
+        .map(|c| format!("SUM({c})::int as {c}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let negated = SESSION_COLUMNS
+        .iter()
+        .map(|c| format!("-n.{}", c))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    // Stage the new breakdowns
+    let column = |f: fn(&XpBreakdown) -> i32| -> Vec<i32> {
+        awards.iter().map(|a| f(&a.breakdown)).collect()
+    };
+    sqlx::query(&format!(
+        r#"
+        CREATE TEMP TABLE ledger_staging ON COMMIT DROP AS
+        SELECT * FROM UNNEST(
+            $1::uuid[], $2::uuid[], $3::uuid[],
+            $4::int[], $5::int[], $6::int[], $7::int[], $8::int[], $9::int[],
+            $10::int[], $11::int[], $12::int[], $13::int[], $14::int[], $15::int[],
+            $16::int[], $17::int[]
+        ) AS s(review_id, pr_id, user_id, {cols})
+        "#
+    ))
+    .bind(awards.iter().map(|a| a.review_id).collect::<Vec<_>>())
+    .bind(awards.iter().map(|a| a.pr_id).collect::<Vec<_>>())
+    .bind(awards.iter().map(|a| a.reviewer_id).collect::<Vec<_>>())
+    .bind(column(|b| b.base))
+    .bind(column(|b| b.comments_low))
+    .bind(column(|b| b.comments_medium))
+    .bind(column(|b| b.comments_high))
+    .bind(column(|b| b.comments_uncategorized))
+    .bind(column(|b| b.logic_bonus))
+    .bind(column(|b| b.structural_bonus))
+    .bind(column(|b| b.addressed_bonus))
+    .bind(column(|b| b.fast_bonus))
+    .bind(column(|b| b.thorough_bonus))
+    .bind(column(|b| b.deep_bonus))
+    .bind(column(|b| b.difficulty_bonus))
+    .bind(column(|b| b.difficulty_pct))
+    .bind(column(|b| b.total() as i32))
+    .execute(&mut **tx)
+    .await?;
+
+    // Current net per session within scope
+    let (scope_prs, scope_users) = scope.unzip();
+    sqlx::query(&format!(
+        r#"
+        CREATE TEMP TABLE ledger_net ON COMMIT DROP AS
+        SELECT session_review_id as review_id, pr_id, user_id, {sums}
+        FROM xp_ledger
+        WHERE kind IN ('session', 'reversal')
+          AND session_review_id IS NOT NULL
+          AND ($1::uuid[] IS NULL
+               OR (pr_id, user_id) IN (SELECT * FROM UNNEST($1::uuid[], $2::uuid[])))
+        GROUP BY session_review_id, pr_id, user_id
+        "#
+    ))
+    .bind(scope_prs)
+    .bind(scope_users)
+    .execute(&mut **tx)
+    .await?;
+
+    // Repo and scoring profile of the PR in `pr_col`
+    let profile_join = |pr_col: &str| {
+        format!(
+            r#"
+            JOIN pull_requests p ON p.id = {pr_col}
+            JOIN repositories r ON r.id = p.repo_id
+            LEFT JOIN scoring_profiles sp ON sp.id = COALESCE(
+                r.scoring_profile_id,
+                (SELECT id FROM scoring_profiles WHERE name = 'default')
+            )
+            "#
+        )
+    };
+
+    // Cancel sessions that changed or disappeared
+    sqlx::query(&format!(
+        r#"
+        INSERT INTO xp_ledger (
+            user_id, kind, session_review_id, pr_id, repo_id, repo_name, pr_number, pr_title,
+            rule_version, scoring_profile, {cols}
+        )
+        SELECT n.user_id, 'reversal', n.review_id, n.pr_id, p.repo_id,
+               r.owner || '/' || r.name, p.number, p.title, $1, sp.name, {negated}
+        FROM ledger_net n
+        LEFT JOIN ledger_staging s ON s.review_id = n.review_id
+        {join}
+        WHERE ({n_cols}) IS DISTINCT FROM ({s_cols})
+          AND ({n_cols}) <> ({zeros})
+        "#,
+        join = profile_join("n.pr_id"),
+        n_cols = prefixed("n"),
+        s_cols = prefixed("s"),
+    ))
+    .bind(rule_version)
+    .execute(&mut **tx)
+    .await?;
+
+    // Append new or changed sessions (rubber stamps with no history are skipped)
+    sqlx::query(&format!(
+        r#"
+        INSERT INTO xp_ledger (
+            user_id, kind, session_review_id, pr_id, repo_id, repo_name, pr_number, pr_title,
+            rule_version, scoring_profile, {cols}
+        )
+        SELECT s.user_id, 'session', s.review_id, s.pr_id, p.repo_id,
+               r.owner || '/' || r.name, p.number, p.title, $1, sp.name, {s_cols}
+        FROM ledger_staging s
+        LEFT JOIN ledger_net n ON n.review_id = s.review_id
+        {join}
+        WHERE ({s_cols}) IS DISTINCT FROM ({n_cols})
+          AND ({s_cols}) <> ({zeros})
+        "#,
+        join = profile_join("s.pr_id"),
+        n_cols = prefixed("n"),
+        s_cols = prefixed("s"),
+    ))
+    .bind(rule_version)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Reverse every session recorded in a repository, before its reviews are
+/// deleted. Reversals carry each session's last rule version and profile.
+pub(crate) async fn reverse_repo(
+    tx: &mut Transaction<'_, Postgres>,
+    repo_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    let cols = SESSION_COLUMNS.join(", ");
+    let sums = SESSION_COLUMNS
+        .iter()
+        .map(|c| format!("-SUM({c})::int"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let nonzero = SESSION_COLUMNS
+        .iter()
+        .map(|c| format!("SUM({c}) <> 0"))
+        .collect::<Vec<_>>()
+        .join(" OR ");
+
+    sqlx::query(&format!(
+        r#"
+        INSERT INTO xp_ledger (
+            user_id, kind, session_review_id, pr_id, repo_id, repo_name, pr_number, pr_title,
+            rule_version, scoring_profile, {cols}
+        )
+        SELECT user_id, 'reversal', session_review_id, pr_id, repo_id,
+               MAX(repo_name), MAX(pr_number), MAX(pr_title),
+               MAX(rule_version), (ARRAY_AGG(scoring_profile ORDER BY id DESC))[1], {sums}
+        FROM xp_ledger
+        WHERE repo_id = $1
+          AND kind IN ('session', 'reversal')
+          AND session_review_id IS NOT NULL
+        GROUP BY session_review_id, pr_id, repo_id, user_id
+        HAVING {nonzero}
+        "#
+    ))
+    .bind(repo_id)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Record the reward credited for an achievement unlock
+pub(crate) async fn record_achievement(
+    tx: &mut Transaction<'_, Postgres>,
+    unlocked: &UserAchievement,
+    rule_version: i32,
+) -> Result<(), sqlx::Error> {
+    if unlocked.xp_awarded == 0 {
+        return Ok(());
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO xp_ledger (user_id, kind, achievement_id, rule_version, achievement, total, created_at)
+        VALUES ($1, 'achievement', $2, $3, $4, $4, $5)
+        "#,
+    )
+    .bind(unlocked.user_id)
+    .bind(&unlocked.achievement_id)
+    .bind(rule_version)
+    .bind(unlocked.xp_awarded)
+    .bind(unlocked.unlocked_at)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Record the reward taken back when an achievement is revoked
+pub(crate) async fn record_revocation(
+    tx: &mut Transaction<'_, Postgres>,
+    revoked: &UserAchievement,
+    rule_version: i32,
+) -> Result<(), sqlx::Error> {
+    if revoked.xp_awarded == 0 {
+        return Ok(());
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO xp_ledger (user_id, kind, achievement_id, rule_version, achievement, total)
+        VALUES ($1, 'revocation', $2, $3, $4, $4)
+        "#,
+    )
+    .bind(revoked.user_id)
+    .bind(&revoked.achievement_id)
+    .bind(rule_version)
+    .bind(-revoked.xp_awarded)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// List a user's ledger entries, newest first
+pub async fn list_for_user(
+    pool: &PgPool,
+    user_id: Uuid,
+    filter: &LedgerFilter,
+) -> Result<Vec<LedgerEntry>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT l.*
+        FROM xp_ledger l
+        WHERE l.user_id = $1
+          AND ($2::uuid IS NULL OR l.repo_id = $2)
+          AND ($3::int IS NULL OR l.pr_number = $3)
+        ORDER BY l.id DESC
+        LIMIT $4
+        "#,
+    )
+    .bind(user_id)
+    .bind(filter.repo_id)
+    .bind(filter.pr_number)
+    .bind(filter.limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| LedgerEntry {
+            id: r.get("id"),
+            kind: r.get("kind"),
+            session_review_id: r.get("session_review_id"),
+            repo: r.get("repo_name"),
+            pr_number: r.get("pr_number"),
+            pr_title: r.get("pr_title"),
+            achievement_id: r.get("achievement_id"),
+            rule_version: r.get("rule_version"),
+            scoring_profile: r.get("scoring_profile"),
+            components: XpBreakdown {
+                base: r.get("base"),
+                comments_low: r.get("comments_low"),
+                comments_medium: r.get("comments_medium"),
+                comments_high: r.get("comments_high"),
+                comments_uncategorized: r.get("comments_uncategorized"),
+                logic_bonus: r.get("logic_bonus"),
+                structural_bonus: r.get("structural_bonus"),
+                addressed_bonus: r.get("addressed_bonus"),
+                fast_bonus: r.get("fast_bonus"),
+                thorough_bonus: r.get("thorough_bonus"),
+                deep_bonus: r.get("deep_bonus"),
+                difficulty_bonus: r.get("difficulty_bonus"),
+                difficulty_pct: r.get("difficulty_pct"),
+                achievement: r.get("achievement"),
+            },
+            total: r.get("total"),
+            created_at: r.get("created_at"),
+        })
+        .collect())
+}
+
+/// Net XP across a user's ledger entries matching the filter (ignores limit)
+pub async fn net_for_user(
+    pool: &PgPool,
+    user_id: Uuid,
+    filter: &LedgerFilter,
+) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT COALESCE(SUM(l.total), 0)::bigint as net
+        FROM xp_ledger l
+        WHERE l.user_id = $1
+          AND ($2::uuid IS NULL OR l.repo_id = $2)
+          AND ($3::int IS NULL OR l.pr_number = $3)
+        "#,
+    )
+    .bind(user_id)
+    .bind(filter.repo_id)
+    .bind(filter.pr_number)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.get("net"))
+}
@@ -38,6 +38,14 @@ pub struct GithubPr {
     pub closed_at: Option<DateTime<Utc>>,
 }
 
+/// Size of a PR, only returned by the single PR endpoint
+#[derive(Debug, Deserialize)]
+pub struct GithubPrSize {
+    pub additions: i32,
+    pub deletions: i32,
+    pub changed_files: i32,
+}
+
 /// Review as returned by GitHub API
 #[derive(Debug, Deserialize)]
 pub struct GithubReview {
@@ -77,6 +85,9 @@ pub struct GithubReviewComment {
     pub diff_hunk: Option<String>,
     pub line: Option<i32>,
     pub in_reply_to_id: Option<i64>,
+    /// Line in the commit the comment was made on (`line` is null once the
+    /// comment is outdated)
+    pub original_line: Option<i32>,
 }
 
 /// Commit as returned by GitHub API
@@ -97,6 +108,21 @@ pub struct GithubCommitAuthor {
     pub date: DateTime<Utc>,
 }
 
+/// Single commit with its changed files
+#[derive(Debug, Deserialize)]
+pub struct GithubCommitWithFiles {
+    #[serde(default)]
+    pub files: Vec<GithubCommitFile>,
+}
+
+/// File changed by a commit or PR. `patch` is missing for binary or very
+/// large diffs.
+#[derive(Debug, Deserialize)]
+pub struct GithubCommitFile {
+    pub filename: String,
+    pub patch: Option<String>,
+}
+
 impl GitHubClient {
     pub fn new(token: Option<String>) -> Self {
         let client = reqwest::Client::new();
@@ -171,6 +197,34 @@ impl GitHubClient {
         self.get(&url).await
     }
 
+    /// Fetch the lines and files a PR changes
+    pub async fn fetch_pr_size(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: i32,
+    ) -> Result<GithubPrSize, ClientError> {
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/pulls/{}",
+            owner, repo, pr_number
+        );
+        self.get(&url).await
+    }
+
+    /// Fetch the first 100 files a PR changes
+    pub async fn fetch_pr_files(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: i32,
+    ) -> Result<Vec<GithubCommitFile>, ClientError> {
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/pulls/{}/files?per_page=100",
+            owner, repo, pr_number
+        );
+        self.get(&url).await
+    }
+
     /// Fetch all reviews for a PR
     pub async fn list_reviews(
         &self,
@@ -271,6 +325,21 @@ impl GitHubClient {
         );
         self.get(&url).await
     }
+
+    /// Fetch the files changed by a commit
+    pub async fn fetch_commit_files(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+    ) -> Result<Vec<GithubCommitFile>, ClientError> {
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/commits/{}",
+            owner, repo, sha
+        );
+        let commit: GithubCommitWithFiles = self.get(&url).await?;
+        Ok(commit.files)
+    }
 }
 
 #[cfg(test)]
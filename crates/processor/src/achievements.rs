@@ -1,136 +1,308 @@
 //! Achievement checking and unlocking
+//!
+//! Unlock conditions are the declarative rules in `db::achievement_rules`
+//! (built in, or stored on custom achievements), the same ones the progress
+//! API reports against.
+//!
+//! Evaluation is idempotent: it compares the current metric values with what
+//! the user holds, unlocking achievements that are met and reporting held ones
+//! that no longer are. Revoking those is opt-in, audit-logged, and never
+//! announced on Discord. Rules with a time window are earned once: their
+//! activity ages out of the window, so they are never reported.
+//!
+//! Achievements are also tracked per repository and per team (reviews of PRs
+//! authored by the team's members): the same rules, evaluated on the scoped
+//! activity. Scoped unlocks carry no XP and aren't announced.
 
-use chrono::{Duration, Utc};
+use common::models::{AchievementMetric, AchievementScope};
+use db::achievement_rules::AchievementRule;
 use sqlx::PgPool;
-use tracing::info;
+use std::collections::HashSet;
+use tracing::{info, warn};
 use uuid::Uuid;
 
-/// Achievement definitions
-pub mod defs {
-    // Reviewer achievements
-    pub const FIRST_REVIEW: &str = "first_review";
-    pub const REVIEW_STREAK_7: &str = "review_streak_7";
-    pub const SPEED_DEMON: &str = "speed_demon";
-    pub const REVIEW_10: &str = "review_10";
-    pub const REVIEW_50: &str = "review_50";
-    pub const REVIEW_100: &str = "review_100";
-
-    // Author achievements
-    pub const FIRST_PR: &str = "first_pr";
-    pub const PR_MERGED_10: &str = "pr_merged_10";
-}
+use crate::sessions::XP_RULES_VERSION;
+
+#[cfg(test)]
+#[path = "achievements_test.rs"]
+mod achievements_test;
 
 /// Checks and awards achievements
 pub struct AchievementChecker {
     pool: PgPool,
 }
 
+/// Outcome of evaluating a user's achievements
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Evaluation {
+    /// Newly unlocked
+    pub unlocked: Vec<String>,
+    /// Held but no longer met (only revoked when asked to)
+    pub stale: Vec<String>,
+    /// Taken back
+    pub revoked: Vec<String>,
+    /// Newly unlocked within a repository or team
+    pub scoped_unlocked: Vec<(AchievementScope, String)>,
+    /// Held in a scope but no longer met (only revoked when asked to)
+    pub scoped_stale: Vec<(AchievementScope, String)>,
+    pub scoped_revoked: Vec<(AchievementScope, String)>,
+}
+
+impl Evaluation {
+    fn extend(&mut self, other: Evaluation) {
+        self.unlocked.extend(other.unlocked);
+        self.stale.extend(other.stale);
+        self.revoked.extend(other.revoked);
+        self.scoped_unlocked.extend(other.scoped_unlocked);
+        self.scoped_stale.extend(other.scoped_stale);
+        self.scoped_revoked.extend(other.scoped_revoked);
+    }
+}
+
+/// What changes for a user given each rule's current metric value: rules to
+/// unlock (met, not held) and to revoke (held, not met, and not earned once).
+/// Evaluation depends only on the current values, so repeating it is a no-op.
+pub fn plan_changes<'a>(
+    rules: &'a [AchievementRule],
+    values: &[i64],
+    held: &HashSet<String>,
+) -> (Vec<&'a AchievementRule>, Vec<(&'a AchievementRule, i64)>) {
+    let mut unlock = Vec::new();
+    let mut stale = Vec::new();
+    for (rule, &value) in rules.iter().zip(values) {
+        match (rule.is_met(value), held.contains(&rule.achievement_id)) {
+            (true, false) => unlock.push(rule),
+            (false, true) if !rule.is_earned_once() => stale.push((rule, value)),
+            _ => {}
+        }
+    }
+    (unlock, stale)
+}
+
 impl AchievementChecker {
     pub fn new(pool: PgPool) -> Self {
         Self { pool }
     }
 
-    /// Check achievements for a reviewer
-    pub async fn check_reviewer(&self, user_id: &Uuid) -> Result<Vec<String>, common::Error> {
-        let mut unlocked = Vec::new();
+    /// Check every achievement for a user
+    pub async fn check_user(&self, user_id: &Uuid) -> Result<Vec<String>, common::Error> {
+        Ok(self.evaluate(user_id, |_| true, false).await?.unlocked)
+    }
 
-        // Check review count milestones
-        let since = Utc::now() - Duration::days(365 * 10); // All time
-        let count = db::reviews::count_by_user(&self.pool, *user_id, since)
+    /// Re-evaluate every achievement for a user, globally and in each
+    /// repository and team: unlock what is now met and report (or, with
+    /// `revoke`, take back) what no longer is
+    pub async fn reevaluate_user(
+        &self,
+        user_id: &Uuid,
+        revoke: bool,
+    ) -> Result<Evaluation, common::Error> {
+        let mut evaluation = self.evaluate(user_id, |_| true, revoke).await?;
+
+        let scopes = db::achievements::list_scopes_for_user(&self.pool, *user_id)
             .await
             .map_err(|e| common::Error::Database(e.to_string()))?;
-
-        // First review
-        if count == 1 && self.try_unlock(user_id, defs::FIRST_REVIEW).await? {
-            unlocked.push(defs::FIRST_REVIEW.to_string());
+        for scope in scopes {
+            evaluation.extend(self.evaluate_scope(user_id, scope, revoke).await?);
         }
 
-        // 10 reviews
-        if count >= 10 && self.try_unlock(user_id, defs::REVIEW_10).await? {
-            unlocked.push(defs::REVIEW_10.to_string());
-        }
+        Ok(evaluation)
+    }
 
-        // 50 reviews
-        if count >= 50 && self.try_unlock(user_id, defs::REVIEW_50).await? {
-            unlocked.push(defs::REVIEW_50.to_string());
-        }
+    /// Re-evaluate every user with activity or achievements (e.g. after a new
+    /// achievement is defined or data is reconciled). Per-user failures are
+    /// logged and skipped.
+    pub async fn reevaluate_all(&self, revoke: bool) -> Result<Evaluation, common::Error> {
+        let users = db::users::list_active_ids(&self.pool)
+            .await
+            .map_err(|e| common::Error::Database(e.to_string()))?;
 
-        // 100 reviews
-        if count >= 100 && self.try_unlock(user_id, defs::REVIEW_100).await? {
-            unlocked.push(defs::REVIEW_100.to_string());
+        let mut total = Evaluation::default();
+        for user_id in &users {
+            match self.reevaluate_user(user_id, revoke).await {
+                Ok(evaluation) => total.extend(evaluation),
+                Err(e) => warn!("Achievement check failed for {}: {}", user_id, e),
+            }
         }
+        Ok(total)
+    }
+
+    /// Check achievements for a reviewer
+    pub async fn check_reviewer(&self, user_id: &Uuid) -> Result<Vec<String>, common::Error> {
+        let evaluation = self
+            .evaluate(user_id, |metric| !metric.is_author_metric(), false)
+            .await?;
+        Ok(evaluation.unlocked)
+    }
+
+    /// Check achievements for a PR author
+    pub async fn check_author(&self, user_id: &Uuid) -> Result<Vec<String>, common::Error> {
+        let evaluation = self
+            .evaluate(user_id, AchievementMetric::is_author_metric, false)
+            .await?;
+        Ok(evaluation.unlocked)
+    }
 
-        // Speed demon: 10+ fast reviews (within 1 hour of commits)
-        let fast_count = db::reviews::count_fast_reviews(&self.pool, *user_id)
+    /// Evaluate the rules matching `include` against the user's current
+    /// metrics
+    async fn evaluate(
+        &self,
+        user_id: &Uuid,
+        include: impl Fn(&AchievementMetric) -> bool,
+        revoke: bool,
+    ) -> Result<Evaluation, common::Error> {
+        let held: HashSet<String> = db::achievements::list_for_user(&self.pool, *user_id)
             .await
-            .map_err(|e| common::Error::Database(e.to_string()))?;
+            .map_err(|e| common::Error::Database(e.to_string()))?
+            .into_iter()
+            .map(|a| a.achievement_id)
+            .collect();
 
-        if fast_count >= 10 && self.try_unlock(user_id, defs::SPEED_DEMON).await? {
-            unlocked.push(defs::SPEED_DEMON.to_string());
+        let rules: Vec<AchievementRule> = db::achievement_rules::list_rules(&self.pool)
+            .await
+            .map_err(|e| common::Error::Database(e.to_string()))?
+            .into_iter()
+            .filter(|r| include(&r.metric))
+            .collect();
+        if rules.is_empty() {
+            return Ok(Evaluation::default());
         }
 
-        // 7-day review streak
-        let has_streak = db::reviews::has_7_day_streak(&self.pool, *user_id)
+        let values = db::achievement_rules::metric_values(&self.pool, *user_id, &rules)
             .await
             .map_err(|e| common::Error::Database(e.to_string()))?;
+        let (to_unlock, stale) = plan_changes(&rules, &values, &held);
 
-        if has_streak && self.try_unlock(user_id, defs::REVIEW_STREAK_7).await? {
-            unlocked.push(defs::REVIEW_STREAK_7.to_string());
+        let mut evaluation = Evaluation::default();
+        for rule in to_unlock {
+            if self.try_unlock(user_id, &rule.achievement_id).await? {
+                evaluation.unlocked.push(rule.achievement_id.clone());
+            }
+        }
+        for (rule, value) in stale {
+            evaluation.stale.push(rule.achievement_id.clone());
+            if revoke && self.try_revoke(user_id, rule, value).await? {
+                evaluation.revoked.push(rule.achievement_id.clone());
+            }
         }
 
-        Ok(unlocked)
+        Ok(evaluation)
     }
 
-    /// Check achievements for a PR author
-    pub async fn check_author(&self, user_id: &Uuid) -> Result<Vec<String>, common::Error> {
-        let mut unlocked = Vec::new();
+    /// Evaluate the scopable rules within one repository or team
+    async fn evaluate_scope(
+        &self,
+        user_id: &Uuid,
+        scope: AchievementScope,
+        revoke: bool,
+    ) -> Result<Evaluation, common::Error> {
+        let held: HashSet<String> =
+            db::achievements::list_for_user_in_scope(&self.pool, *user_id, scope)
+                .await
+                .map_err(|e| common::Error::Database(e.to_string()))?
+                .into_iter()
+                .map(|a| a.achievement_id)
+                .collect();
 
-        // Count PRs authored by this user
-        let pr_count = db::prs::count_by_author(&self.pool, *user_id)
+        let rules: Vec<AchievementRule> = db::achievement_rules::list_rules(&self.pool)
+            .await
+            .map_err(|e| common::Error::Database(e.to_string()))?
+            .iter()
+            .filter_map(|r| r.scoped(scope))
+            .collect();
+
+        let values = db::achievement_rules::metric_values(&self.pool, *user_id, &rules)
             .await
             .map_err(|e| common::Error::Database(e.to_string()))?;
+        let (to_unlock, stale) = plan_changes(&rules, &values, &held);
 
-        // First PR
-        if pr_count == 1 && self.try_unlock(user_id, defs::FIRST_PR).await? {
-            unlocked.push(defs::FIRST_PR.to_string());
+        let mut evaluation = Evaluation::default();
+        for rule in to_unlock {
+            let unlocked =
+                db::achievements::unlock_scoped(&self.pool, *user_id, &rule.achievement_id, scope)
+                    .await
+                    .map_err(|e| common::Error::Database(e.to_string()))?;
+            if unlocked {
+                info!(
+                    "🏆 Achievement unlocked: {} for user {:?} in {:?}",
+                    rule.achievement_id, user_id, scope
+                );
+                evaluation
+                    .scoped_unlocked
+                    .push((scope, rule.achievement_id.clone()));
+            }
         }
-
-        // Count merged PRs
-        let merged_count = db::prs::count_merged_by_author(&self.pool, *user_id)
+        for (rule, value) in stale {
+            evaluation
+                .scoped_stale
+                .push((scope, rule.achievement_id.clone()));
+            if !revoke {
+                continue;
+            }
+            let revoked = db::achievements::revoke_scoped(
+                &self.pool,
+                *user_id,
+                rule,
+                scope,
+                value,
+                XP_RULES_VERSION,
+            )
             .await
             .map_err(|e| common::Error::Database(e.to_string()))?;
-
-        // 10 merged PRs
-        if merged_count >= 10 && self.try_unlock(user_id, defs::PR_MERGED_10).await? {
-            unlocked.push(defs::PR_MERGED_10.to_string());
+            if revoked {
+                info!(
+                    "Achievement revoked: {} for user {:?} in {:?} ({} < {})",
+                    rule.achievement_id, user_id, scope, value, rule.threshold
+                );
+                evaluation
+                    .scoped_revoked
+                    .push((scope, rule.achievement_id.clone()));
+            }
         }
 
-        Ok(unlocked)
+        Ok(evaluation)
     }
 
-    /// Try to unlock an achievement, returns true if newly unlocked
+    /// Try to unlock an achievement (crediting its XP reward), returns true if newly unlocked
     async fn try_unlock(
         &self,
         user_id: &Uuid,
         achievement_id: &str,
     ) -> Result<bool, common::Error> {
-        // Check if already has it
-        let has = db::achievements::has_achievement(&self.pool, *user_id, achievement_id)
-            .await
-            .map_err(|e| common::Error::Database(e.to_string()))?;
+        let unlocked =
+            db::achievements::unlock(&self.pool, *user_id, achievement_id, XP_RULES_VERSION)
+                .await
+                .map_err(|e| common::Error::Database(e.to_string()))?;
 
-        if has {
+        let Some(unlocked) = unlocked else {
             return Ok(false);
-        }
+        };
+
+        info!(
+            "🏆 Achievement unlocked: {} for user {:?} (+{} XP)",
+            achievement_id, user_id, unlocked.xp_awarded
+        );
+        Ok(true)
+    }
 
-        // Unlock it
-        db::achievements::unlock(&self.pool, *user_id, achievement_id)
+    /// Revoke an achievement (taking back its XP), returns true if it was held
+    async fn try_revoke(
+        &self,
+        user_id: &Uuid,
+        rule: &AchievementRule,
+        value: i64,
+    ) -> Result<bool, common::Error> {
+        let revoked = db::achievements::revoke(&self.pool, *user_id, rule, value, XP_RULES_VERSION)
             .await
             .map_err(|e| common::Error::Database(e.to_string()))?;
 
+        let Some(revoked) = revoked else {
+            return Ok(false);
+        };
+
         info!(
-            "🏆 Achievement unlocked: {} for user {:?}",
-            achievement_id, user_id
+            "Achievement revoked: {} for user {:?} ({} < {}, -{} XP)",
+            rule.achievement_id, user_id, value, rule.threshold, revoked.xp_awarded
         );
         Ok(true)
     }
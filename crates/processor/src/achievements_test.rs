@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod tests {
+    use crate::achievements::plan_changes;
+    use db::achievement_rules::{rule_for, AchievementRule};
+    use std::collections::HashSet;
+
+    fn held(ids: &[&str]) -> HashSet<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_plan_unlocks_met_rules() {
+        let rules = vec![
+            rule_for("first_review").unwrap(),
+            rule_for("review_10").unwrap(),
+        ];
+        let (unlock, stale) = plan_changes(&rules, &[12, 12], &held(&[]));
+
+        let ids: Vec<_> = unlock.iter().map(|r| r.achievement_id.as_str()).collect();
+        assert_eq!(ids, ["first_review", "review_10"]);
+        assert!(stale.is_empty());
+    }
+
+    #[test]
+    fn test_plan_is_idempotent() {
+        let rules = vec![
+            rule_for("first_review").unwrap(),
+            rule_for("review_10").unwrap(),
+        ];
+        let (unlock, stale) =
+            plan_changes(&rules, &[12, 12], &held(&["first_review", "review_10"]));
+
+        assert!(unlock.is_empty());
+        assert!(stale.is_empty());
+    }
+
+    #[test]
+    fn test_plan_reports_held_rules_no_longer_met() {
+        let rules = vec![
+            rule_for("first_review").unwrap(),
+            rule_for("review_10").unwrap(),
+            rule_for("review_50").unwrap(),
+        ];
+        // Reviews were reassigned in a user merge: 12 -> 4
+        let (unlock, stale) =
+            plan_changes(&rules, &[4, 4, 4], &held(&["first_review", "review_10"]));
+
+        assert!(unlock.is_empty());
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].0.achievement_id, "review_10");
+        assert_eq!(stale[0].1, 4);
+    }
+
+    #[test]
+    fn test_plan_keeps_windowed_rules_once_earned() {
+        let rules = vec![AchievementRule {
+            achievement_id: "sprint".to_string(),
+            threshold: 5,
+            window_days: Some(7),
+            ..rule_for("review_10").unwrap()
+        }];
+        // The 5 reviews that unlocked it are older than the window now
+        let (unlock, stale) = plan_changes(&rules, &[0], &held(&["sprint"]));
+
+        assert!(unlock.is_empty());
+        assert!(stale.is_empty());
+    }
+}
@@ -0,0 +1,208 @@
+//! PR cycle-time analytics
+//!
+//! For PRs merged over the last `weeks`: time from opening to first review,
+//! review rounds (sessions across reviewers), time from the last approval to
+//! merge and total time to merge. Each is summarized as p50/p90 for the repo,
+//! per merge week and per author. Times count working hours on the author's
+//! calendar, like the open-PRs list.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use common::models::{
+    AuthorCycleTime, CycleTimeStats, Percentiles, RepoAnalytics, Review, WeeklyCycleTime,
+};
+use db::analytics::MergedPr;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::latency::Calendars;
+use crate::sessions::count_sessions;
+
+#[cfg(test)]
+#[path = "analytics_test.rs"]
+mod analytics_test;
+
+/// Weeks covered, unless configured
+pub const DEFAULT_WEEKS: i64 = 12;
+
+/// Cycle times of one merged PR
+#[derive(Debug, Clone)]
+pub struct PrCycleTime {
+    pub author: String,
+    pub merged_at: DateTime<Utc>,
+    pub time_to_first_review_hours: Option<f64>,
+    pub review_rounds: i64,
+    pub approval_to_merge_hours: Option<f64>,
+    pub time_to_merge_hours: f64,
+}
+
+/// The `p`th percentile (0-100) of sorted values, interpolating between
+/// neighbours; None if empty
+pub fn percentile(sorted: &[f64], p: f64) -> Option<f64> {
+    let last = sorted.len().checked_sub(1)?;
+    let rank = p.clamp(0.0, 100.0) / 100.0 * last as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64))
+}
+
+/// p50 and p90 of the given values
+pub fn percentiles(mut values: Vec<f64>) -> Percentiles {
+    values.sort_by(|a, b| a.total_cmp(b));
+    Percentiles {
+        count: values.len() as i64,
+        p50: percentile(&values, 50.0),
+        p90: percentile(&values, 90.0),
+    }
+}
+
+/// Monday (UTC) of the week `t` falls in
+pub fn week_start(t: DateTime<Utc>) -> NaiveDate {
+    let day = t.date_naive();
+    day - Duration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+/// Reviews submitted by the time their PR merged. Reviews on PRs not in
+/// `prs` are dropped.
+pub fn before_merge(reviews: Vec<Review>, prs: &[MergedPr]) -> Vec<Review> {
+    let merged_at: HashMap<Uuid, DateTime<Utc>> =
+        prs.iter().map(|pr| (pr.pr_id, pr.merged_at)).collect();
+    reviews
+        .into_iter()
+        .filter(|r| {
+            merged_at
+                .get(&r.pr_id)
+                .is_some_and(|m| r.submitted_at <= *m)
+        })
+        .collect()
+}
+
+/// First week of a window of `weeks` (at least 1) ending with the current
+/// week, and when it starts
+pub fn window(now: DateTime<Utc>, weeks: i64) -> (NaiveDate, DateTime<Utc>) {
+    let first_week = week_start(now) - Duration::weeks(weeks.max(1) - 1);
+    let since = first_week
+        .and_hms_opt(0, 0, 0)
+        .unwrap_or_default()
+        .and_utc();
+    (first_week, since)
+}
+
+/// Cycle times of each merged PR, given its review sessions
+pub fn cycle_times(
+    prs: &[MergedPr],
+    rounds: &HashMap<Uuid, i64>,
+    calendars: &Calendars,
+) -> Vec<PrCycleTime> {
+    prs.iter()
+        .map(|pr| {
+            let calendar = calendars.for_user(pr.author_id);
+            let hours = |from: DateTime<Utc>, to: DateTime<Utc>| {
+                calendar.working_time(from, to).num_minutes() as f64 / 60.0
+            };
+            PrCycleTime {
+                author: pr.author_login.clone(),
+                merged_at: pr.merged_at,
+                time_to_first_review_hours: pr.first_review_at.map(|t| hours(pr.created_at, t)),
+                review_rounds: rounds.get(&pr.pr_id).copied().unwrap_or(0),
+                approval_to_merge_hours: pr.last_approval_at.map(|t| hours(t, pr.merged_at)),
+                time_to_merge_hours: hours(pr.created_at, pr.merged_at),
+            }
+        })
+        .collect()
+}
+
+/// p50/p90 of each metric over the given PRs
+pub fn summarize(prs: &[&PrCycleTime]) -> CycleTimeStats {
+    CycleTimeStats {
+        prs: prs.len() as i64,
+        time_to_first_review_hours: percentiles(
+            prs.iter()
+                .filter_map(|pr| pr.time_to_first_review_hours)
+                .collect(),
+        ),
+        review_rounds: percentiles(prs.iter().map(|pr| pr.review_rounds as f64).collect()),
+        approval_to_merge_hours: percentiles(
+            prs.iter()
+                .filter_map(|pr| pr.approval_to_merge_hours)
+                .collect(),
+        ),
+        time_to_merge_hours: percentiles(prs.iter().map(|pr| pr.time_to_merge_hours).collect()),
+    }
+}
+
+/// Overall, weekly and per-author stats for `weeks` weeks from `first_week`
+pub fn analytics(prs: &[PrCycleTime], first_week: NaiveDate, weeks: i64) -> RepoAnalytics {
+    let all: Vec<&PrCycleTime> = prs.iter().collect();
+
+    let mut by_week: HashMap<NaiveDate, Vec<&PrCycleTime>> = HashMap::new();
+    let mut by_author: HashMap<&str, Vec<&PrCycleTime>> = HashMap::new();
+    for pr in prs {
+        by_week
+            .entry(week_start(pr.merged_at))
+            .or_default()
+            .push(pr);
+        by_author.entry(&pr.author).or_default().push(pr);
+    }
+
+    let by_week = (0..weeks)
+        .map(|i| {
+            let week_start = first_week + Duration::weeks(i);
+            WeeklyCycleTime {
+                week_start,
+                stats: summarize(by_week.get(&week_start).map(Vec::as_slice).unwrap_or(&[])),
+            }
+        })
+        .collect();
+
+    let mut by_author: Vec<AuthorCycleTime> = by_author
+        .into_iter()
+        .map(|(author, prs)| AuthorCycleTime {
+            author: author.to_string(),
+            stats: summarize(&prs),
+        })
+        .collect();
+    by_author.sort_by(|a, b| {
+        b.stats
+            .prs
+            .cmp(&a.stats.prs)
+            .then_with(|| a.author.cmp(&b.author))
+    });
+
+    RepoAnalytics {
+        weeks,
+        overall: summarize(&all),
+        by_week,
+        by_author,
+    }
+}
+
+/// Cycle-time analytics of a repo's PRs merged over the last `weeks` (at
+/// least 1), the current week included
+pub async fn repo_analytics(
+    pool: &PgPool,
+    repo_id: Uuid,
+    weeks: i64,
+) -> Result<RepoAnalytics, sqlx::Error> {
+    let (first_week, since) = window(Utc::now(), weeks);
+    let prs = db::analytics::merged_prs(pool, repo_id, since).await?;
+
+    let pr_ids: Vec<Uuid> = prs.iter().map(|pr| pr.pr_id).collect();
+    // Post-merge follow-ups aren't review rounds
+    let reviews = before_merge(db::analytics::reviews_for_prs(pool, &pr_ids).await?, &prs);
+    let commits = db::commits::list_for_prs(pool, &pr_ids).await?;
+    let max_gap = db::scoring_profiles::weights_for_repo(pool, repo_id)
+        .await?
+        .unwrap_or_default()
+        .session_gap();
+    let mut rounds: HashMap<Uuid, i64> = HashMap::new();
+    for ((pr_id, _), sessions) in count_sessions(reviews, &commits, max_gap) {
+        *rounds.entry(pr_id).or_insert(0) += sessions;
+    }
+
+    let calendars = Calendars::load(pool).await?;
+    Ok(analytics(
+        &cycle_times(&prs, &rounds, &calendars),
+        first_week,
+        weeks,
+    ))
+}
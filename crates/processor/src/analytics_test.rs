@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod tests {
+    use crate::analytics::*;
+    use crate::latency::Calendars;
+    use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+    use common::models::{Review, ReviewState};
+    use db::analytics::MergedPr;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 6, day, hour, 0, 0).unwrap()
+    }
+
+    fn cycle(author: &str, merged_at: DateTime<Utc>, merge_hours: f64) -> PrCycleTime {
+        PrCycleTime {
+            author: author.to_string(),
+            merged_at,
+            time_to_first_review_hours: None,
+            review_rounds: 1,
+            approval_to_merge_hours: None,
+            time_to_merge_hours: merge_hours,
+        }
+    }
+
+    #[test]
+    fn test_percentile_interpolates() {
+        assert_eq!(percentile(&[], 50.0), None);
+        assert_eq!(percentile(&[4.0], 90.0), Some(4.0));
+        assert_eq!(percentile(&[1.0, 2.0, 3.0, 4.0], 50.0), Some(2.5));
+        let values: Vec<f64> = (1..=11).map(|v| v as f64).collect();
+        assert_eq!(percentile(&values, 90.0), Some(10.0));
+    }
+
+    #[test]
+    fn test_percentiles_sorts_and_counts() {
+        let p = percentiles(vec![30.0, 10.0, 20.0]);
+        assert_eq!(p.count, 3);
+        assert_eq!(p.p50, Some(20.0));
+        assert_eq!(p.p90, Some(28.0));
+        assert_eq!(percentiles(Vec::new()).p50, None);
+    }
+
+    #[test]
+    fn test_week_start_is_monday() {
+        // 2026-06-01 is a Monday
+        let monday = NaiveDate::from_ymd_opt(2026, 6, 1).unwrap();
+        assert_eq!(week_start(at(1, 0)), monday);
+        assert_eq!(week_start(at(7, 23)), monday);
+        assert_eq!(week_start(at(8, 0)), monday + Duration::weeks(1));
+    }
+
+    #[test]
+    fn test_cycle_times_from_milestones() {
+        let pr = MergedPr {
+            pr_id: Uuid::new_v4(),
+            number: 1,
+            title: "PR".to_string(),
+            author_id: Uuid::new_v4(),
+            author_login: "alice".to_string(),
+            created_at: at(1, 9),
+            merged_at: at(2, 9),
+            first_review_at: Some(at(1, 12)),
+            last_approval_at: Some(at(2, 3)),
+        };
+        let unreviewed = MergedPr {
+            pr_id: Uuid::new_v4(),
+            first_review_at: None,
+            last_approval_at: None,
+            ..pr.clone()
+        };
+        let rounds = HashMap::from([(pr.pr_id, 2)]);
+
+        let times = cycle_times(&[pr, unreviewed], &rounds, &Calendars::default());
+
+        assert_eq!(times[0].time_to_first_review_hours, Some(3.0));
+        assert_eq!(times[0].review_rounds, 2);
+        assert_eq!(times[0].approval_to_merge_hours, Some(6.0));
+        assert_eq!(times[0].time_to_merge_hours, 24.0);
+        assert_eq!(times[1].time_to_first_review_hours, None);
+        assert_eq!(times[1].review_rounds, 0);
+    }
+
+    #[test]
+    fn test_before_merge_drops_later_reviews() {
+        let pr = MergedPr {
+            pr_id: Uuid::new_v4(),
+            number: 1,
+            title: "PR".to_string(),
+            author_id: Uuid::new_v4(),
+            author_login: "alice".to_string(),
+            created_at: at(1, 9),
+            merged_at: at(2, 9),
+            first_review_at: None,
+            last_approval_at: None,
+        };
+        let review = |pr_id: Uuid, submitted_at: DateTime<Utc>| Review {
+            id: Uuid::new_v4(),
+            pr_id,
+            reviewer_id: Uuid::new_v4(),
+            github_id: 1,
+            state: ReviewState::Commented,
+            body: None,
+            comments_count: 0,
+            submitted_at,
+        };
+        let reviews = vec![
+            review(pr.pr_id, at(1, 12)),
+            review(pr.pr_id, at(2, 9)),
+            review(pr.pr_id, at(3, 9)),
+            review(Uuid::new_v4(), at(1, 12)),
+        ];
+        let kept: Vec<DateTime<Utc>> = before_merge(reviews, &[pr])
+            .iter()
+            .map(|r| r.submitted_at)
+            .collect();
+
+        assert_eq!(kept, vec![at(1, 12), at(2, 9)]);
+    }
+
+    #[test]
+    fn test_analytics_groups_by_week_and_author() {
+        let first_week = NaiveDate::from_ymd_opt(2026, 6, 1).unwrap();
+        let prs = vec![
+            cycle("alice", at(2, 10), 10.0),
+            cycle("bob", at(3, 10), 30.0),
+            cycle("alice", at(16, 10), 20.0),
+        ];
+
+        let result = analytics(&prs, first_week, 3);
+
+        assert_eq!(result.overall.prs, 3);
+        assert_eq!(result.overall.time_to_merge_hours.p50, Some(20.0));
+        assert_eq!(result.overall.time_to_first_review_hours.count, 0);
+
+        let weekly: Vec<i64> = result.by_week.iter().map(|w| w.stats.prs).collect();
+        assert_eq!(weekly, vec![2, 0, 1]);
+        assert_eq!(result.by_week[1].stats.time_to_merge_hours.p50, None);
+        assert_eq!(
+            result.by_week[2].week_start,
+            first_week + Duration::weeks(2)
+        );
+
+        assert_eq!(result.by_author[0].author, "alice");
+        assert_eq!(result.by_author[0].stats.prs, 2);
+        assert_eq!(
+            result.by_author[0].stats.time_to_merge_hours.p50,
+            Some(15.0)
+        );
+        assert_eq!(result.by_author[1].author, "bob");
+    }
+}
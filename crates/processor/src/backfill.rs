@@ -1,7 +1,7 @@
 //! Sync service for GitHub data
 
 use chrono::Utc;
-use common::models::{PrState, ReviewState};
+use common::models::{PrSize, PrState, RepoSyncSettings, ReviewState};
 use github::{GitHubClient, GithubPr};
 use sqlx::PgPool;
 use thiserror::Error;
@@ -55,6 +55,9 @@ impl Backfiller {
         // Get or create the repository
         let gh_repo = self.client.get_repo(owner, name).await?;
         let repo = db::repos::upsert(&self.pool, gh_repo.id, owner, name).await?;
+        let settings = db::repos::get_sync_settings(&self.pool, repo.id)
+            .await?
+            .unwrap_or_default();
 
         // Get last sync time - if none, use max_age_days as starting point
         let last_synced = db::repos::get_last_synced_at(&self.pool, repo.id).await?;
@@ -85,7 +88,7 @@ impl Backfiller {
 
         for pr in prs {
             progress.current_pr = Some(pr.number);
-            match self.process_pr(&repo.id, owner, name, &pr).await {
+            match self.process_pr(&repo.id, owner, name, &pr, &settings).await {
                 Ok((reviews_count, new_users)) => {
                     progress.reviews_processed += reviews_count;
                     progress.users_created += new_users;
@@ -132,6 +135,7 @@ impl Backfiller {
         owner: &str,
         repo_name: &str,
         pr: &GithubPr,
+        settings: &RepoSyncSettings,
     ) -> Result<(u32, u32), BackfillError> {
         debug!("Processing PR #{}: {}", pr.number, pr.title);
 
@@ -176,28 +180,68 @@ impl Backfiller {
             db::prs::update_timestamps(&self.pool, db_pr.id, pr.merged_at, pr.closed_at).await?;
         }
 
-        // Fetch commits for review session boundaries
-        match self.client.fetch_commits(owner, repo_name, pr.number).await {
-            Ok(commits) => {
-                for commit in commits {
-                    // Try to match commit author to a user (best effort)
-                    let author_id = None; // TODO: match by git email if needed
-                    let _ = db::commits::insert(
-                        &self.pool,
-                        db_pr.id,
-                        &commit.sha,
-                        author_id,
-                        commit.commit.author.date,
-                        Some(&commit.commit.message),
-                    )
-                    .await;
-                }
+        // Fetch size for the difficulty multiplier (not in the PR list response)
+        match self.client.fetch_pr_size(owner, repo_name, pr.number).await {
+            Ok(size) => {
+                let size = PrSize {
+                    additions: size.additions,
+                    deletions: size.deletions,
+                    changed_files: size.changed_files,
+                };
+                db::prs::set_size(&self.pool, db_pr.id, size).await?;
             }
             Err(github::client::ClientError::RateLimited { retry_after }) => {
                 return Err(BackfillError::RateLimited(retry_after));
             }
             Err(e) => {
-                debug!("Failed to fetch commits for PR #{}: {}", pr.number, e);
+                debug!("Failed to fetch size for PR #{}: {}", pr.number, e);
+            }
+        }
+
+        // Open PRs record their files for reviewer recommendations
+        if db_pr.state == PrState::Open {
+            match self
+                .client
+                .fetch_pr_files(owner, repo_name, pr.number)
+                .await
+            {
+                Ok(files) => {
+                    let paths: Vec<String> = files.into_iter().map(|f| f.filename).collect();
+                    db::prs::set_files(&self.pool, db_pr.id, &paths).await?;
+                }
+                Err(github::client::ClientError::RateLimited { retry_after }) => {
+                    return Err(BackfillError::RateLimited(retry_after));
+                }
+                Err(e) => {
+                    debug!("Failed to fetch files for PR #{}: {}", pr.number, e);
+                }
+            }
+        }
+
+        // Fetch commits for review session boundaries
+        if settings.fetch_commits {
+            match self.client.fetch_commits(owner, repo_name, pr.number).await {
+                Ok(commits) => {
+                    for commit in commits {
+                        // Try to match commit author to a user (best effort)
+                        let author_id = None; // TODO: match by git email if needed
+                        let _ = db::commits::insert(
+                            &self.pool,
+                            db_pr.id,
+                            &commit.sha,
+                            author_id,
+                            commit.commit.author.date,
+                            Some(&commit.commit.message),
+                        )
+                        .await;
+                    }
+                }
+                Err(github::client::ClientError::RateLimited { retry_after }) => {
+                    return Err(BackfillError::RateLimited(retry_after));
+                }
+                Err(e) => {
+                    debug!("Failed to fetch commits for PR #{}: {}", pr.number, e);
+                }
             }
         }
 
@@ -214,18 +258,22 @@ impl Backfiller {
         };
 
         // Fetch review comments to count per review
-        let comments = match self
-            .client
-            .list_review_comments(owner, repo_name, pr.number)
-            .await
-        {
-            Ok(c) => c,
-            Err(github::client::ClientError::RateLimited { retry_after }) => {
-                return Err(BackfillError::RateLimited(retry_after));
-            }
-            Err(e) => {
-                debug!("Failed to fetch comments for PR #{}: {}", pr.number, e);
-                Vec::new()
+        let comments = if !settings.fetch_comments {
+            Vec::new()
+        } else {
+            match self
+                .client
+                .list_review_comments(owner, repo_name, pr.number)
+                .await
+            {
+                Ok(c) => c,
+                Err(github::client::ClientError::RateLimited { retry_after }) => {
+                    return Err(BackfillError::RateLimited(retry_after));
+                }
+                Err(e) => {
+                    debug!("Failed to fetch comments for PR #{}: {}", pr.number, e);
+                    Vec::new()
+                }
             }
         };
 
@@ -319,6 +367,10 @@ impl Backfiller {
             }
         }
 
+        let has_inline_comments = comments
+            .iter()
+            .any(|c| c.path.is_some() && c.in_reply_to_id.is_none());
+
         // Store review comments for AI categorization (M5)
         for comment in comments {
             // Skip comments without a user
@@ -353,13 +405,58 @@ impl Backfiller {
                 &comment.body,
                 comment.path.as_deref(),
                 comment.diff_hunk.as_deref(),
-                comment.line,
+                comment.original_line.or(comment.line),
                 comment.in_reply_to_id,
                 comment.created_at,
             )
             .await;
         }
 
+        // Verify which comments a later commit acted on. Changed lines cost
+        // one request per commit, so only PRs with inline comments fetch them.
+        if settings.fetch_commits && has_inline_comments {
+            self.fetch_commit_hunks(owner, repo_name, db_pr.id).await?;
+            if let Err(e) = db::review_comments::mark_addressed(&self.pool, &[db_pr.id]).await {
+                warn!(
+                    "Failed to mark addressed comments for PR #{}: {}",
+                    pr.number, e
+                );
+            }
+        }
+
         Ok((reviews_count, new_users))
     }
+
+    /// Fetch and store the changed lines of a PR's commits that don't have
+    /// them yet
+    async fn fetch_commit_hunks(
+        &self,
+        owner: &str,
+        repo_name: &str,
+        pr_id: uuid::Uuid,
+    ) -> Result<(), BackfillError> {
+        for (commit_id, sha) in db::commits::list_without_hunks(&self.pool, pr_id).await? {
+            match self.client.fetch_commit_files(owner, repo_name, &sha).await {
+                Ok(files) => {
+                    let hunks: Vec<_> = files
+                        .iter()
+                        .filter_map(|f| {
+                            f.patch
+                                .as_deref()
+                                .map(|patch| crate::hunks::parse_patch(&f.filename, patch))
+                        })
+                        .flatten()
+                        .collect();
+                    db::commits::set_hunks(&self.pool, commit_id, &hunks).await?;
+                }
+                Err(github::client::ClientError::RateLimited { retry_after }) => {
+                    return Err(BackfillError::RateLimited(retry_after));
+                }
+                Err(e) => {
+                    debug!("Failed to fetch files for commit {}: {}", sha, e);
+                }
+            }
+        }
+        Ok(())
+    }
 }
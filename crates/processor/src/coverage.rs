@@ -0,0 +1,202 @@
+//! Review coverage report
+//!
+//! Flags merged PRs that got no real review: merged without any review,
+//! merged with nothing but rubber stamps (see `sessions::is_rubber_stamp`), or
+//! merged while a reviewer's change request was still standing. Counts are
+//! also given per merge week, along with how often each reviewer's approvals
+//! are rubber stamps.
+
+use chrono::{Duration, NaiveDate, Utc};
+use common::models::{
+    CoveragePr, CoverageReport, CoverageWeek, Repository, Review, ReviewState, RubberStampRate,
+};
+use db::analytics::MergedPr;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::analytics::{before_merge, week_start, window};
+use crate::sessions::{is_rubber_stamp, sessions_by_pair, ReviewSession};
+
+#[cfg(test)]
+#[path = "coverage_test.rs"]
+mod coverage_test;
+
+/// Weeks covered, unless configured
+pub const DEFAULT_WEEKS: i64 = 12;
+
+fn is_approval(session: &ReviewSession) -> bool {
+    session
+        .reviews
+        .iter()
+        .any(|r| r.state == ReviewState::Approved)
+}
+
+/// Reviewers whose latest verdict before the merge was a change request.
+/// Comment-only reviews don't clear it; an approval or dismissal does.
+fn standing_change_requests(pr: &MergedPr, sessions: &[(Uuid, &ReviewSession)]) -> Vec<Uuid> {
+    let mut verdicts: Vec<(Uuid, &Review)> = sessions
+        .iter()
+        .flat_map(|(reviewer, s)| s.reviews.iter().map(move |r| (*reviewer, r)))
+        .filter(|(_, r)| r.submitted_at <= pr.merged_at && r.state != ReviewState::Commented)
+        .collect();
+    verdicts.sort_by_key(|(_, r)| r.submitted_at);
+    let latest: HashMap<Uuid, &ReviewState> = verdicts
+        .into_iter()
+        .map(|(reviewer, r)| (reviewer, &r.state))
+        .collect();
+
+    let mut blocking: Vec<Uuid> = latest
+        .into_iter()
+        .filter(|(_, state)| **state == ReviewState::ChangesRequested)
+        .map(|(reviewer, _)| reviewer)
+        .collect();
+    blocking.sort();
+    blocking
+}
+
+/// Coverage report for `weeks` weeks from `first_week`, given the review
+/// sessions on each merged PR keyed by (PR, reviewer)
+pub fn coverage(
+    repo: &Repository,
+    prs: &[MergedPr],
+    sessions: &HashMap<(Uuid, Uuid), Vec<ReviewSession>>,
+    logins: &HashMap<Uuid, String>,
+    first_week: NaiveDate,
+    weeks: i64,
+) -> CoverageReport {
+    let mut by_pr: HashMap<Uuid, Vec<(Uuid, &ReviewSession)>> = HashMap::new();
+    for ((pr_id, reviewer_id), pair) in sessions {
+        for session in pair {
+            by_pr
+                .entry(*pr_id)
+                .or_default()
+                .push((*reviewer_id, session));
+        }
+    }
+    let names = |ids: Vec<Uuid>| -> Vec<String> {
+        let mut names: Vec<String> = ids
+            .iter()
+            .filter_map(|id| logins.get(id).cloned())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    };
+
+    let mut unreviewed = Vec::new();
+    let mut rubber_stamped = Vec::new();
+    let mut over_changes_requested = Vec::new();
+    let mut by_week: HashMap<NaiveDate, CoverageWeek> = HashMap::new();
+    let mut rates: HashMap<Uuid, (i64, i64)> = HashMap::new();
+
+    for pr in prs {
+        let pr_sessions = by_pr.get(&pr.pr_id).map(Vec::as_slice).unwrap_or(&[]);
+        let flagged = |reviewers: Vec<String>| CoveragePr {
+            number: pr.number,
+            title: pr.title.clone(),
+            author: pr.author_login.clone(),
+            url: format!(
+                "https://github.com/{}/{}/pull/{}",
+                repo.owner, repo.name, pr.number
+            ),
+            merged_at: pr.merged_at,
+            reviewers,
+        };
+        let week = by_week.entry(week_start(pr.merged_at)).or_default();
+        week.merged += 1;
+
+        for (reviewer, session) in pr_sessions {
+            if is_approval(session) {
+                let rate = rates.entry(*reviewer).or_default();
+                rate.0 += 1;
+                if is_rubber_stamp(session) {
+                    rate.1 += 1;
+                }
+            }
+        }
+
+        if pr_sessions.is_empty() {
+            week.unreviewed += 1;
+            unreviewed.push(flagged(Vec::new()));
+        } else if pr_sessions.iter().all(|(_, s)| is_rubber_stamp(s))
+            && pr_sessions.iter().any(|(_, s)| is_approval(s))
+        {
+            week.rubber_stamped += 1;
+            let stampers = pr_sessions.iter().map(|(reviewer, _)| *reviewer).collect();
+            rubber_stamped.push(flagged(names(stampers)));
+        }
+
+        let blocking = standing_change_requests(pr, pr_sessions);
+        if !blocking.is_empty() {
+            week.merged_over_changes_requested += 1;
+            over_changes_requested.push(flagged(names(blocking)));
+        }
+    }
+
+    let by_week = (0..weeks)
+        .map(|i| {
+            let week_start = first_week + Duration::weeks(i);
+            CoverageWeek {
+                week_start,
+                ..by_week.remove(&week_start).unwrap_or_default()
+            }
+        })
+        .collect();
+
+    let mut reviewers: Vec<RubberStampRate> = rates
+        .into_iter()
+        .filter_map(|(id, (approvals, rubber_stamps))| {
+            Some(RubberStampRate {
+                login: logins.get(&id)?.clone(),
+                approvals,
+                rubber_stamps,
+                rate: rubber_stamps as f64 / approvals as f64,
+            })
+        })
+        .collect();
+    reviewers.sort_by(|a, b| {
+        b.rate
+            .total_cmp(&a.rate)
+            .then(b.approvals.cmp(&a.approvals))
+            .then_with(|| a.login.cmp(&b.login))
+    });
+
+    CoverageReport {
+        weeks,
+        merged: prs.len() as i64,
+        unreviewed,
+        rubber_stamped,
+        merged_over_changes_requested: over_changes_requested,
+        by_week,
+        reviewers,
+    }
+}
+
+/// Coverage report of a repo's PRs merged over the last `weeks` (at least
+/// 1), the current week included
+pub async fn repo_coverage(
+    pool: &PgPool,
+    repo: &Repository,
+    weeks: i64,
+) -> Result<CoverageReport, sqlx::Error> {
+    let (first_week, since) = window(Utc::now(), weeks);
+    let prs = db::analytics::merged_prs(pool, repo.id, since).await?;
+
+    let pr_ids: Vec<Uuid> = prs.iter().map(|pr| pr.pr_id).collect();
+    // A review after the merge didn't gate it
+    let reviews = before_merge(db::analytics::reviews_for_prs(pool, &pr_ids).await?, &prs);
+    let commits = db::commits::list_for_prs(pool, &pr_ids).await?;
+    let max_gap = db::scoring_profiles::weights_for_repo(pool, repo.id)
+        .await?
+        .unwrap_or_default()
+        .session_gap();
+    let sessions = sessions_by_pair(reviews, &commits, max_gap);
+
+    let mut reviewer_ids: Vec<Uuid> = sessions.keys().map(|(_, reviewer)| *reviewer).collect();
+    reviewer_ids.sort();
+    reviewer_ids.dedup();
+    let logins = db::users::logins_by_id(pool, &reviewer_ids).await?;
+
+    Ok(coverage(repo, &prs, &sessions, &logins, first_week, weeks))
+}
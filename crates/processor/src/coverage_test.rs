@@ -0,0 +1,201 @@
+#[cfg(test)]
+mod tests {
+    use crate::analytics::before_merge;
+    use crate::coverage::*;
+    use crate::sessions::sessions_by_pair;
+    use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+    use common::models::{Repository, Review, ReviewState};
+    use db::analytics::MergedPr;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 6, day, hour, 0, 0).unwrap()
+    }
+
+    fn repo() -> Repository {
+        Repository {
+            id: Uuid::new_v4(),
+            github_id: 1,
+            owner: "acme".to_string(),
+            name: "widgets".to_string(),
+            created_at: at(1, 0),
+        }
+    }
+
+    fn merged(number: i32, merged_at: DateTime<Utc>) -> MergedPr {
+        MergedPr {
+            pr_id: Uuid::new_v4(),
+            number,
+            title: format!("PR {}", number),
+            author_id: Uuid::new_v4(),
+            author_login: "alice".to_string(),
+            created_at: merged_at - Duration::days(1),
+            merged_at,
+            first_review_at: None,
+            last_approval_at: None,
+        }
+    }
+
+    fn review(
+        pr: &MergedPr,
+        reviewer_id: Uuid,
+        state: ReviewState,
+        comments: i32,
+        submitted_at: DateTime<Utc>,
+    ) -> Review {
+        Review {
+            id: Uuid::new_v4(),
+            pr_id: pr.pr_id,
+            reviewer_id,
+            github_id: 1,
+            state,
+            body: None,
+            comments_count: comments,
+            submitted_at,
+        }
+    }
+
+    #[test]
+    fn test_flags_unreviewed_rubber_stamped_and_over_changes_requested() {
+        let (bob, carol) = (Uuid::new_v4(), Uuid::new_v4());
+        let logins = HashMap::from([(bob, "bob".to_string()), (carol, "carol".to_string())]);
+        let unreviewed = merged(1, at(2, 12));
+        let stamped = merged(2, at(3, 12));
+        let reviewed = merged(3, at(4, 12));
+        let blocked = merged(4, at(10, 12));
+        let reviews = vec![
+            review(&stamped, bob, ReviewState::Approved, 0, at(3, 10)),
+            review(&reviewed, bob, ReviewState::Commented, 3, at(4, 8)),
+            review(&reviewed, carol, ReviewState::Approved, 0, at(4, 10)),
+            review(&blocked, carol, ReviewState::ChangesRequested, 2, at(10, 8)),
+            // A later comment doesn't clear the change request
+            review(&blocked, carol, ReviewState::Commented, 1, at(10, 9)),
+            review(&blocked, bob, ReviewState::Approved, 1, at(10, 10)),
+        ];
+        let sessions = sessions_by_pair(reviews, &[], Duration::hours(24));
+        let prs = [unreviewed, stamped, reviewed, blocked];
+
+        let report = coverage(
+            &repo(),
+            &prs,
+            &sessions,
+            &logins,
+            NaiveDate::from_ymd_opt(2026, 6, 1).unwrap(),
+            2,
+        );
+
+        assert_eq!(report.merged, 4);
+        assert_eq!(report.unreviewed.len(), 1);
+        assert_eq!(report.unreviewed[0].number, 1);
+        assert_eq!(
+            report.unreviewed[0].url,
+            "https://github.com/acme/widgets/pull/1"
+        );
+        assert_eq!(report.rubber_stamped.len(), 1);
+        assert_eq!(report.rubber_stamped[0].number, 2);
+        assert_eq!(report.rubber_stamped[0].reviewers, vec!["bob"]);
+        assert_eq!(report.merged_over_changes_requested.len(), 1);
+        assert_eq!(report.merged_over_changes_requested[0].number, 4);
+        assert_eq!(
+            report.merged_over_changes_requested[0].reviewers,
+            vec!["carol"]
+        );
+
+        assert_eq!(report.by_week.len(), 2);
+        assert_eq!(report.by_week[0].merged, 3);
+        assert_eq!(report.by_week[0].unreviewed, 1);
+        assert_eq!(report.by_week[0].rubber_stamped, 1);
+        assert_eq!(report.by_week[1].merged, 1);
+        assert_eq!(report.by_week[1].merged_over_changes_requested, 1);
+    }
+
+    #[test]
+    fn test_approval_clears_change_request() {
+        let bob = Uuid::new_v4();
+        let logins = HashMap::from([(bob, "bob".to_string())]);
+        let pr = merged(1, at(5, 12));
+        let reviews = vec![
+            review(&pr, bob, ReviewState::ChangesRequested, 2, at(5, 8)),
+            review(&pr, bob, ReviewState::Approved, 1, at(5, 10)),
+        ];
+        let sessions = sessions_by_pair(reviews, &[], Duration::hours(24));
+
+        let report = coverage(
+            &repo(),
+            std::slice::from_ref(&pr),
+            &sessions,
+            &logins,
+            NaiveDate::from_ymd_opt(2026, 6, 1).unwrap(),
+            1,
+        );
+
+        assert!(report.merged_over_changes_requested.is_empty());
+        assert!(report.rubber_stamped.is_empty());
+    }
+
+    #[test]
+    fn test_reviews_after_merge_are_ignored() {
+        let (bob, carol) = (Uuid::new_v4(), Uuid::new_v4());
+        let logins = HashMap::from([(bob, "bob".to_string()), (carol, "carol".to_string())]);
+        let unreviewed = merged(1, at(2, 12));
+        let stamped = merged(2, at(3, 12));
+        let reviews = vec![
+            review(&unreviewed, carol, ReviewState::Approved, 2, at(2, 15)),
+            review(&stamped, bob, ReviewState::Approved, 0, at(3, 10)),
+            // Follow-up comments after the merge, within the session gap
+            review(&stamped, bob, ReviewState::Commented, 3, at(3, 14)),
+        ];
+        let prs = [unreviewed, stamped];
+        let sessions = sessions_by_pair(before_merge(reviews, &prs), &[], Duration::hours(24));
+
+        let report = coverage(
+            &repo(),
+            &prs,
+            &sessions,
+            &logins,
+            NaiveDate::from_ymd_opt(2026, 6, 1).unwrap(),
+            1,
+        );
+
+        assert_eq!(report.unreviewed.len(), 1);
+        assert_eq!(report.unreviewed[0].number, 1);
+        assert_eq!(report.rubber_stamped.len(), 1);
+        assert_eq!(report.rubber_stamped[0].number, 2);
+        assert_eq!(report.reviewers.len(), 1);
+        assert_eq!(report.reviewers[0].login, "bob");
+    }
+
+    #[test]
+    fn test_rubber_stamp_rates_per_reviewer() {
+        let (bob, carol) = (Uuid::new_v4(), Uuid::new_v4());
+        let logins = HashMap::from([(bob, "bob".to_string()), (carol, "carol".to_string())]);
+        let first = merged(1, at(2, 12));
+        let second = merged(2, at(3, 12));
+        let reviews = vec![
+            review(&first, bob, ReviewState::Approved, 0, at(2, 10)),
+            review(&second, bob, ReviewState::Approved, 2, at(3, 10)),
+            review(&first, carol, ReviewState::Approved, 4, at(2, 11)),
+            review(&second, carol, ReviewState::Commented, 1, at(3, 11)),
+        ];
+        let sessions = sessions_by_pair(reviews, &[], Duration::hours(24));
+
+        let report = coverage(
+            &repo(),
+            &[first, second],
+            &sessions,
+            &logins,
+            NaiveDate::from_ymd_opt(2026, 6, 1).unwrap(),
+            1,
+        );
+
+        assert_eq!(report.reviewers.len(), 2);
+        assert_eq!(report.reviewers[0].login, "bob");
+        assert_eq!(report.reviewers[0].approvals, 2);
+        assert_eq!(report.reviewers[0].rubber_stamps, 1);
+        assert_eq!(report.reviewers[0].rate, 0.5);
+        assert_eq!(report.reviewers[1].login, "carol");
+        assert_eq!(report.reviewers[1].approvals, 1);
+        assert_eq!(report.reviewers[1].rate, 0.0);
+    }
+}
@@ -0,0 +1,300 @@
+//! Code-area expertise map
+//!
+//! Every review comment on a file counts towards the file's directories (up
+//! to `MAX_DIR_DEPTH` levels), its extension and its language. A comment
+//! weighs its quality score over 5 (1 if not categorized) and halves every
+//! `HALF_LIFE_DAYS`, so recent, substantive reviews count most.
+
+use chrono::{DateTime, Duration, Utc};
+use common::models::{AreaExpert, AreaExperts, ExpertiseArea, RepoExperts, User, UserExpertise};
+use db::expertise::PathComment;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[cfg(test)]
+#[path = "expertise_test.rs"]
+mod expertise_test;
+
+/// Comments older than this are ignored
+pub const WINDOW_DAYS: i64 = 365;
+
+/// A comment's weight halves every this many days
+pub const HALF_LIFE_DAYS: f64 = 90.0;
+
+/// Deepest directory level tracked ("crates/api/src" is 3)
+pub const MAX_DIR_DEPTH: usize = 3;
+
+/// Start of the window of comments that count
+pub fn window_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    now - Duration::days(WINDOW_DAYS)
+}
+
+/// Weight of one comment: quality over 5 (1 if uncategorized), halved every
+/// `HALF_LIFE_DAYS` of age
+pub fn comment_weight(
+    quality_score: Option<i32>,
+    created_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> f64 {
+    let quality = quality_score.map_or(1.0, |q| q.clamp(1, 10) as f64 / 5.0);
+    let age_days = (now - created_at).num_seconds().max(0) as f64 / 86_400.0;
+    quality * 0.5_f64.powf(age_days / HALF_LIFE_DAYS)
+}
+
+/// Directories containing a file, outermost first, at most `MAX_DIR_DEPTH`
+pub fn directories(path: &str) -> Vec<String> {
+    let parts: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+    let dirs = parts.len().saturating_sub(1).min(MAX_DIR_DEPTH);
+    (1..=dirs).map(|depth| parts[..depth].join("/")).collect()
+}
+
+/// Lowercased extension of a file, None for files without one (or dotfiles)
+pub fn extension(path: &str) -> Option<String> {
+    let file = path.rsplit('/').next().unwrap_or(path);
+    let (stem, ext) = file.rsplit_once('.')?;
+    (!stem.is_empty() && !ext.is_empty()).then(|| ext.to_lowercase())
+}
+
+/// Language of a file, from its extension or well-known file name
+pub fn language(path: &str) -> Option<&'static str> {
+    let file = path.rsplit('/').next().unwrap_or(path);
+    match file {
+        "Dockerfile" => return Some("Docker"),
+        "Makefile" => return Some("Make"),
+        _ => {}
+    }
+    let language = match extension(path)?.as_str() {
+        "rs" => "Rust",
+        "py" => "Python",
+        "go" => "Go",
+        "ts" | "tsx" => "TypeScript",
+        "js" | "jsx" | "mjs" | "cjs" => "JavaScript",
+        "java" => "Java",
+        "kt" | "kts" => "Kotlin",
+        "scala" => "Scala",
+        "rb" => "Ruby",
+        "php" => "PHP",
+        "cs" => "C#",
+        "c" | "h" => "C",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" => "C++",
+        "swift" => "Swift",
+        "ex" | "exs" => "Elixir",
+        "sh" | "bash" => "Shell",
+        "sql" => "SQL",
+        "html" => "HTML",
+        "css" | "scss" => "CSS",
+        "vue" => "Vue",
+        "tf" => "Terraform",
+        "proto" => "Protobuf",
+        "md" => "Markdown",
+        "yml" | "yaml" => "YAML",
+        "toml" => "TOML",
+        "json" => "JSON",
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Accumulated weight of one area
+#[derive(Debug, Clone)]
+struct Tally {
+    score: f64,
+    comments: i32,
+    last_reviewed_at: DateTime<Utc>,
+}
+
+impl Tally {
+    fn add(&mut self, weight: f64, at: DateTime<Utc>) {
+        self.score += weight;
+        self.comments += 1;
+        self.last_reviewed_at = self.last_reviewed_at.max(at);
+    }
+}
+
+/// Per kind of area, tallies by (area, user)
+#[derive(Debug, Default)]
+struct Tallies {
+    directories: HashMap<(String, Uuid), Tally>,
+    extensions: HashMap<(String, Uuid), Tally>,
+    languages: HashMap<(String, Uuid), Tally>,
+}
+
+fn tally(comments: &[PathComment], now: DateTime<Utc>) -> Tallies {
+    let mut tallies = Tallies::default();
+    for c in comments {
+        let weight = comment_weight(c.quality_score, c.created_at, now);
+        let add = |map: &mut HashMap<(String, Uuid), Tally>, area: String| {
+            map.entry((area, c.user_id))
+                .or_insert(Tally {
+                    score: 0.0,
+                    comments: 0,
+                    last_reviewed_at: c.created_at,
+                })
+                .add(weight, c.created_at);
+        };
+        for dir in directories(&c.path) {
+            add(&mut tallies.directories, dir);
+        }
+        if let Some(ext) = extension(&c.path) {
+            add(&mut tallies.extensions, ext);
+        }
+        if let Some(lang) = language(&c.path) {
+            add(&mut tallies.languages, lang.to_string());
+        }
+    }
+    tallies
+}
+
+/// Best first, ties by name
+fn sort_areas(areas: &mut [ExpertiseArea]) {
+    areas.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+}
+
+fn user_areas(map: HashMap<(String, Uuid), Tally>, limit: usize) -> Vec<ExpertiseArea> {
+    let mut areas: Vec<ExpertiseArea> = map
+        .into_iter()
+        .map(|((name, _), t)| ExpertiseArea {
+            name,
+            score: t.score,
+            comments: t.comments,
+            last_reviewed_at: t.last_reviewed_at,
+        })
+        .collect();
+    sort_areas(&mut areas);
+    areas.truncate(limit);
+    areas
+}
+
+/// One user's expertise from their comments, top `limit` areas of each kind
+pub fn user_expertise(
+    user: User,
+    comments: &[PathComment],
+    now: DateTime<Utc>,
+    limit: usize,
+) -> UserExpertise {
+    let tallies = tally(comments, now);
+    UserExpertise {
+        user,
+        directories: user_areas(tallies.directories, limit),
+        extensions: user_areas(tallies.extensions, limit),
+        languages: user_areas(tallies.languages, limit),
+    }
+}
+
+fn area_experts(
+    map: HashMap<(String, Uuid), Tally>,
+    logins: &HashMap<Uuid, String>,
+    areas: usize,
+    per_area: usize,
+) -> Vec<AreaExperts> {
+    let mut by_area: HashMap<String, Vec<AreaExpert>> = HashMap::new();
+    for ((area, user_id), t) in map {
+        let Some(login) = logins.get(&user_id) else {
+            continue;
+        };
+        by_area.entry(area).or_default().push(AreaExpert {
+            login: login.clone(),
+            score: t.score,
+            comments: t.comments,
+        });
+    }
+
+    let mut result: Vec<AreaExperts> = by_area
+        .into_iter()
+        .map(|(name, mut experts)| {
+            experts.sort_by(|a, b| {
+                b.score
+                    .total_cmp(&a.score)
+                    .then_with(|| a.login.cmp(&b.login))
+            });
+            let score = experts.iter().map(|e| e.score).sum();
+            experts.truncate(per_area);
+            AreaExperts {
+                name,
+                score,
+                experts,
+            }
+        })
+        .collect();
+    result.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    result.truncate(areas);
+    result
+}
+
+/// Top `per_area` reviewers of the `areas` most reviewed areas of each kind
+pub fn repo_experts(
+    comments: &[PathComment],
+    logins: &HashMap<Uuid, String>,
+    now: DateTime<Utc>,
+    areas: usize,
+    per_area: usize,
+) -> RepoExperts {
+    let tallies = tally(comments, now);
+    RepoExperts {
+        directories: area_experts(tallies.directories, logins, areas, per_area),
+        extensions: area_experts(tallies.extensions, logins, areas, per_area),
+        languages: area_experts(tallies.languages, logins, areas, per_area),
+    }
+}
+
+/// Every user's area scores, for scoring files against a user
+#[derive(Debug, Default)]
+pub struct ExpertiseIndex {
+    directories: HashMap<(String, Uuid), f64>,
+    extensions: HashMap<(String, Uuid), f64>,
+    languages: HashMap<(String, Uuid), f64>,
+}
+
+impl ExpertiseIndex {
+    pub fn new(comments: &[PathComment], now: DateTime<Utc>) -> Self {
+        let scores = |map: HashMap<(String, Uuid), Tally>| {
+            map.into_iter().map(|(key, t)| (key, t.score)).collect()
+        };
+        let tallies = tally(comments, now);
+        Self {
+            directories: scores(tallies.directories),
+            extensions: scores(tallies.extensions),
+            languages: scores(tallies.languages),
+        }
+    }
+
+    /// How well a user knows a file: the mean score of its directories plus
+    /// the score of its language (of its extension if the language is unknown)
+    pub fn file_score(&self, user_id: Uuid, path: &str) -> f64 {
+        let get = |map: &HashMap<(String, Uuid), f64>, area: String| {
+            map.get(&(area, user_id)).copied().unwrap_or(0.0)
+        };
+
+        let dirs = directories(path);
+        let dir_count = dirs.len();
+        let dir_score = if dir_count == 0 {
+            0.0
+        } else {
+            let total: f64 = dirs.into_iter().map(|d| get(&self.directories, d)).sum();
+            total / dir_count as f64
+        };
+        let kind_score = match (language(path), extension(path)) {
+            (Some(lang), _) => get(&self.languages, lang.to_string()),
+            (None, Some(ext)) => get(&self.extensions, ext),
+            (None, None) => 0.0,
+        };
+        dir_score + kind_score
+    }
+
+    /// Mean file score over a set of files, 0 if there are none
+    pub fn files_score(&self, user_id: Uuid, paths: &[String]) -> f64 {
+        if paths.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = paths.iter().map(|p| self.file_score(user_id, p)).sum();
+        total / paths.len() as f64
+    }
+}
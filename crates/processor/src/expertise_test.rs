@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests {
+    use crate::expertise::*;
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use common::models::User;
+    use db::expertise::PathComment;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 6, 1, 12, 0, 0).unwrap()
+    }
+
+    fn comment(user_id: Uuid, path: &str, quality: Option<i32>, days_ago: i64) -> PathComment {
+        PathComment {
+            user_id,
+            path: path.to_string(),
+            quality_score: quality,
+            created_at: now() - Duration::days(days_ago),
+        }
+    }
+
+    fn make_user(id: Uuid) -> User {
+        User {
+            id,
+            github_id: 1,
+            login: "alice".to_string(),
+            avatar_url: None,
+            xp: 0,
+            level: 1,
+            created_at: now(),
+            updated_at: now(),
+        }
+    }
+
+    #[test]
+    fn test_directories_capped_at_max_depth() {
+        assert_eq!(
+            directories("crates/api/src/routes/users.rs"),
+            vec!["crates", "crates/api", "crates/api/src"]
+        );
+        assert_eq!(directories("src/main.rs"), vec!["src"]);
+        assert!(directories("README.md").is_empty());
+    }
+
+    #[test]
+    fn test_extension_and_language() {
+        assert_eq!(extension("web/App.TSX"), Some("tsx".to_string()));
+        assert_eq!(extension(".github/.gitignore"), None);
+        assert_eq!(extension("Makefile"), None);
+
+        assert_eq!(language("crates/db/src/lib.rs"), Some("Rust"));
+        assert_eq!(language("docker/Dockerfile"), Some("Docker"));
+        assert_eq!(language("assets/logo.png"), None);
+    }
+
+    #[test]
+    fn test_comment_weight_quality_and_recency() {
+        assert_eq!(comment_weight(None, now(), now()), 1.0);
+        assert_eq!(comment_weight(Some(10), now(), now()), 2.0);
+        assert_eq!(
+            comment_weight(Some(5), now() - Duration::days(90), now()),
+            0.5
+        );
+    }
+
+    #[test]
+    fn test_user_expertise_ranks_areas() {
+        let id = Uuid::new_v4();
+        let comments = vec![
+            comment(id, "crates/db/src/users.rs", Some(8), 0),
+            comment(id, "crates/db/src/prs.rs", None, 0),
+            comment(id, "web/src/app.ts", None, 180),
+        ];
+
+        let expertise = user_expertise(make_user(id), &comments, now(), 10);
+
+        let names: Vec<&str> = expertise
+            .directories
+            .iter()
+            .map(|a| a.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["crates", "crates/db", "crates/db/src", "web", "web/src"]
+        );
+        assert_eq!(expertise.directories[0].comments, 2);
+        assert!((expertise.directories[0].score - 2.6).abs() < 1e-9);
+        assert_eq!(expertise.languages[0].name, "Rust");
+        assert_eq!(expertise.languages[1].name, "TypeScript");
+        assert!((expertise.languages[1].score - 0.25).abs() < 1e-9);
+
+        let top_one = user_expertise(make_user(id), &comments, now(), 1);
+        assert_eq!(top_one.extensions.len(), 1);
+    }
+
+    #[test]
+    fn test_repo_experts_per_area() {
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let logins = HashMap::from([(alice, "alice".to_string()), (bob, "bob".to_string())]);
+        let comments = vec![
+            comment(alice, "api/routes.rs", None, 0),
+            comment(alice, "api/routes.rs", None, 0),
+            comment(bob, "api/state.rs", None, 0),
+            comment(bob, "migrations/schema.sql", None, 0),
+        ];
+
+        let experts = repo_experts(&comments, &logins, now(), 10, 1);
+
+        assert_eq!(experts.directories[0].name, "api");
+        assert_eq!(experts.directories[0].score, 3.0);
+        assert_eq!(experts.directories[0].experts.len(), 1);
+        assert_eq!(experts.directories[0].experts[0].login, "alice");
+        assert_eq!(experts.languages[1].name, "SQL");
+        assert_eq!(experts.languages[1].experts[0].login, "bob");
+    }
+}
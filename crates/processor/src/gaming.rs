@@ -0,0 +1,398 @@
+//! Anti-gaming analysis
+//!
+//! Looks for patterns that farm XP rather than review code and stores them as
+//! flags for an admin to dismiss or penalize (see `db::gaming`):
+//! - mutual approvals: a group of users (a pair or a larger ring) who mostly
+//!   approve each other's PRs
+//! - duplicate comments: the same comment, up to case, punctuation and
+//!   numbers, posted many times across PRs
+//! - fast approvals: approving without comments within minutes of the PR
+//!   being opened or pushed (one flag per reviewer and week)
+//! - author favoritism: a reviewer whose approvals go to one author far more
+//!   than that author's share of everyone's approvals
+//!
+//! Detection is deterministic, so re-running it only adds new findings. After
+//! a sync only users with new comments or reviews are re-checked for duplicate
+//! comments and fast approvals; approval counts are aggregated in SQL and
+//! always cover everyone.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use db::gaming::{ApprovalCount, CommentBody, FastApproval, Finding, FlagKind};
+use serde::Serialize;
+use sqlx::PgPool;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::analytics::week_start;
+
+#[cfg(test)]
+#[path = "gaming_test.rs"]
+mod gaming_test;
+
+/// Approvals each way linking two users into a possible group
+pub const MUTUAL_MIN_LINK: i64 = 2;
+/// Approvals each member gives inside the group
+pub const MUTUAL_MIN_APPROVALS: i64 = 5;
+/// Share of each member's approvals going to the rest of the group
+pub const MUTUAL_MIN_SHARE: f64 = 0.5;
+
+/// Copies of a comment before it counts as spam
+pub const DUPLICATE_MIN_COUNT: usize = 5;
+/// ...spread over at least this many PRs
+pub const DUPLICATE_MIN_PRS: usize = 3;
+/// Normalized comments shorter than this are ignored ("lgtm", "nit")
+pub const DUPLICATE_MIN_CHARS: usize = 10;
+
+/// Approvals sooner than this after opening or a push are too fast to be a read
+pub const FAST_APPROVAL_SECS: i64 = 120;
+
+/// Approvals of one author before favoritism is considered
+pub const FAVORITISM_MIN_APPROVALS: i64 = 10;
+/// Share of the reviewer's approvals going to the author
+pub const FAVORITISM_MIN_SHARE: f64 = 0.5;
+/// ...compared to the author's share of all approvals
+pub const FAVORITISM_BASELINE_FACTOR: f64 = 3.0;
+
+/// Outcome of an analysis run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GamingReport {
+    pub mutual_approvals: usize,
+    pub duplicate_comments: usize,
+    pub fast_approvals: usize,
+    pub author_favoritism: usize,
+    /// Findings not flagged before
+    pub new_flags: u64,
+}
+
+/// Run every detector over all activity and store the findings as flags
+pub async fn analyze(pool: &PgPool) -> Result<GamingReport, sqlx::Error> {
+    let run_at = db::recalc::now(pool).await?;
+    let report = analyze_since(pool, None).await?;
+    db::gaming::set_last_run(pool, run_at).await?;
+    Ok(report)
+}
+
+/// Run every detector, re-checking duplicate comments and fast approvals only
+/// for users active since the last run (a full run if there was none)
+pub async fn analyze_incremental(pool: &PgPool) -> Result<GamingReport, sqlx::Error> {
+    let Some(since) = db::gaming::get_last_run(pool).await? else {
+        return analyze(pool).await;
+    };
+    let run_at = db::recalc::now(pool).await?;
+    let report = analyze_since(pool, Some(since)).await?;
+    db::gaming::set_last_run(pool, run_at).await?;
+    Ok(report)
+}
+
+async fn analyze_since(
+    pool: &PgPool,
+    since: Option<DateTime<Utc>>,
+) -> Result<GamingReport, sqlx::Error> {
+    let approvals = db::gaming::approval_counts(pool).await?;
+    let comments = db::gaming::comment_bodies(pool, since).await?;
+    let fast = db::gaming::fast_approvals(pool, FAST_APPROVAL_SECS, since).await?;
+
+    let mutual = find_mutual_approvals(&approvals);
+    let duplicates = find_duplicate_comments(&comments);
+    let fast = find_fast_approvals(&fast);
+    let favoritism = find_author_favoritism(&approvals);
+
+    let mut report = GamingReport {
+        mutual_approvals: mutual.len(),
+        duplicate_comments: duplicates.len(),
+        fast_approvals: fast.len(),
+        author_favoritism: favoritism.len(),
+        new_flags: 0,
+    };
+
+    let findings: Vec<Finding> = [mutual, duplicates, fast, favoritism].concat();
+    report.new_flags = db::gaming::upsert_flags(pool, &findings).await?;
+
+    info!(
+        "Gaming analysis: {} findings, {} new flags",
+        findings.len(),
+        report.new_flags
+    );
+    Ok(report)
+}
+
+/// Total approvals given by each reviewer
+fn approvals_given(approvals: &[ApprovalCount]) -> HashMap<Uuid, i64> {
+    let mut given = HashMap::new();
+    for a in approvals {
+        *given.entry(a.reviewer_id).or_insert(0) += a.approvals;
+    }
+    given
+}
+
+fn share(part: i64, total: i64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+/// Groups of users who approve each other often, and mostly each other.
+///
+/// Users are linked when each has approved the other at least
+/// `MUTUAL_MIN_LINK` times. Each connected group is then shrunk until every
+/// member gives at least `MUTUAL_MIN_APPROVALS` approvals, and
+/// `MUTUAL_MIN_SHARE` of all their approvals, to the rest of the group, so a
+/// ring that spreads approvals evenly is found as well as a pair.
+pub fn find_mutual_approvals(approvals: &[ApprovalCount]) -> Vec<Finding> {
+    let given = approvals_given(approvals);
+    let by_pair: HashMap<(Uuid, Uuid), &ApprovalCount> = approvals
+        .iter()
+        .map(|a| ((a.reviewer_id, a.author_id), a))
+        .collect();
+
+    let mut links: BTreeMap<Uuid, BTreeSet<Uuid>> = BTreeMap::new();
+    for a in approvals.iter().filter(|a| a.approvals >= MUTUAL_MIN_LINK) {
+        let back = by_pair.get(&(a.author_id, a.reviewer_id));
+        if back.is_some_and(|b| b.approvals >= MUTUAL_MIN_LINK) {
+            links.entry(a.reviewer_id).or_default().insert(a.author_id);
+        }
+    }
+
+    // Approvals `user` gives to the rest of `group`
+    let inside = |user: Uuid, group: &BTreeSet<Uuid>| -> i64 {
+        group
+            .iter()
+            .filter_map(|other| by_pair.get(&(user, *other)))
+            .map(|a| a.approvals)
+            .sum()
+    };
+
+    let mut groups = Vec::new();
+    let mut pending = connected_groups(&links, &links.keys().copied().collect());
+    while let Some(group) = pending.pop() {
+        let keep: BTreeSet<Uuid> = group
+            .iter()
+            .copied()
+            .filter(|user| {
+                let count = inside(*user, &group);
+                count >= MUTUAL_MIN_APPROVALS && share(count, given[user]) >= MUTUAL_MIN_SHARE
+            })
+            .collect();
+        if keep.len() == group.len() {
+            groups.push(group);
+        } else if keep.len() >= 2 {
+            // Dropping members can split the rest
+            pending.extend(connected_groups(&links, &keep));
+        }
+    }
+
+    let mut findings: Vec<Finding> = groups
+        .into_iter()
+        .map(|group| {
+            let members: Vec<Uuid> = group.iter().copied().collect();
+            let stats: Vec<String> = members
+                .iter()
+                .map(|user| {
+                    let count = inside(*user, &group);
+                    format!("{} ({:.0}%)", count, share(count, given[user]) * 100.0)
+                })
+                .collect();
+            let pairs = members
+                .iter()
+                .flat_map(|user| {
+                    members
+                        .iter()
+                        .filter_map(|other| by_pair.get(&(*user, *other)))
+                        .flat_map(|a| a.pr_ids.iter().map(|pr| (*pr, a.reviewer_id)))
+                })
+                .collect();
+            let ids: Vec<String> = members.iter().map(Uuid::to_string).collect();
+            Finding {
+                kind: FlagKind::MutualApprovals,
+                fingerprint: format!("{}:{}", FlagKind::MutualApprovals.as_str(), ids.join(":")),
+                user_id: members[0],
+                other_user_id: members.get(1).copied(),
+                details: format!(
+                    "{} users approving each other: {} approvals inside the group",
+                    members.len(),
+                    stats.join(", ")
+                ),
+                pairs,
+            }
+        })
+        .collect();
+    findings.sort_by(|a, b| a.fingerprint.cmp(&b.fingerprint));
+    findings
+}
+
+/// Connected groups (of 2 or more) among `users`, following `links` between
+/// them only
+fn connected_groups(
+    links: &BTreeMap<Uuid, BTreeSet<Uuid>>,
+    users: &BTreeSet<Uuid>,
+) -> Vec<BTreeSet<Uuid>> {
+    let mut seen: HashSet<Uuid> = HashSet::new();
+    let mut groups = Vec::new();
+    for start in users {
+        if !seen.insert(*start) {
+            continue;
+        }
+        let mut group = BTreeSet::from([*start]);
+        let mut stack = vec![*start];
+        while let Some(user) = stack.pop() {
+            for next in links.get(&user).into_iter().flatten() {
+                if users.contains(next) && seen.insert(*next) {
+                    group.insert(*next);
+                    stack.push(*next);
+                }
+            }
+        }
+        if group.len() >= 2 {
+            groups.push(group);
+        }
+    }
+    groups
+}
+
+/// Lowercase letters only, words separated by single spaces, so comments
+/// differing in case, punctuation, numbers or spacing compare equal
+pub fn normalize_comment(body: &str) -> String {
+    body.to_lowercase()
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Comments one user posted many times across several PRs
+pub fn find_duplicate_comments(comments: &[CommentBody]) -> Vec<Finding> {
+    let mut groups: HashMap<(Uuid, String), (usize, BTreeSet<Uuid>)> = HashMap::new();
+    for c in comments {
+        let normalized = normalize_comment(&c.body);
+        if normalized.chars().count() < DUPLICATE_MIN_CHARS {
+            continue;
+        }
+        let group = groups.entry((c.user_id, normalized)).or_default();
+        group.0 += 1;
+        group.1.insert(c.pr_id);
+    }
+
+    let mut findings: Vec<Finding> = groups
+        .into_iter()
+        .filter(|(_, (count, prs))| *count >= DUPLICATE_MIN_COUNT && prs.len() >= DUPLICATE_MIN_PRS)
+        .map(|((user_id, normalized), (count, prs))| {
+            let excerpt: String = normalized.chars().take(60).collect();
+            Finding {
+                kind: FlagKind::DuplicateComments,
+                fingerprint: format!(
+                    "{}:{}:{}",
+                    FlagKind::DuplicateComments.as_str(),
+                    user_id,
+                    normalized
+                ),
+                user_id,
+                other_user_id: None,
+                details: format!(
+                    "\"{}\" posted {} times on {} PRs",
+                    excerpt,
+                    count,
+                    prs.len()
+                ),
+                pairs: prs.into_iter().map(|pr| (pr, user_id)).collect(),
+            }
+        })
+        .collect();
+    findings.sort_by(|a, b| a.fingerprint.cmp(&b.fingerprint));
+    findings
+}
+
+/// One finding per reviewer and week with approvals given too fast
+pub fn find_fast_approvals(approvals: &[FastApproval]) -> Vec<Finding> {
+    let mut weeks: BTreeMap<(Uuid, NaiveDate), Vec<&FastApproval>> = BTreeMap::new();
+    for a in approvals.iter().filter(|a| a.seconds < FAST_APPROVAL_SECS) {
+        weeks
+            .entry((a.reviewer_id, week_start(a.submitted_at)))
+            .or_default()
+            .push(a);
+    }
+
+    weeks
+        .into_iter()
+        .map(|((reviewer_id, week), fast)| {
+            let authors: BTreeSet<Uuid> = fast.iter().map(|a| a.author_id).collect();
+            let fastest = fast.iter().map(|a| a.seconds).min().unwrap_or_default();
+            let mut pairs: Vec<(Uuid, Uuid)> = fast.iter().map(|a| (a.pr_id, reviewer_id)).collect();
+            pairs.sort();
+            pairs.dedup();
+            Finding {
+                kind: FlagKind::FastApproval,
+                fingerprint: format!(
+                    "{}:{}:{}",
+                    FlagKind::FastApproval.as_str(),
+                    reviewer_id,
+                    week
+                ),
+                user_id: reviewer_id,
+                // The author, when every approval went to the same one
+                other_user_id: (authors.len() == 1).then(|| fast[0].author_id),
+                details: format!(
+                    "{} approvals without comments in the week of {}, fastest {}s after the PR was opened or pushed",
+                    fast.len(),
+                    week,
+                    fastest
+                ),
+                pairs,
+            }
+        })
+        .collect()
+}
+
+/// Reviewers whose approvals concentrate on one author well beyond how
+/// much that author is approved overall
+pub fn find_author_favoritism(approvals: &[ApprovalCount]) -> Vec<Finding> {
+    let given = approvals_given(approvals);
+    let total: i64 = given.values().sum();
+    let mut received: HashMap<Uuid, i64> = HashMap::new();
+    for a in approvals {
+        *received.entry(a.author_id).or_insert(0) += a.approvals;
+    }
+
+    let mut findings: Vec<Finding> = approvals
+        .iter()
+        .filter_map(|a| {
+            if a.approvals < FAVORITISM_MIN_APPROVALS {
+                return None;
+            }
+            let reviewer_share = share(a.approvals, given[&a.reviewer_id]);
+            // The author's share of everyone else's approvals
+            let baseline = share(
+                received[&a.author_id] - a.approvals,
+                total - given[&a.reviewer_id],
+            );
+            if reviewer_share < FAVORITISM_MIN_SHARE
+                || reviewer_share < baseline * FAVORITISM_BASELINE_FACTOR
+            {
+                return None;
+            }
+
+            Some(Finding {
+                kind: FlagKind::AuthorFavoritism,
+                fingerprint: format!(
+                    "{}:{}:{}",
+                    FlagKind::AuthorFavoritism.as_str(),
+                    a.reviewer_id,
+                    a.author_id
+                ),
+                user_id: a.reviewer_id,
+                other_user_id: Some(a.author_id),
+                details: format!(
+                    "{} approvals, {:.0}% of the reviewer's approvals (baseline {:.0}%)",
+                    a.approvals,
+                    reviewer_share * 100.0,
+                    baseline * 100.0
+                ),
+                pairs: a.pr_ids.iter().map(|pr| (*pr, a.reviewer_id)).collect(),
+            })
+        })
+        .collect();
+    findings.sort_by(|a, b| a.fingerprint.cmp(&b.fingerprint));
+    findings
+}
@@ -0,0 +1,180 @@
+#[cfg(test)]
+mod tests {
+    use crate::gaming::*;
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use db::gaming::{ApprovalCount, CommentBody, FastApproval, FlagKind};
+    use uuid::Uuid;
+
+    fn user(n: u128) -> Uuid {
+        Uuid::from_u128(n)
+    }
+
+    fn approvals(reviewer: Uuid, author: Uuid, count: i64) -> ApprovalCount {
+        ApprovalCount {
+            reviewer_id: reviewer,
+            author_id: author,
+            approvals: count,
+            pr_ids: (0..count).map(|_| Uuid::new_v4()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_mutual_approvals_flags_exclusive_pair() {
+        let (a, b, c) = (user(1), user(2), user(3));
+        let counts = vec![
+            approvals(a, b, 6),
+            approvals(b, a, 5),
+            approvals(a, c, 1),
+            approvals(c, a, 2),
+        ];
+
+        let findings = find_mutual_approvals(&counts);
+        assert_eq!(findings.len(), 1);
+        let f = &findings[0];
+        assert_eq!(f.kind, FlagKind::MutualApprovals);
+        assert_eq!((f.user_id, f.other_user_id), (a, Some(b)));
+        assert_eq!(f.pairs.len(), 11);
+    }
+
+    #[test]
+    fn test_mutual_approvals_ignores_broad_reviewers() {
+        let (a, b, c) = (user(1), user(2), user(3));
+        // a approves b often, but approves c even more
+        let counts = vec![approvals(a, b, 6), approvals(b, a, 6), approvals(a, c, 10)];
+
+        assert!(find_mutual_approvals(&counts).is_empty());
+    }
+
+    #[test]
+    fn test_mutual_approvals_flags_ring() {
+        let (a, b, c, d) = (user(1), user(2), user(3), user(4));
+        // A ring of three spreading approvals evenly: no pair is exclusive
+        let mut counts = vec![
+            approvals(a, b, 3),
+            approvals(a, c, 3),
+            approvals(b, a, 3),
+            approvals(b, c, 3),
+            approvals(c, a, 3),
+            approvals(c, b, 3),
+        ];
+        // d trades a few approvals with a but mostly reviews elsewhere
+        counts.push(approvals(d, a, 2));
+        counts.push(approvals(a, d, 2));
+        counts.push(approvals(d, user(5), 10));
+
+        let findings = find_mutual_approvals(&counts);
+        assert_eq!(findings.len(), 1);
+        let f = &findings[0];
+        assert_eq!(f.user_id, a);
+        assert!(f.details.starts_with("3 users"));
+        // Approvals between a, b and c only
+        assert_eq!(f.pairs.len(), 18);
+        assert!(f.pairs.iter().all(|(_, u)| *u != d));
+    }
+
+    #[test]
+    fn test_normalize_comment() {
+        assert_eq!(
+            normalize_comment("  Please add a TEST!! (see #123)\n"),
+            "please add a test see"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_comments_need_several_prs() {
+        let reviewer = user(1);
+        let prs: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        let comment = |pr: Uuid, body: &str| CommentBody {
+            user_id: reviewer,
+            pr_id: pr,
+            body: body.to_string(),
+        };
+
+        // Five copies on two PRs is not enough
+        let mut comments: Vec<CommentBody> = (0..5)
+            .map(|i| comment(prs[i % 2], "Consider renaming this variable."))
+            .collect();
+        assert!(find_duplicate_comments(&comments).is_empty());
+
+        comments.push(comment(prs[2], "consider renaming this variable"));
+        let findings = find_duplicate_comments(&comments);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].pairs.len(), 3);
+
+        // Short comments are never flagged
+        let short: Vec<CommentBody> = (0..6).map(|i| comment(prs[i % 3], "LGTM!")).collect();
+        assert!(find_duplicate_comments(&short).is_empty());
+    }
+
+    fn fast(reviewer: Uuid, author: Uuid, at: DateTime<Utc>, seconds: i64) -> FastApproval {
+        FastApproval {
+            review_id: Uuid::new_v4(),
+            pr_id: Uuid::new_v4(),
+            reviewer_id: reviewer,
+            author_id: author,
+            submitted_at: at,
+            seconds,
+        }
+    }
+
+    #[test]
+    fn test_fast_approvals() {
+        let monday = Utc.with_ymd_and_hms(2026, 3, 2, 10, 0, 0).unwrap();
+        let findings = find_fast_approvals(&[
+            fast(user(1), user(2), monday, 30),
+            fast(user(1), user(2), monday, FAST_APPROVAL_SECS),
+        ]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].other_user_id, Some(user(2)));
+        assert_eq!(findings[0].pairs.len(), 1);
+    }
+
+    #[test]
+    fn test_fast_approvals_one_flag_per_reviewer_and_week() {
+        let monday = Utc.with_ymd_and_hms(2026, 3, 2, 10, 0, 0).unwrap();
+        let (reviewer, other) = (user(1), user(9));
+        let approvals = vec![
+            fast(reviewer, user(2), monday, 30),
+            fast(reviewer, user(3), monday + Duration::days(4), 60),
+            fast(reviewer, user(2), monday + Duration::days(7), 10),
+            fast(other, user(2), monday, 5),
+        ];
+
+        let findings = find_fast_approvals(&approvals);
+        assert_eq!(findings.len(), 3);
+        let first_week = findings
+            .iter()
+            .find(|f| f.user_id == reviewer && f.fingerprint.ends_with("2026-03-02"))
+            .unwrap();
+        assert_eq!(first_week.pairs.len(), 2);
+        // Different authors: no single other user
+        assert_eq!(first_week.other_user_id, None);
+        assert!(first_week.details.starts_with("2 approvals"));
+    }
+
+    #[test]
+    fn test_author_favoritism_against_baseline() {
+        let (reviewer, favorite, other) = (user(1), user(2), user(3));
+        let mut counts: Vec<ApprovalCount> = (10..20)
+            .flat_map(|n| {
+                [
+                    approvals(user(n), favorite, 1),
+                    approvals(user(n), other, 4),
+                ]
+            })
+            .collect();
+
+        counts.push(approvals(reviewer, favorite, 12));
+        counts.push(approvals(reviewer, other, 2));
+        let findings = find_author_favoritism(&counts);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].user_id, reviewer);
+        assert_eq!(findings[0].other_user_id, Some(favorite));
+
+        // An author everyone approves is not favoritism
+        let mut popular: Vec<ApprovalCount> =
+            (10..20).map(|n| approvals(user(n), favorite, 5)).collect();
+        popular.push(approvals(reviewer, favorite, 12));
+        assert!(find_author_favoritism(&popular).is_empty());
+    }
+}
@@ -0,0 +1,30 @@
+//! Patch hunk parsing, for matching review comments to the commits that
+//! addressed them
+
+use db::commits::Hunk;
+
+#[cfg(test)]
+#[path = "hunks_test.rs"]
+mod hunks_test;
+
+/// Parse the hunk headers (`@@ -start,lines +start,lines @@`) of a unified
+/// diff for one file. Malformed headers are skipped.
+pub fn parse_patch(path: &str, patch: &str) -> Vec<Hunk> {
+    patch
+        .lines()
+        .filter_map(|line| line.strip_prefix("@@ -"))
+        .filter_map(|rest| {
+            let old = rest.split_whitespace().next()?;
+            let (start, lines) = match old.split_once(',') {
+                Some((start, lines)) => (start.parse().ok()?, lines.parse().ok()?),
+                // A missing count means one line
+                None => (old.parse().ok()?, 1),
+            };
+            Some(Hunk {
+                path: path.to_string(),
+                old_start: start,
+                old_lines: lines,
+            })
+        })
+        .collect()
+}
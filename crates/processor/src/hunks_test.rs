@@ -0,0 +1,40 @@
+#[cfg(test)]
+mod tests {
+    use crate::hunks::*;
+    use db::commits::Hunk;
+
+    fn hunk(old_start: i32, old_lines: i32) -> Hunk {
+        Hunk {
+            path: "src/lib.rs".to_string(),
+            old_start,
+            old_lines,
+        }
+    }
+
+    #[test]
+    fn test_parse_patch_multiple_hunks() {
+        let patch = "@@ -10,7 +10,8 @@ fn main() {\n context\n-old\n+new\n+added\n@@ -40,3 +41,3 @@\n-a\n+b";
+        assert_eq!(
+            parse_patch("src/lib.rs", patch),
+            vec![hunk(10, 7), hunk(40, 3)]
+        );
+    }
+
+    #[test]
+    fn test_parse_patch_single_line_and_new_file() {
+        assert_eq!(
+            parse_patch("src/lib.rs", "@@ -5 +5 @@\n-x\n+y"),
+            vec![hunk(5, 1)]
+        );
+        assert_eq!(
+            parse_patch("src/lib.rs", "@@ -0,0 +1,3 @@\n+a\n+b\n+c"),
+            vec![hunk(0, 0)]
+        );
+    }
+
+    #[test]
+    fn test_parse_patch_ignores_content_and_malformed_headers() {
+        let patch = "@@ -x,3 +1,3 @@\n-@@ -1,2 +1,2 @@\n+fine";
+        assert!(parse_patch("src/lib.rs", patch).is_empty());
+    }
+}
@@ -0,0 +1,212 @@
+//! Review latency in working time
+//!
+//! Every latency (fast review bonus, time to first review, open PR ages) is
+//! measured here, from the moment a PR became ready for review: when it was
+//! opened, or the last push before the review. Elapsed time only counts the
+//! working hours of a calendar, so nights, weekends and holidays don't count
+//! against anyone. Users without a team calendar (see `db::calendars`) are
+//! measured around the clock.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use common::models::WorkCalendar;
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use tracing::warn;
+use uuid::Uuid;
+
+#[cfg(test)]
+#[path = "latency_test.rs"]
+mod latency_test;
+
+/// Working hours to measure latency against
+#[derive(Debug, Clone)]
+pub struct Calendar {
+    tz: Tz,
+    /// Indexed by days from Monday
+    work_days: [bool; 7],
+    start_hour: i64,
+    end_hour: i64,
+    holidays: HashSet<NaiveDate>,
+    /// Every hour of every day counts
+    always: bool,
+}
+
+impl Default for Calendar {
+    /// Around the clock: working time equals wall-clock time
+    fn default() -> Self {
+        Self {
+            tz: Tz::UTC,
+            work_days: [true; 7],
+            start_hour: 0,
+            end_hour: 24,
+            holidays: HashSet::new(),
+            always: true,
+        }
+    }
+}
+
+impl Calendar {
+    /// Compile a stored calendar, checking its values and timezone
+    pub fn new(calendar: &WorkCalendar) -> Result<Self, String> {
+        calendar.validate()?;
+        let tz: Tz = calendar
+            .timezone
+            .parse()
+            .map_err(|_| format!("Unknown timezone '{}'", calendar.timezone))?;
+
+        let mut work_days = [false; 7];
+        for day in &calendar.work_days {
+            work_days[(*day - 1) as usize] = true;
+        }
+        let holidays: HashSet<NaiveDate> = calendar.holidays.iter().copied().collect();
+        let always = work_days.iter().all(|d| *d)
+            && calendar.day_start_hour == 0
+            && calendar.day_end_hour == 24
+            && holidays.is_empty();
+
+        Ok(Self {
+            tz,
+            work_days,
+            start_hour: calendar.day_start_hour as i64,
+            end_hour: calendar.day_end_hour as i64,
+            holidays,
+            always,
+        })
+    }
+
+    /// Working time between `from` and `to`, zero if `to` is not after `from`
+    pub fn working_time(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Duration {
+        if to <= from {
+            return Duration::zero();
+        }
+        if self.always {
+            return to - from;
+        }
+
+        let mut total = Duration::zero();
+        let mut day = from.with_timezone(&self.tz).date_naive();
+        let last = to.with_timezone(&self.tz).date_naive();
+        while day <= last {
+            if self.is_work_day(day) {
+                let midnight = day.and_hms_opt(0, 0, 0).expect("midnight is valid");
+                let start = self.to_utc(midnight + Duration::hours(self.start_hour));
+                let end = self.to_utc(midnight + Duration::hours(self.end_hour));
+                let overlap = end.min(to) - start.max(from);
+                if overlap > Duration::zero() {
+                    total += overlap;
+                }
+            }
+            day = match day.succ_opt() {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        total
+    }
+
+    fn is_work_day(&self, day: NaiveDate) -> bool {
+        self.work_days[day.weekday().num_days_from_monday() as usize]
+            && !self.holidays.contains(&day)
+    }
+
+    /// A local time as UTC. Times skipped by a DST jump move to the first
+    /// valid time after them.
+    fn to_utc(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        (0..=2)
+            .find_map(|h| {
+                self.tz
+                    .from_local_datetime(&(local + Duration::hours(h)))
+                    .earliest()
+            })
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&local))
+    }
+}
+
+/// Calendars of every user, around the clock for users without one
+#[derive(Debug, Clone, Default)]
+pub struct Calendars {
+    by_user: HashMap<Uuid, Calendar>,
+    default: Calendar,
+}
+
+impl Calendars {
+    pub fn new(by_user: HashMap<Uuid, Calendar>) -> Self {
+        Self {
+            by_user,
+            default: Calendar::default(),
+        }
+    }
+
+    /// Load every user's team calendar. Invalid stored calendars are skipped.
+    pub async fn load(pool: &PgPool) -> Result<Self, sqlx::Error> {
+        let by_user = db::calendars::by_user(pool)
+            .await?
+            .into_iter()
+            .filter_map(|(user_id, calendar)| match Calendar::new(&calendar) {
+                Ok(calendar) => Some((user_id, calendar)),
+                Err(e) => {
+                    warn!("Ignoring calendar of user {}: {}", user_id, e);
+                    None
+                }
+            })
+            .collect();
+
+        Ok(Self::new(by_user))
+    }
+
+    pub fn for_user(&self, user_id: Uuid) -> &Calendar {
+        self.by_user.get(&user_id).unwrap_or(&self.default)
+    }
+}
+
+/// When a PR became ready for a review: the later of its opening and the last
+/// push before the review, either of which may be unknown
+pub fn ready_at(
+    opened_at: Option<DateTime<Utc>>,
+    last_push: Option<DateTime<Utc>>,
+) -> Option<DateTime<Utc>> {
+    opened_at.max(last_push)
+}
+
+/// Whether a review came within `limit` working time of the PR becoming ready
+pub fn is_fast(
+    calendar: &Calendar,
+    ready_at: DateTime<Utc>,
+    reviewed_at: DateTime<Utc>,
+    limit: Duration,
+) -> bool {
+    reviewed_at > ready_at && calendar.working_time(ready_at, reviewed_at) < limit
+}
+
+/// Average working seconds over (ready, reviewed) pairs, None if empty
+pub fn average_secs(calendar: &Calendar, waits: &[(DateTime<Utc>, DateTime<Utc>)]) -> Option<f64> {
+    if waits.is_empty() {
+        return None;
+    }
+    let total: i64 = waits
+        .iter()
+        .map(|(ready, reviewed)| calendar.working_time(*ready, *reviewed).num_seconds())
+        .sum();
+    Some(total as f64 / waits.len() as f64)
+}
+
+/// A user's average working time to first review the PRs they reviewed since
+/// `since`, optionally in one repo
+pub async fn avg_time_to_first_review_secs(
+    pool: &PgPool,
+    user_id: Uuid,
+    repo_id: Option<Uuid>,
+    since: DateTime<Utc>,
+) -> Result<Option<f64>, sqlx::Error> {
+    let calendar = match db::calendars::for_user(pool, user_id).await? {
+        Some(stored) => Calendar::new(&stored).unwrap_or_else(|e| {
+            warn!("Ignoring calendar of user {}: {}", user_id, e);
+            Calendar::default()
+        }),
+        None => Calendar::default(),
+    };
+    let waits = db::users::first_review_waits(pool, user_id, repo_id, since).await?;
+    Ok(average_secs(&calendar, &waits))
+}
@@ -0,0 +1,193 @@
+#[cfg(test)]
+mod tests {
+    use crate::latency::*;
+    use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+    use common::models::WorkCalendar;
+
+    fn utc(day: u32, hour: u32, min: u32) -> DateTime<Utc> {
+        // January 2026: the 5th is a Monday
+        Utc.with_ymd_and_hms(2026, 1, day, hour, min, 0).unwrap()
+    }
+
+    /// Mon-Fri 9-17 in the given timezone
+    fn office(timezone: &str) -> Calendar {
+        Calendar::new(&WorkCalendar {
+            timezone: timezone.to_string(),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    // is_fast tests
+    #[test]
+    fn test_is_fast_under_1_hour() {
+        assert!(is_fast(
+            &Calendar::default(),
+            utc(1, 10, 0),
+            utc(1, 10, 30),
+            Duration::hours(1)
+        ));
+    }
+
+    #[test]
+    fn test_is_fast_exactly_1_hour_not_fast() {
+        assert!(!is_fast(
+            &Calendar::default(),
+            utc(1, 10, 0),
+            utc(1, 11, 0),
+            Duration::hours(1)
+        ));
+    }
+
+    #[test]
+    fn test_is_fast_over_1_hour() {
+        assert!(!is_fast(
+            &Calendar::default(),
+            utc(1, 10, 0),
+            utc(1, 12, 0),
+            Duration::hours(1)
+        ));
+    }
+
+    #[test]
+    fn test_is_fast_59_minutes() {
+        assert!(is_fast(
+            &Calendar::default(),
+            utc(1, 10, 0),
+            utc(1, 10, 59),
+            Duration::hours(1)
+        ));
+    }
+
+    #[test]
+    fn test_is_fast_not_before_ready() {
+        assert!(!is_fast(
+            &Calendar::default(),
+            utc(1, 10, 0),
+            utc(1, 10, 0),
+            Duration::hours(1)
+        ));
+    }
+
+    #[test]
+    fn test_is_fast_overnight_push() {
+        // Pushed at 23:00 in Tokyo, reviewed at 09:30 the next morning there
+        let tokyo = office("Asia/Tokyo");
+        let pushed = utc(5, 14, 0);
+        let reviewed = utc(6, 0, 30);
+
+        assert!(!is_fast(
+            &Calendar::default(),
+            pushed,
+            reviewed,
+            Duration::hours(1)
+        ));
+        assert!(is_fast(&tokyo, pushed, reviewed, Duration::hours(1)));
+    }
+
+    // Calendar tests
+    #[test]
+    fn test_working_time_skips_weekend() {
+        // Friday 15:00 to Monday 11:00: 2h Friday + 2h Monday
+        assert_eq!(
+            office("UTC").working_time(utc(9, 15, 0), utc(12, 11, 0)),
+            Duration::hours(4)
+        );
+    }
+
+    #[test]
+    fn test_working_time_respects_timezone() {
+        // 09:00-17:00 in New York is 14:00-22:00 UTC in January
+        let new_york = office("America/New_York");
+        assert_eq!(
+            new_york.working_time(utc(5, 12, 0), utc(5, 16, 0)),
+            Duration::hours(2)
+        );
+    }
+
+    #[test]
+    fn test_working_time_skips_holidays() {
+        let calendar = Calendar::new(&WorkCalendar {
+            holidays: vec![NaiveDate::from_ymd_opt(2026, 1, 6).unwrap()],
+            ..Default::default()
+        })
+        .unwrap();
+
+        // Monday 16:00 to Wednesday 10:00, Tuesday off
+        assert_eq!(
+            calendar.working_time(utc(5, 16, 0), utc(7, 10, 0)),
+            Duration::hours(2)
+        );
+    }
+
+    #[test]
+    fn test_working_time_across_dst_change() {
+        // Berlin moves to summer time on Sunday 2026-03-29; each of the six
+        // working days still has 8 hours
+        let berlin = office("Europe/Berlin");
+        let from = Utc.with_ymd_and_hms(2026, 3, 27, 0, 0, 0).unwrap();
+        let to = Utc.with_ymd_and_hms(2026, 4, 4, 0, 0, 0).unwrap();
+
+        assert_eq!(berlin.working_time(from, to), Duration::hours(48));
+    }
+
+    #[test]
+    fn test_always_calendar_is_wall_clock() {
+        let calendar = Calendar::new(&WorkCalendar {
+            timezone: "Asia/Kolkata".to_string(),
+            work_days: (1..=7).collect(),
+            day_start_hour: 0,
+            day_end_hour: 24,
+            holidays: vec![],
+        })
+        .unwrap();
+
+        assert_eq!(
+            calendar.working_time(utc(3, 7, 15), utc(4, 8, 0)),
+            Duration::minutes(24 * 60 + 45)
+        );
+    }
+
+    #[test]
+    fn test_calendar_rejects_invalid() {
+        let unknown_tz = WorkCalendar {
+            timezone: "Mars/Olympus".to_string(),
+            ..Default::default()
+        };
+        assert!(Calendar::new(&unknown_tz).is_err());
+
+        let backwards = WorkCalendar {
+            day_start_hour: 17,
+            day_end_hour: 9,
+            ..Default::default()
+        };
+        assert!(Calendar::new(&backwards).is_err());
+
+        let bad_day = WorkCalendar {
+            work_days: vec![0],
+            ..Default::default()
+        };
+        assert!(Calendar::new(&bad_day).is_err());
+    }
+
+    #[test]
+    fn test_ready_at_takes_latest() {
+        assert_eq!(ready_at(None, None), None);
+        assert_eq!(ready_at(Some(utc(1, 10, 0)), None), Some(utc(1, 10, 0)));
+        assert_eq!(
+            ready_at(Some(utc(1, 10, 0)), Some(utc(2, 9, 0))),
+            Some(utc(2, 9, 0))
+        );
+        assert_eq!(
+            ready_at(Some(utc(2, 10, 0)), Some(utc(1, 9, 0))),
+            Some(utc(2, 10, 0))
+        );
+    }
+
+    #[test]
+    fn test_average_secs() {
+        let waits = [(utc(5, 9, 0), utc(5, 10, 0)), (utc(5, 9, 0), utc(5, 12, 0))];
+        assert_eq!(average_secs(&office("UTC"), &waits), Some(2.0 * 3600.0));
+        assert_eq!(average_secs(&office("UTC"), &[]), None);
+    }
+}
@@ -1,13 +1,25 @@
 //! Event processing and metrics computation
 
 pub mod achievements;
+pub mod analytics;
 pub mod backfill;
 pub mod categorize;
+pub mod coverage;
+pub mod expertise;
+pub mod gaming;
+pub mod hunks;
+pub mod latency;
 pub mod metrics;
 pub mod recalculate;
+pub mod recommend;
+pub mod responsiveness;
 pub mod scores;
 pub mod sessions;
+pub mod simulate;
+pub mod stale;
 pub mod sync;
+pub mod untrack;
+pub mod workload;
 
 #[cfg(test)]
 mod tests;
@@ -17,5 +29,7 @@ pub use categorize::{
     categorize_batch, get_stats as get_category_stats, CategorizeError, CategorizeStats,
     CategoryStats,
 };
-pub use recalculate::{recalculate_all_xp, RecalculationStats};
+pub use recalculate::{recalculate_all_xp, recalculate_incremental, RecalculationStats};
+pub use simulate::{simulate_recalculation, SimulationReport};
 pub use sync::{SyncConfig, SyncService};
+pub use untrack::{untrack_repo, UntrackMode, UntrackStats};
@@ -1,24 +1,12 @@
 //! Metrics computation
 
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Utc};
 
 #[cfg(test)]
 #[path = "metrics_test.rs"]
 mod metrics_test;
 use common::models::PullRequest;
 
-/// Calculate time to first review in seconds
-pub fn time_to_first_review(pr: &PullRequest) -> Option<i64> {
-    pr.first_review_at
-        .map(|first_review| (first_review - pr.created_at).num_seconds())
-}
-
-/// Calculate if a review was "fast" (under 1 hour)
-pub fn is_fast_review(pr_created: DateTime<Utc>, review_submitted: DateTime<Utc>) -> bool {
-    let diff = review_submitted - pr_created;
-    diff < Duration::hours(1)
-}
-
 /// Calculate if this was the first review on a PR
 pub fn is_first_review(pr: &PullRequest, review_submitted: DateTime<Utc>) -> bool {
     match pr.first_review_at {
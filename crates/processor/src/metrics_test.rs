@@ -24,64 +24,6 @@ mod tests {
         }
     }
 
-    // time_to_first_review tests
-    #[test]
-    fn test_time_to_first_review_none_if_no_review() {
-        let pr = make_pr(Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap(), None);
-        assert_eq!(time_to_first_review(&pr), None);
-    }
-
-    #[test]
-    fn test_time_to_first_review_30_minutes() {
-        let created = Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap();
-        let first_review = Utc.with_ymd_and_hms(2026, 1, 1, 10, 30, 0).unwrap();
-        let pr = make_pr(created, Some(first_review));
-
-        assert_eq!(time_to_first_review(&pr), Some(30 * 60)); // 30 minutes in seconds
-    }
-
-    #[test]
-    fn test_time_to_first_review_2_days() {
-        let created = Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap();
-        let first_review = Utc.with_ymd_and_hms(2026, 1, 3, 10, 0, 0).unwrap();
-        let pr = make_pr(created, Some(first_review));
-
-        assert_eq!(time_to_first_review(&pr), Some(2 * 24 * 60 * 60)); // 2 days in seconds
-    }
-
-    // is_fast_review tests
-    #[test]
-    fn test_is_fast_review_under_1_hour() {
-        let pr_created = Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap();
-        let review_submitted = Utc.with_ymd_and_hms(2026, 1, 1, 10, 30, 0).unwrap();
-
-        assert!(is_fast_review(pr_created, review_submitted));
-    }
-
-    #[test]
-    fn test_is_fast_review_exactly_1_hour_not_fast() {
-        let pr_created = Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap();
-        let review_submitted = Utc.with_ymd_and_hms(2026, 1, 1, 11, 0, 0).unwrap();
-
-        assert!(!is_fast_review(pr_created, review_submitted));
-    }
-
-    #[test]
-    fn test_is_fast_review_over_1_hour() {
-        let pr_created = Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap();
-        let review_submitted = Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, 0).unwrap();
-
-        assert!(!is_fast_review(pr_created, review_submitted));
-    }
-
-    #[test]
-    fn test_is_fast_review_59_minutes() {
-        let pr_created = Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap();
-        let review_submitted = Utc.with_ymd_and_hms(2026, 1, 1, 10, 59, 0).unwrap();
-
-        assert!(is_fast_review(pr_created, review_submitted));
-    }
-
     // is_first_review tests
     #[test]
     fn test_is_first_review_no_previous_review() {
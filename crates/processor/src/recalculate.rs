@@ -1,30 +1,163 @@
 //! XP recalculation based on new session-based rules
+//!
+//! Two modes:
+//! - full: reset everything and rescore every (PR, reviewer) pair. Use after
+//!   changing the XP formula.
+//! - incremental: rescore only pairs whose reviews, PR commits or comments
+//!   changed since the last run. Runs automatically after each sync.
 
+use chrono::{DateTime, Utc};
+use common::models::{Commit, PrSize, Review, ScoringWeights, XpBreakdown};
+use db::recalc::SessionAward;
+use db::review_comments::CommentQualityData;
 use sqlx::PgPool;
-use tracing::info;
+use std::collections::{HashMap, HashSet};
+use tracing::{info, warn};
 use uuid::Uuid;
 
-use crate::sessions::{calculate_session_xp_with_quality, group_reviews_into_sessions};
+use crate::latency::{self, Calendars};
+use crate::sessions::{
+    group_reviews_into_sessions_with_gap, session_quality, session_xp_breakdown, XP_RULES_VERSION,
+};
 
-/// Recalculate all user XP from scratch based on review sessions
+#[cfg(test)]
+#[path = "recalculate_test.rs"]
+mod recalculate_test;
+
+/// XP for one session of a (PR, reviewer) pair
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionScore {
+    /// First review of the session (where xp_earned is stored)
+    pub first_review_id: Uuid,
+    pub xp: i64,
+    pub breakdown: XpBreakdown,
+}
+
+/// Scored sessions for one (PR, reviewer) pair
+#[derive(Debug, Clone)]
+pub struct PairScore {
+    pub pr_id: Uuid,
+    pub reviewer_id: Uuid,
+    pub review_count: usize,
+    pub sessions: Vec<SessionScore>,
+}
+
+/// Group reviews into sessions and score them, per (PR, reviewer) pair.
+///
+/// `commits` may span many PRs; they are indexed by PR once. Comment quality
+/// is keyed by review, so each session is scored on the comments submitted in
+/// it (see [`session_quality`]). Each PR is scored
+/// with its repo's weights from `weights_by_pr` (default weights if missing).
+/// Latency is measured from the PR opening (`opened_at`) or the last push
+/// before each session, on the reviewer's calendar. PRs missing from `sizes`
+/// get no difficulty multiplier.
+/// Output is sorted by (pr_id, reviewer_id) so runs are deterministic.
+pub fn score_pairs(
+    reviews: Vec<Review>,
+    commits: Vec<Commit>,
+    quality: &HashMap<Uuid, CommentQualityData>,
+    weights_by_pr: &HashMap<Uuid, ScoringWeights>,
+    opened_at: &HashMap<Uuid, DateTime<Utc>>,
+    sizes: &HashMap<Uuid, PrSize>,
+    calendars: &Calendars,
+) -> Vec<PairScore> {
+    let default_weights = ScoringWeights::default();
+
+    let mut commits_by_pr: HashMap<Uuid, Vec<Commit>> = HashMap::new();
+    for commit in commits {
+        commits_by_pr.entry(commit.pr_id).or_default().push(commit);
+    }
+    for pr_commits in commits_by_pr.values_mut() {
+        pr_commits.sort_by_key(|c| c.committed_at);
+    }
+
+    let mut review_groups: HashMap<(Uuid, Uuid), Vec<Review>> = HashMap::new();
+    for review in reviews {
+        review_groups
+            .entry((review.pr_id, review.reviewer_id))
+            .or_default()
+            .push(review);
+    }
+
+    let mut keys: Vec<_> = review_groups.keys().copied().collect();
+    keys.sort();
+
+    keys.into_iter()
+        .map(|(pr_id, reviewer_id)| {
+            let pr_reviews = review_groups
+                .remove(&(pr_id, reviewer_id))
+                .unwrap_or_default();
+            let review_count = pr_reviews.len();
+            let pr_commits = commits_by_pr.get(&pr_id).map(Vec::as_slice).unwrap_or(&[]);
+            let weights = weights_by_pr.get(&pr_id).unwrap_or(&default_weights);
+            let max_gap = weights.session_gap();
+            let calendar = calendars.for_user(reviewer_id);
+            let size = sizes.get(&pr_id);
+
+            let sessions =
+                group_reviews_into_sessions_with_gap(pr_reviews, pr_commits.to_vec(), max_gap)
+                    .into_iter()
+                    .filter_map(|session| {
+                        let ready = latency::ready_at(
+                            opened_at.get(&pr_id).copied(),
+                            last_commit_before(pr_commits, session.started_at),
+                        );
+                        let quality_data = session_quality(&session, quality);
+                        let breakdown = session_xp_breakdown(
+                            &session,
+                            ready,
+                            quality_data.as_ref(),
+                            size,
+                            weights,
+                            calendar,
+                        );
+                        session.reviews.first().map(|r| SessionScore {
+                            first_review_id: r.id,
+                            xp: breakdown.total(),
+                            breakdown,
+                        })
+                    })
+                    .collect();
+
+            PairScore {
+                pr_id,
+                reviewer_id,
+                review_count,
+                sessions,
+            }
+        })
+        .collect()
+}
+
+/// Zero the XP of sessions whose (PR, reviewer) pair was excluded by a
+/// penalized gaming flag. Sessions are kept so the pair is still recorded.
+pub fn apply_exclusions(scores: &mut [PairScore], excluded: &HashSet<(Uuid, Uuid)>) {
+    for pair in scores
+        .iter_mut()
+        .filter(|p| excluded.contains(&(p.pr_id, p.reviewer_id)))
+    {
+        for session in &mut pair.sessions {
+            session.breakdown = XpBreakdown::default();
+            session.xp = 0;
+        }
+    }
+}
+
+/// Most recent commit strictly before `at` (commits must be sorted)
+fn last_commit_before(sorted_commits: &[Commit], at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let idx = sorted_commits.partition_point(|c| c.committed_at < at);
+    idx.checked_sub(1).map(|i| sorted_commits[i].committed_at)
+}
+
+/// Recalculate all user XP from scratch based on review sessions.
+///
+/// Scores are computed in memory and swapped in with a single transaction, so
+/// the live leaderboard keeps the old values until the new ones are complete.
 pub async fn recalculate_all_xp(pool: &PgPool) -> Result<RecalculationStats, sqlx::Error> {
     info!("Starting XP recalculation for all users");
+    let run_at = db::recalc::now(pool).await?;
 
-    // Step 1: Reset all user XP and review xp_earned
-    info!("Resetting all user XP and review xp_earned to 0");
-    sqlx::query("UPDATE users SET xp = 0, level = 1")
-        .execute(pool)
-        .await?;
-    // Note: review_sessions column may not exist yet, ignore errors
-    let _ = sqlx::query("UPDATE users SET review_sessions = 0")
-        .execute(pool)
-        .await;
-    // Note: xp_earned column may not exist yet, ignore errors
-    let _ = sqlx::query("UPDATE reviews SET xp_earned = 0")
-        .execute(pool)
-        .await;
-
-    // Step 2: Get all reviews and commits
+    // Step 1: Get all reviews, commits and comment quality
     info!("Fetching all reviews");
     let reviews = db::reviews::list_all(pool).await?;
     info!("Fetched {} reviews", reviews.len());
@@ -33,126 +166,201 @@ pub async fn recalculate_all_xp(pool: &PgPool) -> Result<RecalculationStats, sql
     let commits = db::commits::list_all(pool).await?;
     info!("Fetched {} commits", commits.len());
 
-    // Step 3: Group reviews by (pr_id, reviewer_id)
-    let mut review_groups: std::collections::HashMap<(Uuid, Uuid), Vec<_>> =
-        std::collections::HashMap::new();
-    for review in reviews {
-        review_groups
-            .entry((review.pr_id, review.reviewer_id))
-            .or_default()
-            .push(review);
-    }
+    let quality =
+        db::review_comments::get_quality_data_by_review(pool, None, &HashMap::new()).await?;
+    let weights = db::scoring_profiles::weights_by_pr(pool, None).await?;
+    let opened_at = db::prs::opened_at_by_id(pool, None).await?;
+    let sizes = db::prs::sizes_by_id(pool, None).await?;
+    let calendars = Calendars::load(pool).await?;
+    let excluded = db::gaming::excluded_pairs(pool).await?;
+
+    // Step 2: Score every pair, then swap the results in atomically
+    let mut scores = score_pairs(
+        reviews, commits, &quality, &weights, &opened_at, &sizes, &calendars,
+    );
+    apply_exclusions(&mut scores, &excluded);
+    let awards = session_awards(&scores);
+    info!("Swapping in {} session awards", awards.len());
+    db::recalc::replace_all_xp(pool, &awards, XP_RULES_VERSION).await?;
+
+    // Step 3: Achievements and watermark (failures are reported, not fatal)
+    let stats = finish_run(pool, &scores, &awards, run_at).await;
 
     info!(
-        "Grouped reviews into {} unique (pr, reviewer) pairs",
-        review_groups.len()
+        "Recalculation complete: {} sessions, {} XP awarded, {} users updated, {} errors",
+        stats.total_sessions,
+        stats.total_xp_awarded,
+        stats.users_updated,
+        stats.errors.len()
     );
 
-    // Step 4: Process each group into sessions and award XP
-    let total_reviews_count: usize = review_groups.values().map(|v| v.len()).sum();
-    let mut total_sessions = 0;
-    let mut total_xp_awarded = 0i64;
-    let mut users_updated = std::collections::HashSet::new();
-    let mut user_session_counts: std::collections::HashMap<Uuid, i32> =
-        std::collections::HashMap::new();
-
-    for ((pr_id, reviewer_id), pr_reviews) in review_groups {
-        // Get commits for this PR
-        let pr_commits: Vec<_> = commits
-            .iter()
-            .filter(|c| c.pr_id == pr_id)
-            .cloned()
-            .collect();
-
-        // Get quality data for this PR/user combination
-        let quality_data =
-            db::review_comments::get_quality_data_for_pr_user(pool, pr_id, reviewer_id)
-                .await
-                .ok();
-
-        // Group into sessions
-        let sessions = group_reviews_into_sessions(pr_reviews, pr_commits.clone());
-        let session_count = sessions.len() as i32;
-        total_sessions += session_count as usize;
-
-        // Track sessions per user
-        *user_session_counts.entry(reviewer_id).or_insert(0) += session_count;
-
-        // Calculate XP for each session
-        for session in sessions {
-            // Find the most recent commit before this session
-            let commit_before = pr_commits
-                .iter()
-                .filter(|c| c.committed_at < session.started_at)
-                .max_by_key(|c| c.committed_at)
-                .map(|c| c.committed_at);
-
-            let xp =
-                calculate_session_xp_with_quality(&session, commit_before, quality_data.as_ref());
-
-            if xp > 0 {
-                // Award XP to user
-                let _ = db::users::add_xp(pool, reviewer_id, xp).await;
-                total_xp_awarded += xp;
-                users_updated.insert(reviewer_id);
-
-                // Store XP on the first review of the session for period filtering
-                if let Some(first_review) = session.reviews.first() {
-                    let _ = sqlx::query("UPDATE reviews SET xp_earned = $1 WHERE id = $2")
-                        .bind(xp as i32)
-                        .bind(first_review.id)
-                        .execute(pool)
-                        .await;
-                }
-            }
-        }
+    Ok(stats)
+}
+
+/// Recalculate XP only for (PR, reviewer) pairs touched since the last run.
+///
+/// Falls back to a full recalculation if no run has been recorded yet.
+pub async fn recalculate_incremental(pool: &PgPool) -> Result<RecalculationStats, sqlx::Error> {
+    let Some(since) = db::recalc::get_last_run(pool).await? else {
+        info!("No previous recalculation recorded, running full recalculation");
+        return recalculate_all_xp(pool).await;
+    };
+    let run_at = db::recalc::now(pool).await?;
+
+    let pairs = db::recalc::list_touched_pairs(pool, since).await?;
+    if pairs.is_empty() {
+        info!("Incremental recalculation: nothing changed since {}", since);
+        db::recalc::set_last_run(pool, run_at).await?;
+        return Ok(RecalculationStats::default());
     }
 
-    // Step 5: Update session counts for all users (if column exists)
+    let mut pr_ids: Vec<Uuid> = pairs.iter().map(|(pr_id, _)| *pr_id).collect();
+    pr_ids.sort();
+    pr_ids.dedup();
     info!(
-        "Updating session counts for {} users",
-        user_session_counts.len()
+        "Incremental recalculation: {} pairs across {} PRs",
+        pairs.len(),
+        pr_ids.len()
     );
-    for (user_id, session_count) in &user_session_counts {
-        // Note: review_sessions column may not exist yet, ignore errors
-        let _ = sqlx::query("UPDATE users SET review_sessions = $1 WHERE id = $2")
-            .bind(*session_count)
-            .bind(*user_id)
-            .execute(pool)
-            .await;
-    }
 
-    // Step 6: Check and unlock achievements for all users
-    info!("Checking achievements for {} users", users_updated.len());
+    let touched: HashSet<(Uuid, Uuid)> = pairs.iter().copied().collect();
+    let reviews: Vec<Review> = db::reviews::list_for_prs(pool, &pr_ids)
+        .await?
+        .into_iter()
+        .filter(|r| touched.contains(&(r.pr_id, r.reviewer_id)))
+        .collect();
+    let commits = db::commits::list_for_prs(pool, &pr_ids).await?;
+    let quality =
+        db::review_comments::get_quality_data_by_review(pool, Some(&pr_ids), &HashMap::new())
+            .await?;
+    let weights = db::scoring_profiles::weights_by_pr(pool, Some(&pr_ids)).await?;
+    let opened_at = db::prs::opened_at_by_id(pool, Some(&pr_ids)).await?;
+    let sizes = db::prs::sizes_by_id(pool, Some(&pr_ids)).await?;
+    let calendars = Calendars::load(pool).await?;
+    let excluded = db::gaming::excluded_pairs(pool).await?;
+
+    let mut scores = score_pairs(
+        reviews, commits, &quality, &weights, &opened_at, &sizes, &calendars,
+    );
+    apply_exclusions(&mut scores, &excluded);
+    let awards = session_awards(&scores);
+    db::recalc::apply_pair_xp(pool, &pairs, &awards, XP_RULES_VERSION).await?;
+
+    let stats = finish_run(pool, &scores, &awards, run_at).await;
+
+    info!(
+        "Incremental recalculation complete: {} pairs, {} sessions, {} XP, {} errors",
+        stats.pairs_processed,
+        stats.total_sessions,
+        stats.total_xp_awarded,
+        stats.errors.len()
+    );
+
+    Ok(stats)
+}
+
+/// XP awards for the first review of every scored session
+fn session_awards(scores: &[PairScore]) -> Vec<SessionAward> {
+    scores
+        .iter()
+        .flat_map(|pair| {
+            pair.sessions.iter().map(|s| SessionAward {
+                review_id: s.first_review_id,
+                pr_id: pair.pr_id,
+                reviewer_id: pair.reviewer_id,
+                breakdown: s.breakdown,
+            })
+        })
+        .collect()
+}
+
+/// After XP is written: re-evaluate achievements for the affected reviewers
+/// and the authors of their PRs (for author metrics such as quick fixes),
+/// weight new reviews for decayed ranking and advance the watermark. Failures
+/// are collected into `errors`.
+async fn finish_run(
+    pool: &PgPool,
+    scores: &[PairScore],
+    awards: &[SessionAward],
+    run_at: DateTime<Utc>,
+) -> RecalculationStats {
+    let mut errors = Vec::new();
+
+    let reviewers: HashSet<Uuid> = scores.iter().map(|s| s.reviewer_id).collect();
+    let mut users = reviewers.clone();
+    let pr_ids: Vec<Uuid> = scores
+        .iter()
+        .map(|s| s.pr_id)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    match db::prs::author_ids(pool, &pr_ids).await {
+        Ok(authors) => users.extend(authors),
+        Err(e) => {
+            warn!("Failed to load PR authors: {}", e);
+            errors.push(format!("loading PR authors: {}", e));
+        }
+    }
+    info!("Checking achievements for {} users", users.len());
     let checker = crate::achievements::AchievementChecker::new(pool.clone());
     let mut total_achievements = 0;
-    for user_id in &users_updated {
-        if let Ok(unlocked) = checker.check_reviewer(user_id).await {
-            total_achievements += unlocked.len();
+    let mut stale_achievements = 0;
+    let mut scoped_achievements = 0;
+    for user_id in &users {
+        match checker.reevaluate_user(user_id, false).await {
+            Ok(evaluation) => {
+                total_achievements += evaluation.unlocked.len();
+                stale_achievements += evaluation.stale.len();
+                scoped_achievements += evaluation.scoped_unlocked.len();
+            }
+            Err(e) => {
+                warn!("Achievement check failed for {}: {}", user_id, e);
+                errors.push(format!("achievement check for user {}: {}", user_id, e));
+            }
         }
     }
-    info!("Unlocked {} achievements", total_achievements);
-
     info!(
-        "Recalculation complete: {} sessions, {} XP awarded, {} users updated, {} achievements",
-        total_sessions,
-        total_xp_awarded,
-        users_updated.len(),
-        total_achievements
+        "Unlocked {} achievements ({} in repos/teams), {} held achievements no longer met",
+        total_achievements, scoped_achievements, stale_achievements
     );
 
-    Ok(RecalculationStats {
-        total_reviews: total_reviews_count,
-        total_sessions,
-        total_xp_awarded,
-        users_updated: users_updated.len(),
-    })
+    if let Err(e) = db::decay::refresh_weights(pool).await {
+        warn!("Failed to refresh decayed XP weights: {}", e);
+        errors.push(format!("refreshing decay weights: {}", e));
+    }
+
+    if let Err(e) = db::recalc::set_last_run(pool, run_at).await {
+        warn!("Failed to record recalculation time: {}", e);
+        errors.push(format!("recording last run: {}", e));
+    }
+
+    RecalculationStats {
+        total_reviews: scores.iter().map(|s| s.review_count).sum(),
+        total_sessions: scores.iter().map(|s| s.sessions.len()).sum(),
+        total_xp_awarded: awards.iter().map(|a| a.xp() as i64).sum(),
+        users_updated: reviewers.len(),
+        pairs_processed: scores.len(),
+        achievements_unlocked: total_achievements,
+        achievements_stale: stale_achievements,
+        scoped_achievements_unlocked: scoped_achievements,
+        errors,
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct RecalculationStats {
     pub total_reviews: usize,
     pub total_sessions: usize,
     pub total_xp_awarded: i64,
     pub users_updated: usize,
+    /// (PR, reviewer) pairs rescored
+    pub pairs_processed: usize,
+    pub achievements_unlocked: usize,
+    /// Held achievements whose rule is no longer met (kept; see
+    /// `AchievementChecker::reevaluate_all` to revoke them)
+    pub achievements_stale: usize,
+    /// Unlocks within a repository or team
+    pub scoped_achievements_unlocked: usize,
+    /// Writes that failed after XP was swapped in (achievements, watermark)
+    pub errors: Vec<String>,
 }
@@ -0,0 +1,422 @@
+#[cfg(test)]
+mod tests {
+    use crate::latency::{Calendar, Calendars};
+    use crate::recalculate::{apply_exclusions, score_pairs};
+    use chrono::{DateTime, TimeZone, Utc};
+    use common::models::{Commit, PrSize, Review, ReviewState, ScoringWeights, WorkCalendar};
+    use db::review_comments::CommentQualityData;
+    use std::collections::{HashMap, HashSet};
+    use uuid::Uuid;
+
+    fn make_review(
+        pr_id: Uuid,
+        reviewer_id: Uuid,
+        submitted_at: DateTime<Utc>,
+        comments: i32,
+    ) -> Review {
+        Review {
+            id: Uuid::new_v4(),
+            pr_id,
+            reviewer_id,
+            github_id: 1,
+            state: ReviewState::Commented,
+            body: None,
+            comments_count: comments,
+            submitted_at,
+        }
+    }
+
+    fn make_commit(pr_id: Uuid, committed_at: DateTime<Utc>) -> Commit {
+        Commit {
+            id: Uuid::new_v4(),
+            pr_id,
+            sha: format!("sha{}", committed_at.timestamp()),
+            author_id: None,
+            committed_at,
+            message: None,
+            created_at: committed_at,
+        }
+    }
+
+    fn at(hour: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 1, hour, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_score_pairs_empty() {
+        assert!(score_pairs(
+            vec![],
+            vec![],
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &Calendars::default()
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_score_pairs_groups_by_pr_and_reviewer() {
+        let pr_a = Uuid::new_v4();
+        let pr_b = Uuid::new_v4();
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+
+        let reviews = vec![
+            make_review(pr_a, alice, at(10, 0), 1),
+            make_review(pr_a, alice, at(10, 5), 1),
+            make_review(pr_a, bob, at(11, 0), 1),
+            make_review(pr_b, alice, at(12, 0), 1),
+        ];
+
+        let scores = score_pairs(
+            reviews,
+            vec![],
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &Calendars::default(),
+        );
+
+        assert_eq!(scores.len(), 3);
+        assert_eq!(scores.iter().map(|s| s.review_count).sum::<usize>(), 4);
+        let a_alice = scores
+            .iter()
+            .find(|s| s.pr_id == pr_a && s.reviewer_id == alice)
+            .unwrap();
+        assert_eq!(a_alice.review_count, 2);
+        assert_eq!(a_alice.sessions.len(), 1);
+    }
+
+    #[test]
+    fn test_score_pairs_output_is_sorted() {
+        let reviews: Vec<_> = (0..5)
+            .map(|_| make_review(Uuid::new_v4(), Uuid::new_v4(), at(10, 0), 1))
+            .collect();
+
+        let scores = score_pairs(
+            reviews,
+            vec![],
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &Calendars::default(),
+        );
+        let keys: Vec<_> = scores.iter().map(|s| (s.pr_id, s.reviewer_id)).collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+    }
+
+    #[test]
+    fn test_score_pairs_xp_on_first_review_of_session() {
+        let pr_id = Uuid::new_v4();
+        let reviewer_id = Uuid::new_v4();
+        let first = make_review(pr_id, reviewer_id, at(10, 0), 1);
+        let second = make_review(pr_id, reviewer_id, at(10, 10), 1);
+        let first_id = first.id;
+
+        let scores = score_pairs(
+            vec![second, first],
+            vec![],
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &Calendars::default(),
+        );
+
+        assert_eq!(scores[0].sessions.len(), 1);
+        assert_eq!(scores[0].sessions[0].first_review_id, first_id);
+        // Base 10 + 2 comments × 5
+        assert_eq!(scores[0].sessions[0].xp, 20);
+    }
+
+    #[test]
+    fn test_score_pairs_commit_splits_sessions_and_fast_bonus() {
+        let pr_id = Uuid::new_v4();
+        let other_pr = Uuid::new_v4();
+        let reviewer_id = Uuid::new_v4();
+
+        let reviews = vec![
+            make_review(pr_id, reviewer_id, at(10, 0), 1),
+            make_review(pr_id, reviewer_id, at(12, 0), 1),
+        ];
+        // Commit on this PR between the reviews, plus an unrelated commit on another PR
+        let commits = vec![
+            make_commit(pr_id, at(11, 30)),
+            make_commit(other_pr, at(9, 59)),
+        ];
+
+        let scores = score_pairs(
+            reviews,
+            commits,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &Calendars::default(),
+        );
+
+        assert_eq!(scores[0].sessions.len(), 2);
+        // First session: no commit before it on this PR
+        assert_eq!(scores[0].sessions[0].xp, 15);
+        // Second session: reviewed 30 min after push → +10 fast bonus
+        assert_eq!(scores[0].sessions[1].xp, 25);
+    }
+
+    #[test]
+    fn test_score_pairs_uses_session_quality_data() {
+        let pr_id = Uuid::new_v4();
+        let reviewer_id = Uuid::new_v4();
+        let review = make_review(pr_id, reviewer_id, at(10, 0), 1);
+
+        let mut quality = HashMap::new();
+        quality.insert(
+            review.id,
+            CommentQualityData {
+                by_tier: (0, 0, 1),
+                by_category: (1, 0, 0),
+                categorized_count: 1,
+                short_count: 0,
+                addressed_count: 0,
+            },
+        );
+
+        let scores = score_pairs(
+            vec![review],
+            vec![],
+            &quality,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &Calendars::default(),
+        );
+
+        // Base 10 + high quality 8 + logic bonus 3
+        assert_eq!(scores[0].sessions[0].xp, 21);
+    }
+
+    #[test]
+    fn test_score_pairs_quality_data_stays_with_its_session() {
+        let pr_id = Uuid::new_v4();
+        let reviewer_id = Uuid::new_v4();
+        // Two sessions split by a push: 2 comments, then 1
+        let first = make_review(pr_id, reviewer_id, at(10, 0), 2);
+        let second = make_review(pr_id, reviewer_id, at(14, 0), 1);
+        let commits = vec![make_commit(pr_id, at(12, 0))];
+
+        // Both comments of the first session were addressed; the second
+        // session's comment is short
+        let mut quality = HashMap::new();
+        quality.insert(
+            first.id,
+            CommentQualityData {
+                by_tier: (0, 2, 0),
+                categorized_count: 2,
+                addressed_count: 2,
+                ..Default::default()
+            },
+        );
+        quality.insert(
+            second.id,
+            CommentQualityData {
+                short_count: 1,
+                ..Default::default()
+            },
+        );
+
+        let scores = score_pairs(
+            vec![first, second],
+            commits,
+            &quality,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &Calendars::default(),
+        );
+
+        let sessions = &scores[0].sessions;
+        assert_eq!(sessions.len(), 2);
+        // Base 10 + 2 medium * 5 + 2 addressed * 5
+        assert_eq!(sessions[0].breakdown.addressed_bonus, 10);
+        assert_eq!(sessions[0].xp, 30);
+        // Addressed bonus is not paid again; the short comment earns nothing
+        assert_eq!(sessions[1].breakdown.addressed_bonus, 0);
+        assert_eq!(sessions[1].breakdown.comments_uncategorized, 0);
+        assert_eq!(sessions[1].xp, 10);
+    }
+
+    #[test]
+    fn test_score_pairs_keeps_zero_xp_sessions() {
+        let pr_id = Uuid::new_v4();
+        let reviewer_id = Uuid::new_v4();
+        // Commented with no comments and no state change: rubber stamp
+        let reviews = vec![make_review(pr_id, reviewer_id, at(10, 0), 0)];
+
+        let scores = score_pairs(
+            reviews,
+            vec![],
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &Calendars::default(),
+        );
+
+        assert_eq!(scores[0].sessions.len(), 1);
+        assert_eq!(scores[0].sessions[0].xp, 0);
+    }
+
+    #[test]
+    fn test_score_pairs_uses_pr_weights() {
+        let custom_pr = Uuid::new_v4();
+        let default_pr = Uuid::new_v4();
+        let reviewer_id = Uuid::new_v4();
+
+        // Two reviews 2 hours apart on each PR
+        let reviews = vec![
+            make_review(custom_pr, reviewer_id, at(10, 0), 1),
+            make_review(custom_pr, reviewer_id, at(12, 0), 1),
+            make_review(default_pr, reviewer_id, at(10, 0), 1),
+            make_review(default_pr, reviewer_id, at(12, 0), 1),
+        ];
+
+        let mut weights = HashMap::new();
+        weights.insert(
+            custom_pr,
+            ScoringWeights {
+                base_xp: 50,
+                session_gap_hours: 1,
+                ..Default::default()
+            },
+        );
+
+        let scores = score_pairs(
+            reviews,
+            vec![],
+            &HashMap::new(),
+            &weights,
+            &HashMap::new(),
+            &HashMap::new(),
+            &Calendars::default(),
+        );
+        let custom = scores.iter().find(|s| s.pr_id == custom_pr).unwrap();
+        let default = scores.iter().find(|s| s.pr_id == default_pr).unwrap();
+
+        // 1h gap splits the custom PR into two sessions of 50 + 5
+        assert_eq!(custom.sessions.len(), 2);
+        assert!(custom.sessions.iter().all(|s| s.xp == 55));
+        // Default weights: one session, base 10 + 2 comments × 5
+        assert_eq!(default.sessions.len(), 1);
+        assert_eq!(default.sessions[0].xp, 20);
+    }
+
+    #[test]
+    fn test_apply_exclusions_zeroes_excluded_pairs() {
+        let pr_id = Uuid::new_v4();
+        let cheater = Uuid::new_v4();
+        let honest = Uuid::new_v4();
+        let reviews = vec![
+            make_review(pr_id, cheater, at(10, 0), 2),
+            make_review(pr_id, honest, at(10, 0), 2),
+        ];
+
+        let mut scores = score_pairs(
+            reviews,
+            vec![],
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &Calendars::default(),
+        );
+        apply_exclusions(&mut scores, &HashSet::from([(pr_id, cheater)]));
+
+        let cheater_score = scores.iter().find(|s| s.reviewer_id == cheater).unwrap();
+        let honest_score = scores.iter().find(|s| s.reviewer_id == honest).unwrap();
+        assert_eq!(cheater_score.sessions.len(), 1);
+        assert_eq!(cheater_score.sessions[0].xp, 0);
+        assert_eq!(cheater_score.sessions[0].breakdown.total(), 0);
+        assert_eq!(honest_score.sessions[0].xp, 20);
+    }
+
+    #[test]
+    fn test_score_pairs_fast_bonus_in_working_time() {
+        let pr_id = Uuid::new_v4();
+        let anywhere = Uuid::new_v4();
+        let office = Uuid::new_v4();
+        // Opened at 01:00 without commits, both review at 09:30
+        let reviews = vec![
+            make_review(pr_id, anywhere, at(9, 30), 1),
+            make_review(pr_id, office, at(9, 30), 1),
+        ];
+        let opened_at = HashMap::from([(pr_id, at(1, 0))]);
+        let office_hours = Calendar::new(&WorkCalendar {
+            work_days: (1..=7).collect(),
+            ..Default::default()
+        })
+        .unwrap();
+        let calendars = Calendars::new(HashMap::from([(office, office_hours)]));
+
+        let scores = score_pairs(
+            reviews,
+            vec![],
+            &HashMap::new(),
+            &HashMap::new(),
+            &opened_at,
+            &HashMap::new(),
+            &calendars,
+        );
+        let fast_bonus = |user| {
+            let pair = scores.iter().find(|s| s.reviewer_id == user).unwrap();
+            pair.sessions[0].breakdown.fast_bonus
+        };
+
+        // 8.5 hours around the clock, but 30 working minutes from 09:00
+        assert_eq!(fast_bonus(anywhere), 0);
+        assert_eq!(fast_bonus(office), 10);
+    }
+
+    #[test]
+    fn test_score_pairs_applies_pr_size() {
+        let big_pr = Uuid::new_v4();
+        let unsized_pr = Uuid::new_v4();
+        let reviewer_id = Uuid::new_v4();
+        let reviews = vec![
+            make_review(big_pr, reviewer_id, at(10, 0), 2),
+            make_review(unsized_pr, reviewer_id, at(10, 0), 2),
+        ];
+        let sizes = HashMap::from([(
+            big_pr,
+            PrSize {
+                additions: 400,
+                deletions: 100,
+                changed_files: 20,
+            },
+        )]);
+
+        let scores = score_pairs(
+            reviews,
+            vec![],
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &sizes,
+            &Calendars::default(),
+        );
+        let session = |pr_id| {
+            let pair = scores.iter().find(|s| s.pr_id == pr_id).unwrap();
+            pair.sessions[0].clone()
+        };
+
+        // 10 base + 2 comments × 5, times 1.5
+        assert_eq!(session(big_pr).breakdown.difficulty_pct, 150);
+        assert_eq!(session(big_pr).xp, 30);
+        assert_eq!(session(unsized_pr).breakdown.difficulty_pct, 100);
+        assert_eq!(session(unsized_pr).xp, 20);
+    }
+}
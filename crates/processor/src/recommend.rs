@@ -0,0 +1,219 @@
+//! Reviewer recommendations for open PRs
+//!
+//! Candidates are everyone who reviewed in the repo over the last
+//! `LOOKBACK_DAYS`, except the author and people already reviewing the PR.
+//! Each is scored on how well they know the PR's files (see `expertise`), how
+//! often they review the author, and how quickly they pick up reviews, minus
+//! a penalty for the open reviews already on their plate. PRs are handled
+//! oldest first and each top pick counts as one more open review, so
+//! suggestions spread across people instead of piling onto the best expert.
+
+use chrono::{Duration, Utc};
+use common::models::ReviewerSuggestion;
+use db::prs::OpenPrWithStats;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::expertise::{self, ExpertiseIndex};
+use crate::latency::{self, Calendars};
+
+#[cfg(test)]
+#[path = "recommend_test.rs"]
+mod recommend_test;
+
+/// Reviews older than this don't make someone a candidate
+pub const LOOKBACK_DAYS: i64 = 90;
+
+pub const EXPERTISE_WEIGHT: f64 = 0.45;
+pub const AUTHOR_WEIGHT: f64 = 0.2;
+pub const RESPONSIVENESS_WEIGHT: f64 = 0.2;
+pub const LOAD_WEIGHT: f64 = 0.5;
+
+/// Open reviews at which the load penalty is half its maximum
+pub const LOAD_HALF: f64 = 2.0;
+
+/// Working hours to first review at which responsiveness scores 0.5
+pub const RESPONSE_HALF_HOURS: f64 = 8.0;
+
+/// A possible reviewer
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub user_id: Uuid,
+    pub login: String,
+    pub open_reviews: i64,
+    pub avg_response_secs: Option<f64>,
+}
+
+/// An open PR to find reviewers for
+#[derive(Debug, Clone)]
+pub struct PrToReview {
+    pub pr_id: Uuid,
+    pub author_id: Uuid,
+    pub files: Vec<String>,
+    /// Logins of people who already reviewed it
+    pub reviewers: Vec<String>,
+}
+
+/// Responsiveness from 1 (instant) towards 0, 0.5 if unknown
+pub fn responsiveness(avg_response_secs: Option<f64>) -> f64 {
+    match avg_response_secs {
+        Some(secs) => 1.0 / (1.0 + secs / 3600.0 / RESPONSE_HALF_HOURS),
+        None => 0.5,
+    }
+}
+
+/// Penalty for open reviews, from 0 (none) towards 1
+pub fn load_penalty(open_reviews: i64) -> f64 {
+    let open = open_reviews.max(0) as f64;
+    open / (open + LOAD_HALF)
+}
+
+/// Top `per_pr` suggestions for each PR, handled in the given order (put the
+/// most urgent first)
+pub fn recommend(
+    prs: &[PrToReview],
+    candidates: &[Candidate],
+    expertise: &ExpertiseIndex,
+    author_reviews: &HashMap<(Uuid, Uuid), i64>,
+    per_pr: usize,
+) -> HashMap<Uuid, Vec<ReviewerSuggestion>> {
+    let mut load: HashMap<Uuid, i64> = candidates
+        .iter()
+        .map(|c| (c.user_id, c.open_reviews))
+        .collect();
+    let mut result = HashMap::new();
+
+    for pr in prs {
+        let eligible: Vec<&Candidate> = candidates
+            .iter()
+            .filter(|c| c.user_id != pr.author_id && !pr.reviewers.contains(&c.login))
+            .collect();
+        let known: Vec<f64> = eligible
+            .iter()
+            .map(|c| expertise.files_score(c.user_id, &pr.files))
+            .collect();
+        let reviewed: Vec<i64> = eligible
+            .iter()
+            .map(|c| {
+                author_reviews
+                    .get(&(c.user_id, pr.author_id))
+                    .copied()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let max_known = known.iter().copied().fold(0.0, f64::max);
+        let max_reviewed = reviewed.iter().copied().max().unwrap_or(0);
+
+        let mut suggestions: Vec<(Uuid, ReviewerSuggestion)> = eligible
+            .iter()
+            .zip(known.iter().zip(&reviewed))
+            .map(|(c, (&known, &reviewed))| {
+                let expertise = if max_known > 0.0 {
+                    known / max_known
+                } else {
+                    0.0
+                };
+                let author = if max_reviewed > 0 {
+                    reviewed as f64 / max_reviewed as f64
+                } else {
+                    0.0
+                };
+                let open_reviews = load[&c.user_id];
+                let score = EXPERTISE_WEIGHT * expertise
+                    + AUTHOR_WEIGHT * author
+                    + RESPONSIVENESS_WEIGHT * responsiveness(c.avg_response_secs)
+                    - LOAD_WEIGHT * load_penalty(open_reviews);
+                (
+                    c.user_id,
+                    ReviewerSuggestion {
+                        login: c.login.clone(),
+                        score,
+                        expertise,
+                        open_reviews,
+                        avg_response_hours: c.avg_response_secs.map(|s| s / 3600.0),
+                        author_prs_reviewed: reviewed,
+                    },
+                )
+            })
+            .collect();
+        suggestions.sort_by(|(_, a), (_, b)| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.login.cmp(&b.login))
+        });
+        suggestions.truncate(per_pr);
+
+        if let Some((top, _)) = suggestions.first() {
+            *load.entry(*top).or_insert(0) += 1;
+        }
+        result.insert(pr.pr_id, suggestions.into_iter().map(|(_, s)| s).collect());
+    }
+
+    result
+}
+
+/// Suggestions for a repo's open PRs, keyed by PR id. PRs that are approved
+/// (and not blocked by a change request) get none. Response times are
+/// measured on each candidate's calendar from `calendars`.
+pub async fn recommend_for_prs(
+    pool: &PgPool,
+    repo_id: Uuid,
+    open_prs: &[OpenPrWithStats],
+    calendars: &Calendars,
+    per_pr: usize,
+) -> Result<HashMap<Uuid, Vec<ReviewerSuggestion>>, sqlx::Error> {
+    let mut waiting: Vec<&OpenPrWithStats> = open_prs
+        .iter()
+        .filter(|pr| pr.approvals == 0 || pr.changes_requested > 0)
+        .collect();
+    if waiting.is_empty() {
+        return Ok(HashMap::new());
+    }
+    waiting.sort_by_key(|pr| pr.created_at);
+
+    let now = Utc::now();
+    let since = now - Duration::days(LOOKBACK_DAYS);
+    let pr_ids: Vec<Uuid> = waiting.iter().map(|pr| pr.id).collect();
+    let mut files = db::prs::files_by_pr(pool, &pr_ids).await?;
+
+    let reviewers = db::recommend::candidates(pool, repo_id, since).await?;
+    let ids: Vec<Uuid> = reviewers.iter().map(|(id, _)| *id).collect();
+    let load = db::recommend::open_review_load(pool, &ids).await?;
+    let waits = db::recommend::first_review_waits(pool, repo_id, since).await?;
+    let candidates: Vec<Candidate> = reviewers
+        .into_iter()
+        .map(|(user_id, login)| Candidate {
+            user_id,
+            login,
+            open_reviews: load.get(&user_id).copied().unwrap_or(0),
+            avg_response_secs: waits
+                .get(&user_id)
+                .and_then(|w| latency::average_secs(calendars.for_user(user_id), w)),
+        })
+        .collect();
+
+    let comments =
+        db::expertise::path_comments(pool, None, Some(repo_id), expertise::window_start(now))
+            .await?;
+    let index = ExpertiseIndex::new(&comments, now);
+    let author_reviews = db::recommend::author_reviews(pool, repo_id, since).await?;
+
+    let prs: Vec<PrToReview> = waiting
+        .into_iter()
+        .map(|pr| PrToReview {
+            pr_id: pr.id,
+            author_id: pr.author_id,
+            files: files.remove(&pr.id).unwrap_or_default(),
+            reviewers: pr.reviewers.clone(),
+        })
+        .collect();
+
+    Ok(recommend(
+        &prs,
+        &candidates,
+        &index,
+        &author_reviews,
+        per_pr,
+    ))
+}
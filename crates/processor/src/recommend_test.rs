@@ -0,0 +1,159 @@
+#[cfg(test)]
+mod tests {
+    use crate::expertise::ExpertiseIndex;
+    use crate::recommend::*;
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use db::expertise::PathComment;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 6, 1, 12, 0, 0).unwrap()
+    }
+
+    fn comment(user_id: Uuid, path: &str) -> PathComment {
+        PathComment {
+            user_id,
+            path: path.to_string(),
+            quality_score: None,
+            created_at: now() - Duration::days(1),
+        }
+    }
+
+    fn candidate(login: &str, open_reviews: i64) -> Candidate {
+        Candidate {
+            user_id: Uuid::new_v4(),
+            login: login.to_string(),
+            open_reviews,
+            avg_response_secs: None,
+        }
+    }
+
+    fn pr(author_id: Uuid, files: &[&str]) -> PrToReview {
+        PrToReview {
+            pr_id: Uuid::new_v4(),
+            author_id,
+            files: files.iter().map(|f| f.to_string()).collect(),
+            reviewers: Vec::new(),
+        }
+    }
+
+    fn logins(
+        suggestions: &HashMap<Uuid, Vec<common::models::ReviewerSuggestion>>,
+        pr_id: Uuid,
+    ) -> Vec<String> {
+        suggestions[&pr_id]
+            .iter()
+            .map(|s| s.login.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_responsiveness_and_load_curves() {
+        assert_eq!(responsiveness(None), 0.5);
+        assert_eq!(responsiveness(Some(0.0)), 1.0);
+        assert_eq!(responsiveness(Some(RESPONSE_HALF_HOURS * 3600.0)), 0.5);
+        assert_eq!(load_penalty(0), 0.0);
+        assert_eq!(load_penalty(2), 0.5);
+        assert!(load_penalty(30) < 1.0);
+    }
+
+    #[test]
+    fn test_expert_ranked_first_author_excluded() {
+        let author = candidate("author", 0);
+        let expert = candidate("expert", 0);
+        let other = candidate("other", 0);
+        let index = ExpertiseIndex::new(
+            &[
+                comment(expert.user_id, "src/db/users.rs"),
+                comment(expert.user_id, "src/db/prs.rs"),
+                comment(author.user_id, "src/db/users.rs"),
+            ],
+            now(),
+        );
+        let pr = pr(author.user_id, &["src/db/repos.rs"]);
+
+        let result = recommend(
+            std::slice::from_ref(&pr),
+            &[author, other, expert],
+            &index,
+            &HashMap::new(),
+            3,
+        );
+
+        assert_eq!(logins(&result, pr.pr_id), vec!["expert", "other"]);
+        assert_eq!(result[&pr.pr_id][0].expertise, 1.0);
+        assert_eq!(result[&pr.pr_id][1].expertise, 0.0);
+    }
+
+    #[test]
+    fn test_existing_reviewers_excluded() {
+        let alice = candidate("alice", 0);
+        let bob = candidate("bob", 0);
+        let mut pr = pr(Uuid::new_v4(), &["a.rs"]);
+        pr.reviewers = vec!["alice".to_string()];
+
+        let result = recommend(
+            std::slice::from_ref(&pr),
+            &[alice, bob],
+            &ExpertiseIndex::new(&[], now()),
+            &HashMap::new(),
+            3,
+        );
+
+        assert_eq!(logins(&result, pr.pr_id), vec!["bob"]);
+    }
+
+    #[test]
+    fn test_load_and_author_history_shift_ranking() {
+        let author = Uuid::new_v4();
+        let busy = candidate("busy", 6);
+        let mut regular = candidate("regular", 0);
+        regular.avg_response_secs = Some(3600.0);
+        let mut author_reviews = HashMap::new();
+        author_reviews.insert((regular.user_id, author), 4);
+        let pr = pr(author, &["a.rs"]);
+
+        let result = recommend(
+            std::slice::from_ref(&pr),
+            &[busy, regular],
+            &ExpertiseIndex::new(&[], now()),
+            &author_reviews,
+            3,
+        );
+
+        let picks = &result[&pr.pr_id];
+        assert_eq!(picks[0].login, "regular");
+        assert_eq!(picks[0].author_prs_reviewed, 4);
+        assert_eq!(picks[0].avg_response_hours, Some(1.0));
+        assert_eq!(picks[1].open_reviews, 6);
+        assert!(picks[0].score > picks[1].score);
+    }
+
+    #[test]
+    fn test_top_picks_spread_across_prs() {
+        let author = Uuid::new_v4();
+        let alice = candidate("alice", 0);
+        let bob = candidate("bob", 0);
+        let index = ExpertiseIndex::new(
+            &[
+                comment(alice.user_id, "src/a.rs"),
+                comment(alice.user_id, "src/a.rs"),
+                comment(bob.user_id, "src/c.rs"),
+            ],
+            now(),
+        );
+        let prs: Vec<PrToReview> = (0..3).map(|_| pr(author, &["src/b.rs"])).collect();
+
+        let result = recommend(&prs, &[alice, bob], &index, &HashMap::new(), 1);
+
+        // alice knows the code best so takes the first PRs; once she has two
+        // open reviews bob, who knows it too, comes out ahead
+        let tops: Vec<String> = prs
+            .iter()
+            .map(|p| logins(&result, p.pr_id)[0].clone())
+            .collect();
+        assert_eq!(tops, vec!["alice", "alice", "bob"]);
+        assert_eq!(result[&prs[1].pr_id][0].open_reviews, 1);
+    }
+}
@@ -0,0 +1,76 @@
+//! Author responsiveness scoring
+//!
+//! Reviewers earn XP; this scores the other side of the review. Authors get
+//! points for each change request they push a fix for (more the sooner they
+//! push) and for each review thread they reply to. The score only ranks the
+//! responsiveness leaderboard and is not credited as XP.
+
+use common::models::ResponsivenessEntry;
+use db::responsiveness::{AuthorResponseData, QUICK_FIX_HOURS};
+
+#[cfg(test)]
+#[path = "responsiveness_test.rs"]
+mod responsiveness_test;
+
+/// Points for a replied review thread
+pub const THREAD_REPLY_POINTS: i64 = 2;
+
+/// Points for a change request addressed after `hours`
+pub fn fix_points(hours: f64) -> i64 {
+    if hours <= 4.0 {
+        10
+    } else if hours <= QUICK_FIX_HOURS {
+        6
+    } else if hours <= 72.0 {
+        3
+    } else {
+        1
+    }
+}
+
+/// Median of the given values, None if empty
+pub fn median(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    })
+}
+
+/// Score one author (rank is filled in by `rank`)
+pub fn score(data: AuthorResponseData) -> ResponsivenessEntry {
+    let mut addressed: Vec<f64> = data.response_hours.iter().flatten().copied().collect();
+    let fix_score: i64 = addressed.iter().map(|h| fix_points(*h)).sum();
+
+    ResponsivenessEntry {
+        rank: 0,
+        score: fix_score + data.threads_replied * THREAD_REPLY_POINTS,
+        change_requests: data.response_hours.len() as i32,
+        change_requests_addressed: addressed.len() as i32,
+        median_response_hours: median(&mut addressed),
+        threads: data.threads as i32,
+        threads_replied: data.threads_replied as i32,
+        reply_rate: (data.threads > 0).then(|| data.threads_replied as f64 / data.threads as f64),
+        user: data.user,
+    }
+}
+
+/// Score and rank authors, best first, keeping the top `limit`
+pub fn rank(data: Vec<AuthorResponseData>, limit: usize) -> Vec<ResponsivenessEntry> {
+    let mut entries: Vec<ResponsivenessEntry> = data.into_iter().map(score).collect();
+    entries.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.user.login.cmp(&b.user.login))
+    });
+    entries.truncate(limit);
+    for (idx, entry) in entries.iter_mut().enumerate() {
+        entry.rank = (idx + 1) as i32;
+    }
+    entries
+}
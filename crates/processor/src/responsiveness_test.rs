@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod tests {
+    use crate::responsiveness::*;
+    use chrono::Utc;
+    use common::models::User;
+    use db::responsiveness::AuthorResponseData;
+    use uuid::Uuid;
+
+    fn make_data(
+        login: &str,
+        response_hours: Vec<Option<f64>>,
+        threads: i64,
+        threads_replied: i64,
+    ) -> AuthorResponseData {
+        AuthorResponseData {
+            user: User {
+                id: Uuid::new_v4(),
+                github_id: 1,
+                login: login.to_string(),
+                avatar_url: None,
+                xp: 0,
+                level: 1,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            },
+            response_hours,
+            threads,
+            threads_replied,
+        }
+    }
+
+    #[test]
+    fn test_fix_points_by_speed() {
+        assert_eq!(fix_points(0.5), 10);
+        assert_eq!(fix_points(4.0), 10);
+        assert_eq!(fix_points(12.0), 6);
+        assert_eq!(fix_points(48.0), 3);
+        assert_eq!(fix_points(200.0), 1);
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&mut []), None);
+        assert_eq!(median(&mut [5.0, 1.0, 3.0]), Some(3.0));
+        assert_eq!(median(&mut [4.0, 1.0, 2.0, 3.0]), Some(2.5));
+    }
+
+    #[test]
+    fn test_score_ignores_unaddressed_requests() {
+        let entry = score(make_data("alice", vec![Some(2.0), None, Some(30.0)], 4, 3));
+
+        assert_eq!(entry.change_requests, 3);
+        assert_eq!(entry.change_requests_addressed, 2);
+        assert_eq!(entry.score, 10 + 3 + 3 * THREAD_REPLY_POINTS);
+        assert_eq!(entry.median_response_hours, Some(16.0));
+        assert_eq!(entry.reply_rate, Some(0.75));
+    }
+
+    #[test]
+    fn test_score_without_threads_has_no_reply_rate() {
+        let entry = score(make_data("bob", vec![None], 0, 0));
+
+        assert_eq!(entry.score, 0);
+        assert_eq!(entry.median_response_hours, None);
+        assert_eq!(entry.reply_rate, None);
+    }
+
+    #[test]
+    fn test_rank_orders_by_score_then_login() {
+        let entries = rank(
+            vec![
+                make_data("carol", vec![Some(100.0)], 0, 0),
+                make_data("bob", vec![Some(1.0)], 0, 0),
+                make_data("alice", vec![Some(2.0)], 0, 0),
+            ],
+            2,
+        );
+
+        let logins: Vec<&str> = entries.iter().map(|e| e.user.login.as_str()).collect();
+        assert_eq!(logins, vec!["alice", "bob"]);
+        assert_eq!(entries[0].rank, 1);
+        assert_eq!(entries[1].rank, 2);
+    }
+}
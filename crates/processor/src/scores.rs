@@ -1,9 +1,10 @@
 //! XP and score calculation
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use common::models::PullRequest;
 use sqlx::PgPool;
 
+use crate::latency::{self, Calendar};
 use crate::metrics;
 
 /// Calculates XP and scores
@@ -36,7 +37,12 @@ impl ScoreCalculator {
         }
 
         // Bonus for fast review (under 1 hour)
-        if metrics::is_fast_review(pr.created_at, review_submitted) {
+        if latency::is_fast(
+            &Calendar::default(),
+            pr.created_at,
+            review_submitted,
+            Duration::hours(1),
+        ) {
             xp += 10;
         }
 
@@ -1,14 +1,21 @@
 //! Review session grouping logic
 
 use chrono::{DateTime, Duration, Utc};
-use common::models::{Commit, Review};
+use common::models::{Commit, PrSize, Review, ScoringWeights, XpBreakdown};
 use db::review_comments::CommentQualityData;
+use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::latency::{self, Calendar};
+
 #[cfg(test)]
 #[path = "sessions_test.rs"]
 mod sessions_test;
 
+/// Version of the session XP formula, recorded on every XP ledger entry.
+/// Bump when the scoring code (not just profile weights) changes.
+pub const XP_RULES_VERSION: i32 = 6;
+
 /// A grouped review session
 #[derive(Debug, Clone)]
 pub struct ReviewSession {
@@ -24,6 +31,15 @@ pub struct ReviewSession {
 pub fn group_reviews_into_sessions(
     reviews: Vec<Review>,
     commits: Vec<Commit>,
+) -> Vec<ReviewSession> {
+    group_reviews_into_sessions_with_gap(reviews, commits, Duration::hours(24))
+}
+
+/// Group reviews into sessions, starting a new session after `max_gap` of inactivity
+pub fn group_reviews_into_sessions_with_gap(
+    reviews: Vec<Review>,
+    commits: Vec<Commit>,
+    max_gap: Duration,
 ) -> Vec<ReviewSession> {
     if reviews.is_empty() {
         return Vec::new();
@@ -43,9 +59,9 @@ pub fn group_reviews_into_sessions(
 
     for review in sorted_reviews {
         let should_start_new_session = if let Some(last_time) = last_review_time {
-            // Check 24-hour gap
+            // Check time gap (24 hours by default)
             let time_gap = review.submitted_at.signed_duration_since(last_time);
-            if time_gap > Duration::hours(24) {
+            if time_gap > max_gap {
                 true
             } else {
                 // Check if commits pushed between last review and this one
@@ -82,6 +98,46 @@ pub fn group_reviews_into_sessions(
     sessions
 }
 
+/// Sessions per (PR, reviewer), grouped as for XP
+pub fn sessions_by_pair(
+    reviews: Vec<Review>,
+    commits: &[Commit],
+    max_gap: Duration,
+) -> HashMap<(Uuid, Uuid), Vec<ReviewSession>> {
+    let mut groups: HashMap<(Uuid, Uuid), Vec<Review>> = HashMap::new();
+    for review in reviews {
+        groups
+            .entry((review.pr_id, review.reviewer_id))
+            .or_default()
+            .push(review);
+    }
+
+    groups
+        .into_iter()
+        .map(|(key, pr_reviews)| {
+            let pr_commits: Vec<Commit> = commits
+                .iter()
+                .filter(|c| c.pr_id == key.0)
+                .cloned()
+                .collect();
+            let sessions = group_reviews_into_sessions_with_gap(pr_reviews, pr_commits, max_gap);
+            (key, sessions)
+        })
+        .collect()
+}
+
+/// Number of sessions per (PR, reviewer), grouped as for XP
+pub fn count_sessions(
+    reviews: Vec<Review>,
+    commits: &[Commit],
+    max_gap: Duration,
+) -> HashMap<(Uuid, Uuid), i64> {
+    sessions_by_pair(reviews, commits, max_gap)
+        .into_iter()
+        .map(|(key, sessions)| (key, sessions.len() as i64))
+        .collect()
+}
+
 fn finalize_session(reviews: Vec<Review>) -> Option<ReviewSession> {
     if reviews.is_empty() {
         return None;
@@ -104,14 +160,12 @@ fn finalize_session(reviews: Vec<Review>) -> Option<ReviewSession> {
 }
 
 /// Calculate XP for a review session (legacy, no quality data)
-pub fn calculate_session_xp(
-    session: &ReviewSession,
-    commit_before_session: Option<DateTime<Utc>>,
-) -> i64 {
-    calculate_session_xp_with_quality(session, commit_before_session, None)
+pub fn calculate_session_xp(session: &ReviewSession, ready_at: Option<DateTime<Utc>>) -> i64 {
+    calculate_session_xp_with_quality(session, ready_at, None)
 }
 
-/// Calculate XP for a review session with quality-weighted bonuses
+/// Calculate XP for a review session with quality-weighted bonuses, using the
+/// default weights
 ///
 /// Quality bonus formula:
 /// - Low quality (1-3): +2 XP per comment (reduced from +5)
@@ -126,70 +180,175 @@ pub fn calculate_session_xp(
 /// Uncategorized comments: use flat +5 XP (standard rate)
 pub fn calculate_session_xp_with_quality(
     session: &ReviewSession,
-    commit_before_session: Option<DateTime<Utc>>,
+    ready_at: Option<DateTime<Utc>>,
     quality_data: Option<&CommentQualityData>,
 ) -> i64 {
-    // Check minimum threshold: at least 1 comment or state change
-    let has_state_change = session.reviews.iter().any(|r| {
-        r.state == common::models::ReviewState::Approved
-            || r.state == common::models::ReviewState::ChangesRequested
-    });
+    calculate_session_xp_with_weights(session, ready_at, quality_data, &ScoringWeights::default())
+}
+
+/// Calculate XP for a review session using a repo's scoring profile weights
+///
+/// Same formula as [`calculate_session_xp_with_quality`], with every constant
+/// taken from `weights`. Comments shorter than `min_comment_chars` (counted in
+/// `quality_data.short_count`) earn no comment XP.
+pub fn calculate_session_xp_with_weights(
+    session: &ReviewSession,
+    ready_at: Option<DateTime<Utc>>,
+    quality_data: Option<&CommentQualityData>,
+    weights: &ScoringWeights,
+) -> i64 {
+    session_xp_breakdown(
+        session,
+        ready_at,
+        quality_data,
+        None,
+        weights,
+        &Calendar::default(),
+    )
+    .total()
+}
 
-    if session.total_comments == 0 && !has_state_change {
+/// Itemized XP for a review session (what the XP ledger records)
+///
+/// `ready_at` is when the PR became ready for this session (see
+/// `latency::ready_at`); the fast bonus counts working time since then on the
+/// reviewer's `calendar`. Base and comment XP are scaled by the PR's
+/// [`difficulty_pct`], unscaled if its `size` isn't known.
+pub fn session_xp_breakdown(
+    session: &ReviewSession,
+    ready_at: Option<DateTime<Utc>>,
+    quality_data: Option<&CommentQualityData>,
+    size: Option<&PrSize>,
+    weights: &ScoringWeights,
+    calendar: &Calendar,
+) -> XpBreakdown {
+    // Check minimum threshold: at least 1 comment or state change
+    if session.total_comments == 0 && !has_state_change(session) {
         // Rubber stamp - no credit
-        return 0;
+        return XpBreakdown::default();
     }
 
     // Check for quick approval (< 1 min, 0 comments) = rubber stamp
-    if has_state_change
-        && session.total_comments == 0
-        && session.ended_at.signed_duration_since(session.started_at) < Duration::minutes(1)
-    {
-        return 0;
+    if is_rubber_stamp(session) {
+        return XpBreakdown::default();
     }
 
-    let mut xp: i64 = 10; // Base XP
+    let mut xp = XpBreakdown {
+        base: weights.base_xp,
+        ..Default::default()
+    };
 
     // Calculate comment XP with quality weighting
     if let Some(qd) = quality_data {
         let (low, medium, high) = qd.by_tier;
         let (logic, structural, _other) = qd.by_category;
         let categorized = qd.categorized_count;
-        let uncategorized = session.total_comments - categorized;
+        // Quality data and the session's comment count can disagree (comments
+        // synced separately from their review); never score negative comments
+        let uncategorized = (session.total_comments - categorized - qd.short_count).max(0);
 
-        // Quality-weighted XP
-        xp += low as i64 * 2; // Low quality: +2 each
-        xp += medium as i64 * 5; // Medium quality: +5 each
-        xp += high as i64 * 8; // High quality: +8 each
+        // Quality-weighted XP per tier
+        xp.comments_low = low * weights.low_quality_xp;
+        xp.comments_medium = medium * weights.medium_quality_xp;
+        xp.comments_high = high * weights.high_quality_xp;
 
-        // Uncategorized comments at standard rate
-        xp += uncategorized as i64 * 5;
+        // Uncategorized comments at the flat rate
+        xp.comments_uncategorized = uncategorized * weights.uncategorized_xp;
 
         // Category bonuses (on top of quality XP)
-        xp += logic as i64 * 3; // Logic bugs: +3 bonus
-        xp += structural as i64 * 2; // Structural: +2 bonus
+        xp.logic_bonus = logic * weights.logic_bonus;
+        xp.structural_bonus = structural * weights.structural_bonus;
+
+        // Comments a later commit acted on
+        xp.addressed_bonus = qd.addressed_count * weights.addressed_bonus;
     } else {
         // No quality data - use flat rate
-        xp += session.total_comments as i64 * 5;
+        xp.comments_uncategorized = session.total_comments * weights.uncategorized_xp;
     }
 
-    // Fast review: +10 XP if reviewed <1 hour after commits pushed
-    if let Some(commit_time) = commit_before_session {
-        let review_delay = session.started_at.signed_duration_since(commit_time);
-        if review_delay < Duration::hours(1) && review_delay > Duration::seconds(0) {
-            xp += 10;
+    // Difficulty: bigger, wider PRs scale base and comment XP, up to the cap
+    let scaled = xp.base
+        + xp.comments_low
+        + xp.comments_medium
+        + xp.comments_high
+        + xp.comments_uncategorized;
+    xp.difficulty_pct = difficulty_pct(size, weights);
+    xp.difficulty_bonus = (scaled * (xp.difficulty_pct - 100) + 50) / 100;
+
+    // Fast review: reviewed within `fast_review_minutes` of working time after
+    // the PR was opened or pushed
+    if let Some(ready_at) = ready_at {
+        let limit = Duration::minutes(weights.fast_review_minutes as i64);
+        if latency::is_fast(calendar, ready_at, session.started_at, limit) {
+            xp.fast_bonus = weights.fast_review_bonus;
         }
     }
 
-    // Thorough: +5 XP if >5 comments
-    if session.total_comments > 5 {
-        xp += 5;
+    // Comments shorter than `min_comment_chars` don't count towards depth
+    let counted_comments = match quality_data {
+        Some(qd) => (session.total_comments - qd.short_count).max(0),
+        None => session.total_comments,
+    };
+
+    // Thorough: more than `thorough_threshold` comments
+    if counted_comments > weights.thorough_threshold {
+        xp.thorough_bonus = weights.thorough_bonus;
     }
 
-    // Deep review: +10 XP if >10 comments
-    if session.total_comments > 10 {
-        xp += 10;
+    // Deep review: more than `deep_threshold` comments
+    if counted_comments > weights.deep_threshold {
+        xp.deep_bonus = weights.deep_bonus;
     }
 
     xp
 }
+
+/// Quality data for the comments submitted with a session's reviews
+/// (`quality` is keyed by review), None if none of them has any
+pub fn session_quality(
+    session: &ReviewSession,
+    quality: &HashMap<Uuid, CommentQualityData>,
+) -> Option<CommentQualityData> {
+    let mut reviews = session.reviews.iter().filter_map(|r| quality.get(&r.id));
+    let mut data = reviews.next()?.clone();
+    for other in reviews {
+        data.add(other);
+    }
+    Some(data)
+}
+
+fn has_state_change(session: &ReviewSession) -> bool {
+    session.reviews.iter().any(|r| {
+        r.state == common::models::ReviewState::Approved
+            || r.state == common::models::ReviewState::ChangesRequested
+    })
+}
+
+/// A verdict (approval or change request) with no comments, all within a
+/// minute. Earns no XP.
+pub fn is_rubber_stamp(session: &ReviewSession) -> bool {
+    has_state_change(session)
+        && session.total_comments == 0
+        && session.ended_at.signed_duration_since(session.started_at) < Duration::minutes(1)
+}
+
+/// Difficulty multiplier of a PR, in percent
+///
+/// Half comes from lines changed, half from files changed, each capped at
+/// `difficulty_lines` / `difficulty_files`; both maxed out give
+/// `difficulty_max_pct`. The per-half caps keep huge generated changes (a
+/// lockfile, a vendored dependency) from earning more than a real change
+/// across many files. PRs of unknown size get 100.
+pub fn difficulty_pct(size: Option<&PrSize>, weights: &ScoringWeights) -> i32 {
+    let Some(size) = size else {
+        return 100;
+    };
+    let max_lines = weights.difficulty_lines.max(1) as i64;
+    let max_files = weights.difficulty_files.max(1) as i64;
+    let lines = size.lines_changed().clamp(0, max_lines);
+    let files = (size.changed_files as i64).clamp(0, max_files);
+
+    let headroom = (weights.difficulty_max_pct - 100).max(0) as i64;
+    let extra = headroom * (lines * max_files + files * max_lines) / (2 * max_lines * max_files);
+    100 + extra as i32
+}
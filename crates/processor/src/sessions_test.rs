@@ -1,11 +1,13 @@
 #[cfg(test)]
 mod tests {
+    use crate::latency::Calendar;
     use crate::sessions::{
-        calculate_session_xp, calculate_session_xp_with_quality, group_reviews_into_sessions,
-        ReviewSession,
+        calculate_session_xp, calculate_session_xp_with_quality, calculate_session_xp_with_weights,
+        difficulty_pct, group_reviews_into_sessions, group_reviews_into_sessions_with_gap,
+        session_xp_breakdown, ReviewSession,
     };
     use chrono::{DateTime, TimeZone, Utc};
-    use common::models::{Commit, Review, ReviewState};
+    use common::models::{Commit, PrSize, Review, ReviewState, ScoringWeights};
     use db::review_comments::CommentQualityData;
     use uuid::Uuid;
 
@@ -471,6 +473,8 @@ mod tests {
             by_tier: (0, 0, 5),     // (low, medium, high)
             by_category: (2, 1, 2), // (logic, structural, other)
             categorized_count: 5,
+            short_count: 0,
+            addressed_count: 0,
         };
 
         let xp = calculate_session_xp_with_quality(&session, None, Some(&quality_data));
@@ -481,6 +485,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_xp_addressed_comments_bonus() {
+        let pr_id = Uuid::new_v4();
+        let reviewer_id = Uuid::new_v4();
+
+        let session = ReviewSession {
+            pr_id,
+            reviewer_id,
+            reviews: vec![make_review(
+                pr_id,
+                reviewer_id,
+                Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap(),
+                ReviewState::ChangesRequested,
+                3,
+            )],
+            started_at: Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap(),
+            ended_at: Utc.with_ymd_and_hms(2026, 1, 1, 10, 30, 0).unwrap(),
+            total_comments: 3,
+        };
+
+        // 2 of the 3 medium-quality comments were fixed by a later commit
+        let quality_data = CommentQualityData {
+            by_tier: (0, 3, 0),
+            by_category: (0, 0, 3),
+            categorized_count: 3,
+            short_count: 0,
+            addressed_count: 2,
+        };
+
+        let xp = session_xp_breakdown(
+            &session,
+            None,
+            Some(&quality_data),
+            None,
+            &ScoringWeights::default(),
+            &Calendar::default(),
+        );
+        assert_eq!(xp.addressed_bonus, 10);
+        // 10 base + 3*5 medium + 2*5 addressed = 35 XP
+        assert_eq!(xp.total(), 35);
+    }
+
     #[test]
     fn test_xp_quality_weighted_mixed() {
         let pr_id = Uuid::new_v4();
@@ -506,6 +552,8 @@ mod tests {
             by_tier: (2, 3, 3),     // (low, medium, high)
             by_category: (1, 0, 7), // (logic, structural, other)
             categorized_count: 8,
+            short_count: 0,
+            addressed_count: 0,
         };
 
         let xp = calculate_session_xp_with_quality(&session, None, Some(&quality_data));
@@ -538,6 +586,8 @@ mod tests {
             by_tier: (3, 0, 0),     // (low, medium, high)
             by_category: (0, 0, 3), // (logic, structural, other)
             categorized_count: 3,
+            short_count: 0,
+            addressed_count: 0,
         };
 
         let xp_with_quality =
@@ -642,6 +692,8 @@ mod tests {
             by_tier: (1, 2, 1),     // (low, medium, high) = 4 total
             by_category: (1, 0, 3), // (logic, structural, other) = 4 total
             categorized_count: 4,
+            short_count: 0,
+            addressed_count: 0,
         };
 
         let xp = calculate_session_xp_with_quality(&session, None, Some(&quality_data));
@@ -649,4 +701,336 @@ mod tests {
         // = 10 + 2 + 10 + 8 + 10 + 3 + 5 = 48 XP
         assert_eq!(xp, 48, "4 categorized + 2 uncategorized = 48 XP");
     }
+
+    #[test]
+    fn test_short_comments_earn_no_comment_xp() {
+        let pr_id = Uuid::new_v4();
+        let reviewer_id = Uuid::new_v4();
+
+        let session = ReviewSession {
+            pr_id,
+            reviewer_id,
+            reviews: vec![make_review(
+                pr_id,
+                reviewer_id,
+                Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap(),
+                ReviewState::Commented,
+                3,
+            )],
+            started_at: Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap(),
+            ended_at: Utc.with_ymd_and_hms(2026, 1, 1, 10, 30, 0).unwrap(),
+            total_comments: 3,
+        };
+
+        // 2 of 3 comments are below min_comment_chars ("nit", "+1")
+        let quality_data = CommentQualityData {
+            short_count: 2,
+            addressed_count: 0,
+            ..Default::default()
+        };
+
+        let xp = calculate_session_xp_with_quality(&session, None, Some(&quality_data));
+        // 10 base + 1 uncategorized * 5
+        assert_eq!(xp, 15);
+    }
+
+    #[test]
+    fn test_short_comments_do_not_earn_depth_bonuses() {
+        let pr_id = Uuid::new_v4();
+        let reviewer_id = Uuid::new_v4();
+
+        let session = ReviewSession {
+            pr_id,
+            reviewer_id,
+            reviews: vec![make_review(
+                pr_id,
+                reviewer_id,
+                Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap(),
+                ReviewState::Commented,
+                12,
+            )],
+            started_at: Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap(),
+            ended_at: Utc.with_ymd_and_hms(2026, 1, 1, 10, 30, 0).unwrap(),
+            total_comments: 12,
+        };
+        let weights = ScoringWeights::default();
+        let score = |quality_data: &CommentQualityData| {
+            session_xp_breakdown(
+                &session,
+                None,
+                Some(quality_data),
+                None,
+                &weights,
+                &Calendar::default(),
+            )
+        };
+
+        // 12 comments, 10 of them "nit"/"+1": only 2 count
+        let mostly_short = score(&CommentQualityData {
+            short_count: 10,
+            ..Default::default()
+        });
+        assert_eq!(mostly_short.thorough_bonus, 0);
+        assert_eq!(mostly_short.deep_bonus, 0);
+
+        // 12 comments, 4 short: 8 count, thorough but not deep
+        let some_short = score(&CommentQualityData {
+            short_count: 4,
+            ..Default::default()
+        });
+        assert_eq!(some_short.thorough_bonus, weights.thorough_bonus);
+        assert_eq!(some_short.deep_bonus, 0);
+    }
+
+    #[test]
+    fn test_quality_data_exceeding_session_comments_never_negative() {
+        let pr_id = Uuid::new_v4();
+        let reviewer_id = Uuid::new_v4();
+
+        let session = ReviewSession {
+            pr_id,
+            reviewer_id,
+            reviews: vec![make_review(
+                pr_id,
+                reviewer_id,
+                Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap(),
+                ReviewState::Commented,
+                1,
+            )],
+            started_at: Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap(),
+            ended_at: Utc.with_ymd_and_hms(2026, 1, 1, 10, 30, 0).unwrap(),
+            total_comments: 1,
+        };
+
+        // More comments in the quality data than in the session
+        let quality_data = CommentQualityData {
+            by_tier: (0, 2, 0),
+            categorized_count: 2,
+            short_count: 1,
+            ..Default::default()
+        };
+
+        let xp = session_xp_breakdown(
+            &session,
+            None,
+            Some(&quality_data),
+            None,
+            &ScoringWeights::default(),
+            &Calendar::default(),
+        );
+        assert_eq!(xp.comments_uncategorized, 0);
+        // 10 base + 2 medium * 5
+        assert_eq!(xp.total(), 20);
+    }
+
+    #[test]
+    fn test_custom_weights() {
+        let pr_id = Uuid::new_v4();
+        let reviewer_id = Uuid::new_v4();
+        let commit_time = Utc.with_ymd_and_hms(2026, 1, 1, 8, 0, 0).unwrap();
+
+        let session = ReviewSession {
+            pr_id,
+            reviewer_id,
+            reviews: vec![make_review(
+                pr_id,
+                reviewer_id,
+                Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap(),
+                ReviewState::Commented,
+                4,
+            )],
+            started_at: Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap(),
+            ended_at: Utc.with_ymd_and_hms(2026, 1, 1, 10, 30, 0).unwrap(),
+            total_comments: 4,
+        };
+
+        let weights = ScoringWeights {
+            base_xp: 20,
+            uncategorized_xp: 1,
+            fast_review_minutes: 180,
+            thorough_threshold: 3,
+            ..Default::default()
+        };
+
+        // Default: 10 base + 4*5, commit 2h before = not fast, 4 comments = not thorough
+        assert_eq!(calculate_session_xp(&session, Some(commit_time)), 30);
+        // Custom: 20 base + 4*1 + 10 fast (within 3h) + 5 thorough (>3)
+        let xp = calculate_session_xp_with_weights(&session, Some(commit_time), None, &weights);
+        assert_eq!(xp, 39);
+    }
+
+    #[test]
+    fn test_custom_session_gap() {
+        let pr_id = Uuid::new_v4();
+        let reviewer_id = Uuid::new_v4();
+
+        let reviews = vec![
+            make_review(
+                pr_id,
+                reviewer_id,
+                Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap(),
+                ReviewState::Commented,
+                1,
+            ),
+            make_review(
+                pr_id,
+                reviewer_id,
+                Utc.with_ymd_and_hms(2026, 1, 1, 18, 0, 0).unwrap(),
+                ReviewState::Commented,
+                1,
+            ),
+        ];
+
+        assert_eq!(
+            group_reviews_into_sessions(reviews.clone(), vec![]).len(),
+            1
+        );
+        assert_eq!(
+            group_reviews_into_sessions_with_gap(reviews, vec![], chrono::Duration::hours(4)).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_session_xp_breakdown_itemized() {
+        let pr_id = Uuid::new_v4();
+        let reviewer_id = Uuid::new_v4();
+        let commit_time = Utc.with_ymd_and_hms(2026, 1, 1, 9, 30, 0).unwrap();
+
+        let session = ReviewSession {
+            pr_id,
+            reviewer_id,
+            reviews: vec![make_review(
+                pr_id,
+                reviewer_id,
+                Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap(),
+                ReviewState::ChangesRequested,
+                6,
+            )],
+            started_at: Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap(),
+            ended_at: Utc.with_ymd_and_hms(2026, 1, 1, 10, 30, 0).unwrap(),
+            total_comments: 6,
+        };
+
+        let quality_data = CommentQualityData {
+            by_tier: (1, 2, 1),
+            by_category: (1, 1, 2),
+            categorized_count: 4,
+            short_count: 0,
+            addressed_count: 0,
+        };
+
+        let weights = ScoringWeights::default();
+        let xp = session_xp_breakdown(
+            &session,
+            Some(commit_time),
+            Some(&quality_data),
+            None,
+            &weights,
+            &Calendar::default(),
+        );
+
+        assert_eq!(xp.base, 10);
+        assert_eq!(xp.comments_low, 2);
+        assert_eq!(xp.comments_medium, 10);
+        assert_eq!(xp.comments_high, 8);
+        assert_eq!(xp.comments_uncategorized, 10);
+        assert_eq!(xp.logic_bonus, 3);
+        assert_eq!(xp.structural_bonus, 2);
+        assert_eq!(xp.fast_bonus, 10);
+        assert_eq!(xp.thorough_bonus, 5);
+        assert_eq!(xp.deep_bonus, 0);
+        assert_eq!(xp.difficulty_pct, 100);
+        assert_eq!(xp.difficulty_bonus, 0);
+        assert_eq!(xp.achievement, 0);
+        assert_eq!(
+            xp.total(),
+            calculate_session_xp_with_weights(
+                &session,
+                Some(commit_time),
+                Some(&quality_data),
+                &weights
+            )
+        );
+        assert_eq!(xp.total(), 60);
+    }
+
+    fn size(lines: i32, files: i32) -> PrSize {
+        PrSize {
+            additions: lines,
+            deletions: 0,
+            changed_files: files,
+        }
+    }
+
+    #[test]
+    fn test_difficulty_pct_scales_with_size() {
+        let weights = ScoringWeights::default();
+
+        assert_eq!(difficulty_pct(None, &weights), 100);
+        assert_eq!(difficulty_pct(Some(&size(0, 0)), &weights), 100);
+        // Half the lines and half the files: half the headroom
+        assert_eq!(difficulty_pct(Some(&size(250, 10)), &weights), 125);
+        assert_eq!(difficulty_pct(Some(&size(500, 20)), &weights), 150);
+    }
+
+    #[test]
+    fn test_difficulty_pct_caps_generated_changes() {
+        let weights = ScoringWeights::default();
+
+        // A 50k line lockfile only maxes out the size half
+        assert_eq!(difficulty_pct(Some(&size(50_000, 1)), &weights), 126);
+        // Nothing goes past the cap
+        assert_eq!(difficulty_pct(Some(&size(50_000, 5_000)), &weights), 150);
+
+        let off = ScoringWeights {
+            difficulty_max_pct: 100,
+            ..Default::default()
+        };
+        assert_eq!(difficulty_pct(Some(&size(50_000, 5_000)), &off), 100);
+    }
+
+    #[test]
+    fn test_session_xp_difficulty_scales_base_and_comments() {
+        let pr_id = Uuid::new_v4();
+        let reviewer_id = Uuid::new_v4();
+        let at = Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap();
+
+        let session = ReviewSession {
+            pr_id,
+            reviewer_id,
+            reviews: vec![make_review(
+                pr_id,
+                reviewer_id,
+                at,
+                ReviewState::Commented,
+                2,
+            )],
+            started_at: at,
+            ended_at: at,
+            total_comments: 2,
+        };
+
+        // 2 logic comments, both addressed
+        let quality_data = CommentQualityData {
+            by_tier: (0, 2, 0),
+            by_category: (2, 0, 0),
+            categorized_count: 2,
+            short_count: 0,
+            addressed_count: 2,
+        };
+
+        let xp = session_xp_breakdown(
+            &session,
+            None,
+            Some(&quality_data),
+            Some(&size(500, 20)),
+            &ScoringWeights::default(),
+            &Calendar::default(),
+        );
+        assert_eq!(xp.difficulty_pct, 150);
+        // Half of 10 base + 2*5 medium; logic and addressed bonuses unscaled
+        assert_eq!(xp.difficulty_bonus, 10);
+        assert_eq!(xp.total(), 10 + 10 + 10 + 2 * 3 + 2 * 5);
+    }
 }
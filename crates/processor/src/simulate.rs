@@ -0,0 +1,299 @@
+//! Scoring what-if simulator
+//!
+//! Rescores every (PR, reviewer) pair in memory with an alternative
+//! configuration and reports how XP and ranks would move from the XP users
+//! currently hold. Achievement rewards don't depend on scoring and count the
+//! same on both sides. Nothing is written.
+
+use common::models::ScoringWeights;
+use db::prs::PrRef;
+use db::recalc::StoredSession;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::latency::Calendars;
+use crate::recalculate::{apply_exclusions, score_pairs, PairScore, SessionScore};
+
+#[cfg(test)]
+#[path = "simulate_test.rs"]
+mod simulate_test;
+
+/// One user's XP and rank under the current and the simulated scoring
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UserXpChange {
+    pub user_id: Uuid,
+    pub login: String,
+    pub old_xp: i64,
+    pub new_xp: i64,
+    pub xp_delta: i64,
+    pub old_rank: usize,
+    pub new_rank: usize,
+    /// Positive when the user moves up the leaderboard
+    pub rank_change: i64,
+}
+
+/// A session whose score differs between current and simulated scoring
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SessionXpChange {
+    /// First review of the session
+    pub review_id: Uuid,
+    pub pr_id: Uuid,
+    pub reviewer_id: Uuid,
+    pub login: String,
+    #[serde(flatten)]
+    pub pr: Option<PrRef>,
+    pub old_xp: i64,
+    pub new_xp: i64,
+    pub xp_delta: i64,
+}
+
+/// Result of a dry-run recalculation
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SimulationReport {
+    pub total_xp_before: i64,
+    pub total_xp_after: i64,
+    /// Users whose rank changes
+    pub users_reranked: usize,
+    /// Sessions whose score changes (before truncating `sessions`)
+    pub sessions_changed: usize,
+    /// Every reviewer, ordered by simulated rank
+    pub users: Vec<UserXpChange>,
+    /// Largest absolute score changes first
+    pub sessions: Vec<SessionXpChange>,
+}
+
+/// Score everything with `adjust` applied to each PR's weights and compare
+/// with the session XP currently stored. Nothing is written to the database.
+pub async fn simulate_recalculation(
+    pool: &PgPool,
+    adjust: impl Fn(&ScoringWeights) -> ScoringWeights,
+    top_sessions: usize,
+) -> Result<SimulationReport, sqlx::Error> {
+    info!("Starting XP simulation");
+    let reviews = db::reviews::list_all(pool).await?;
+    let commits = db::commits::list_all(pool).await?;
+    let current_weights = db::scoring_profiles::weights_by_pr(pool, None).await?;
+
+    // PRs missing from the map are scored with the defaults; adjust those too
+    let default_weights = adjust(&ScoringWeights::default());
+    let simulated_weights: HashMap<Uuid, ScoringWeights> = reviews
+        .iter()
+        .map(|r| r.pr_id)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .map(|pr_id| {
+            let weights = current_weights
+                .get(&pr_id)
+                .map(&adjust)
+                .unwrap_or_else(|| default_weights.clone());
+            (pr_id, weights)
+        })
+        .collect();
+
+    // Comment length filtering happens when quality data is loaded, so load it
+    // with the simulated thresholds
+    let min_chars: HashMap<Uuid, i32> = simulated_weights
+        .iter()
+        .map(|(pr_id, w)| (*pr_id, w.min_comment_chars))
+        .collect();
+    let quality = db::review_comments::get_quality_data_by_review(pool, None, &min_chars).await?;
+
+    let excluded = db::gaming::excluded_pairs(pool).await?;
+    let opened_at = db::prs::opened_at_by_id(pool, None).await?;
+    let sizes = db::prs::sizes_by_id(pool, None).await?;
+    let calendars = Calendars::load(pool).await?;
+    let current = stored_scores(db::recalc::list_stored_sessions(pool).await?);
+    let mut simulated = score_pairs(
+        reviews,
+        commits,
+        &quality,
+        &simulated_weights,
+        &opened_at,
+        &sizes,
+        &calendars,
+    );
+    apply_exclusions(&mut simulated, &excluded);
+
+    let achievement_xp = db::achievements::xp_by_user(pool).await?;
+    let mut user_ids: Vec<Uuid> = current
+        .iter()
+        .chain(&simulated)
+        .map(|p| p.reviewer_id)
+        .chain(achievement_xp.keys().copied())
+        .collect();
+    user_ids.sort();
+    user_ids.dedup();
+    let logins = db::users::logins_by_id(pool, &user_ids).await?;
+
+    let mut report = compare_scores(&current, &simulated, &achievement_xp, &logins, top_sessions);
+
+    let pr_ids: Vec<Uuid> = report.sessions.iter().map(|s| s.pr_id).collect();
+    let prs = db::prs::refs_by_id(pool, &pr_ids).await?;
+    for session in &mut report.sessions {
+        session.pr = prs.get(&session.pr_id).cloned();
+    }
+
+    info!(
+        "Simulation complete: {} -> {} XP, {} users reranked, {} sessions changed",
+        report.total_xp_before,
+        report.total_xp_after,
+        report.users_reranked,
+        report.sessions_changed
+    );
+
+    Ok(report)
+}
+
+/// Stored session XP grouped into pairs, sorted by (pr_id, reviewer_id).
+/// Only totals are stored, so breakdowns are empty and `review_count` is the
+/// number of sessions.
+pub fn stored_scores(sessions: Vec<StoredSession>) -> Vec<PairScore> {
+    let mut pairs: HashMap<(Uuid, Uuid), Vec<SessionScore>> = HashMap::new();
+    for session in sessions {
+        pairs
+            .entry((session.pr_id, session.reviewer_id))
+            .or_default()
+            .push(SessionScore {
+                first_review_id: session.review_id,
+                xp: session.xp,
+                breakdown: Default::default(),
+            });
+    }
+
+    let mut scores: Vec<PairScore> = pairs
+        .into_iter()
+        .map(|((pr_id, reviewer_id), sessions)| PairScore {
+            pr_id,
+            reviewer_id,
+            review_count: sessions.len(),
+            sessions,
+        })
+        .collect();
+    scores.sort_by_key(|p| (p.pr_id, p.reviewer_id));
+    scores
+}
+
+/// Compare two scorings of the same reviews.
+///
+/// Sessions are matched by their first review; a session that only exists on
+/// one side (e.g. a different `session_gap_hours` regrouped it) counts as 0 on
+/// the other. `fixed_xp` (achievement rewards) is added to both user totals.
+/// Ranks are 1-based, by XP descending then login. Only the `top_sessions`
+/// largest session changes are kept.
+pub fn compare_scores(
+    current: &[PairScore],
+    simulated: &[PairScore],
+    fixed_xp: &HashMap<Uuid, i64>,
+    logins: &HashMap<Uuid, String>,
+    top_sessions: usize,
+) -> SimulationReport {
+    let login = |id: &Uuid| logins.get(id).cloned().unwrap_or_else(|| id.to_string());
+
+    // Session XP keyed by first review, with its (PR, reviewer)
+    let index = |scores: &[PairScore]| {
+        let mut by_session: HashMap<Uuid, (Uuid, Uuid, i64)> = HashMap::new();
+        for pair in scores {
+            for session in &pair.sessions {
+                by_session.insert(
+                    session.first_review_id,
+                    (pair.pr_id, pair.reviewer_id, session.xp),
+                );
+            }
+        }
+        by_session
+    };
+    let old_sessions = index(current);
+    let new_sessions = index(simulated);
+
+    let mut sessions: Vec<SessionXpChange> = old_sessions
+        .keys()
+        .chain(new_sessions.keys())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .filter_map(|review_id| {
+            let old = old_sessions.get(review_id);
+            let new = new_sessions.get(review_id);
+            let (pr_id, reviewer_id, _) = *old.or(new)?;
+            let old_xp = old.map_or(0, |s| s.2);
+            let new_xp = new.map_or(0, |s| s.2);
+            (old_xp != new_xp).then(|| SessionXpChange {
+                review_id: *review_id,
+                pr_id,
+                reviewer_id,
+                login: login(&reviewer_id),
+                pr: None,
+                old_xp,
+                new_xp,
+                xp_delta: new_xp - old_xp,
+            })
+        })
+        .collect();
+    sessions.sort_by(|a, b| {
+        b.xp_delta
+            .abs()
+            .cmp(&a.xp_delta.abs())
+            .then(a.review_id.cmp(&b.review_id))
+    });
+    let sessions_changed = sessions.len();
+    sessions.truncate(top_sessions);
+
+    // Per-user totals and ranks
+    let totals = |scores: &[PairScore]| {
+        let mut xp: HashMap<Uuid, i64> = fixed_xp.clone();
+        for pair in scores {
+            *xp.entry(pair.reviewer_id).or_default() +=
+                pair.sessions.iter().map(|s| s.xp).sum::<i64>();
+        }
+        xp
+    };
+    let old_xp = totals(current);
+    let new_xp = totals(simulated);
+    let ranks = |xp: &HashMap<Uuid, i64>| {
+        let mut users: Vec<Uuid> = old_xp.keys().chain(new_xp.keys()).copied().collect();
+        users.sort();
+        users.dedup();
+        users.sort_by(|a, b| {
+            let xp_of = |id: &Uuid| xp.get(id).copied().unwrap_or(0);
+            xp_of(b).cmp(&xp_of(a)).then(login(a).cmp(&login(b)))
+        });
+        users
+            .into_iter()
+            .enumerate()
+            .map(|(i, id)| (id, i + 1))
+            .collect::<HashMap<_, _>>()
+    };
+    let old_ranks = ranks(&old_xp);
+    let new_ranks = ranks(&new_xp);
+
+    let mut users: Vec<UserXpChange> = new_ranks
+        .iter()
+        .map(|(id, &new_rank)| {
+            let old = old_xp.get(id).copied().unwrap_or(0);
+            let new = new_xp.get(id).copied().unwrap_or(0);
+            let old_rank = old_ranks[id];
+            UserXpChange {
+                user_id: *id,
+                login: login(id),
+                old_xp: old,
+                new_xp: new,
+                xp_delta: new - old,
+                old_rank,
+                new_rank,
+                rank_change: old_rank as i64 - new_rank as i64,
+            }
+        })
+        .collect();
+    users.sort_by_key(|u| u.new_rank);
+
+    SimulationReport {
+        total_xp_before: old_xp.values().sum(),
+        total_xp_after: new_xp.values().sum(),
+        users_reranked: users.iter().filter(|u| u.rank_change != 0).count(),
+        sessions_changed,
+        users,
+        sessions,
+    }
+}
@@ -0,0 +1,235 @@
+#[cfg(test)]
+mod tests {
+    use crate::recalculate::{PairScore, SessionScore};
+    use crate::simulate::{compare_scores, stored_scores};
+    use common::models::XpBreakdown;
+    use db::recalc::StoredSession;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn pair(pr_id: Uuid, reviewer_id: Uuid, sessions: &[(Uuid, i64)]) -> PairScore {
+        PairScore {
+            pr_id,
+            reviewer_id,
+            review_count: sessions.len(),
+            sessions: sessions
+                .iter()
+                .map(|&(first_review_id, xp)| SessionScore {
+                    first_review_id,
+                    xp,
+                    breakdown: XpBreakdown::default(),
+                })
+                .collect(),
+        }
+    }
+
+    fn logins(users: &[(Uuid, &str)]) -> HashMap<Uuid, String> {
+        users
+            .iter()
+            .map(|(id, login)| (*id, login.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_compare_scores_identical() {
+        let pr = Uuid::new_v4();
+        let alice = Uuid::new_v4();
+        let scores = vec![pair(pr, alice, &[(Uuid::new_v4(), 20)])];
+
+        let report = compare_scores(
+            &scores,
+            &scores,
+            &HashMap::new(),
+            &logins(&[(alice, "alice")]),
+            10,
+        );
+
+        assert_eq!(report.total_xp_before, 20);
+        assert_eq!(report.total_xp_after, 20);
+        assert_eq!(report.users_reranked, 0);
+        assert_eq!(report.sessions_changed, 0);
+        assert!(report.sessions.is_empty());
+        assert_eq!(report.users.len(), 1);
+        assert_eq!(report.users[0].login, "alice");
+        assert_eq!(report.users[0].old_rank, 1);
+        assert_eq!(report.users[0].new_rank, 1);
+    }
+
+    #[test]
+    fn test_compare_scores_rank_changes() {
+        let pr = Uuid::new_v4();
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+        let (a1, b1) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let current = vec![pair(pr, alice, &[(a1, 30)]), pair(pr, bob, &[(b1, 20)])];
+        let simulated = vec![pair(pr, alice, &[(a1, 25)]), pair(pr, bob, &[(b1, 40)])];
+
+        let report = compare_scores(
+            &current,
+            &simulated,
+            &HashMap::new(),
+            &logins(&[(alice, "alice"), (bob, "bob")]),
+            10,
+        );
+
+        assert_eq!(report.total_xp_before, 50);
+        assert_eq!(report.total_xp_after, 65);
+        assert_eq!(report.users_reranked, 2);
+
+        // Ordered by simulated rank
+        assert_eq!(report.users[0].login, "bob");
+        assert_eq!(report.users[0].old_rank, 2);
+        assert_eq!(report.users[0].new_rank, 1);
+        assert_eq!(report.users[0].rank_change, 1);
+        assert_eq!(report.users[0].xp_delta, 20);
+        assert_eq!(report.users[1].login, "alice");
+        assert_eq!(report.users[1].rank_change, -1);
+        assert_eq!(report.users[1].xp_delta, -5);
+
+        // Biggest change first
+        assert_eq!(report.sessions_changed, 2);
+        assert_eq!(report.sessions[0].review_id, b1);
+        assert_eq!(report.sessions[0].old_xp, 20);
+        assert_eq!(report.sessions[0].new_xp, 40);
+        assert_eq!(report.sessions[1].review_id, a1);
+    }
+
+    #[test]
+    fn test_compare_scores_ties_break_by_login() {
+        let pr = Uuid::new_v4();
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+        let scores = vec![
+            pair(pr, bob, &[(Uuid::new_v4(), 10)]),
+            pair(pr, alice, &[(Uuid::new_v4(), 10)]),
+        ];
+
+        let report = compare_scores(
+            &scores,
+            &scores,
+            &HashMap::new(),
+            &logins(&[(alice, "alice"), (bob, "bob")]),
+            10,
+        );
+
+        assert_eq!(report.users[0].login, "alice");
+        assert_eq!(report.users[1].login, "bob");
+    }
+
+    #[test]
+    fn test_compare_scores_regrouped_sessions() {
+        let pr = Uuid::new_v4();
+        let alice = Uuid::new_v4();
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+
+        // A shorter session gap splits one session into two
+        let current = vec![pair(pr, alice, &[(first, 20)])];
+        let simulated = vec![pair(pr, alice, &[(first, 15), (second, 15)])];
+
+        let report = compare_scores(&current, &simulated, &HashMap::new(), &HashMap::new(), 10);
+
+        assert_eq!(report.sessions_changed, 2);
+        let added = report
+            .sessions
+            .iter()
+            .find(|s| s.review_id == second)
+            .unwrap();
+        assert_eq!(added.old_xp, 0);
+        assert_eq!(added.new_xp, 15);
+        // Unknown logins fall back to the user ID
+        assert_eq!(added.login, alice.to_string());
+        assert_eq!(report.users[0].xp_delta, 10);
+    }
+
+    #[test]
+    fn test_compare_scores_truncates_sessions() {
+        let pr = Uuid::new_v4();
+        let alice = Uuid::new_v4();
+        let sessions: Vec<(Uuid, i64)> = (0..5).map(|i| (Uuid::new_v4(), i * 10)).collect();
+        let doubled: Vec<(Uuid, i64)> = sessions.iter().map(|&(id, xp)| (id, xp * 2)).collect();
+
+        let report = compare_scores(
+            &[pair(pr, alice, &sessions)],
+            &[pair(pr, alice, &doubled)],
+            &HashMap::new(),
+            &HashMap::new(),
+            2,
+        );
+
+        // The 0 XP session is unchanged
+        assert_eq!(report.sessions_changed, 4);
+        assert_eq!(report.sessions.len(), 2);
+        assert_eq!(report.sessions[0].xp_delta, 40);
+        assert_eq!(report.sessions[1].xp_delta, 30);
+    }
+
+    #[test]
+    fn test_compare_scores_includes_achievement_xp() {
+        let pr = Uuid::new_v4();
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+        let carol = Uuid::new_v4();
+        let (a1, b1) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let current = vec![pair(pr, alice, &[(a1, 30)]), pair(pr, bob, &[(b1, 20)])];
+        let simulated = vec![pair(pr, alice, &[(a1, 30)]), pair(pr, bob, &[(b1, 25)])];
+        // Bob's 50 XP achievement keeps him ahead either way; Carol has no reviews
+        let fixed = HashMap::from([(bob, 50), (carol, 10)]);
+
+        let report = compare_scores(
+            &current,
+            &simulated,
+            &fixed,
+            &logins(&[(alice, "alice"), (bob, "bob"), (carol, "carol")]),
+            10,
+        );
+
+        assert_eq!(report.total_xp_before, 110);
+        assert_eq!(report.total_xp_after, 115);
+        assert_eq!(report.users_reranked, 0);
+        let names: Vec<_> = report.users.iter().map(|u| u.login.as_str()).collect();
+        assert_eq!(names, ["bob", "alice", "carol"]);
+        assert_eq!(report.users[0].old_xp, 70);
+        assert_eq!(report.users[0].new_xp, 75);
+    }
+
+    #[test]
+    fn test_stored_scores_group_sessions_by_pair() {
+        let pr = Uuid::new_v4();
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+        let (a1, a2, b1) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let stored = |review_id, reviewer_id, xp| StoredSession {
+            review_id,
+            pr_id: pr,
+            reviewer_id,
+            xp,
+        };
+
+        let scores = stored_scores(vec![
+            stored(a1, alice, 20),
+            stored(b1, bob, 15),
+            stored(a2, alice, 10),
+        ]);
+
+        assert_eq!(scores.len(), 2);
+        let alice_pair = scores.iter().find(|p| p.reviewer_id == alice).unwrap();
+        assert_eq!(alice_pair.sessions.len(), 2);
+        assert_eq!(alice_pair.sessions.iter().map(|s| s.xp).sum::<i64>(), 30);
+
+        // Stored XP is the baseline: a rescoring that matches it changes nothing
+        let report = compare_scores(
+            &scores,
+            &[
+                pair(pr, alice, &[(a1, 20), (a2, 10)]),
+                pair(pr, bob, &[(b1, 15)]),
+            ],
+            &HashMap::new(),
+            &logins(&[(alice, "alice"), (bob, "bob")]),
+            10,
+        );
+        assert_eq!(report.total_xp_before, 45);
+        assert_eq!(report.sessions_changed, 0);
+    }
+}
@@ -0,0 +1,109 @@
+//! Stale PR detection
+//!
+//! An open PR goes stale once nothing happened on it (commits, reviews or
+//! comments) for the repo's `stale_after_days`. Whoever acted last decides who
+//! it's waiting on: after a review the author has to respond, after a push or
+//! a reply from the author (or with no review yet) it's the reviewers' turn.
+
+use chrono::{DateTime, Utc};
+use common::models::{Repository, StalePr, StalePrs, WaitingOn};
+use db::stale::OpenPrActivity;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::metrics::{is_stale, staleness_days};
+
+#[cfg(test)]
+#[path = "stale_test.rs"]
+mod stale_test;
+
+/// Latest commit, review or comment, or when the PR was opened
+pub fn last_activity(pr: &OpenPrActivity) -> DateTime<Utc> {
+    [pr.author_active_at, pr.reviewer_active_at]
+        .into_iter()
+        .flatten()
+        .fold(pr.created_at, DateTime::max)
+}
+
+/// Who acted last has handed the PR to the other side
+pub fn waiting_on(pr: &OpenPrActivity) -> WaitingOn {
+    match (pr.author_active_at, pr.reviewer_active_at) {
+        (_, None) => WaitingOn::Reviewer,
+        (Some(author), Some(reviewer)) if author > reviewer => WaitingOn::Reviewer,
+        _ => WaitingOn::Author,
+    }
+}
+
+/// Open PRs idle for at least `stale_after_days`, most idle first
+pub fn stale_prs(
+    repo: &Repository,
+    activity: Vec<OpenPrActivity>,
+    stale_after_days: i64,
+) -> Vec<StalePr> {
+    let mut stale: Vec<StalePr> = activity
+        .into_iter()
+        .filter_map(|pr| {
+            let last_activity_at = last_activity(&pr);
+            if !is_stale(last_activity_at, stale_after_days) {
+                return None;
+            }
+            let waiting_on = waiting_on(&pr);
+            let nudge = match waiting_on {
+                WaitingOn::Reviewer => pr.last_reviewer,
+                WaitingOn::Author => Some(pr.author_login.clone()),
+            };
+            Some(StalePr {
+                pr_id: pr.pr_id,
+                number: pr.number,
+                title: pr.title,
+                author: pr.author_login,
+                url: format!(
+                    "https://github.com/{}/{}/pull/{}",
+                    repo.owner, repo.name, pr.number
+                ),
+                last_activity_at,
+                idle_days: staleness_days(last_activity_at),
+                waiting_on,
+                nudge,
+            })
+        })
+        .collect();
+
+    stale.sort_by_key(|pr| (pr.last_activity_at, pr.number));
+    stale
+}
+
+/// Whether the bot should post about a stale PR: first once it goes stale,
+/// then again every `stale_after_days` while it stays idle
+pub fn alert_due(pr: &StalePr, last_alert: Option<DateTime<Utc>>, stale_after_days: i64) -> bool {
+    match last_alert {
+        None => true,
+        Some(at) => at < pr.last_activity_at || is_stale(at, stale_after_days),
+    }
+}
+
+/// Stale PRs of a repo, using its configured threshold
+pub async fn repo_stale_prs(pool: &PgPool, repo: &Repository) -> Result<StalePrs, sqlx::Error> {
+    let stale_after_days = db::repos::get_sync_settings(pool, repo.id)
+        .await?
+        .unwrap_or_default()
+        .stale_after_days as i64;
+    let activity = db::stale::open_pr_activity(pool, repo.id).await?;
+    Ok(StalePrs {
+        stale_after_days,
+        prs: stale_prs(repo, activity, stale_after_days),
+    })
+}
+
+/// Stale PRs of a repo the bot should post about now
+pub async fn due_alerts(pool: &PgPool, repo: &Repository) -> Result<Vec<StalePr>, sqlx::Error> {
+    let stale = repo_stale_prs(pool, repo).await?;
+    let ids: Vec<Uuid> = stale.prs.iter().map(|pr| pr.pr_id).collect();
+    let alerted = db::stale::alerted_at(pool, &ids).await?;
+
+    Ok(stale
+        .prs
+        .into_iter()
+        .filter(|pr| alert_due(pr, alerted.get(&pr.pr_id).copied(), stale.stale_after_days))
+        .collect())
+}
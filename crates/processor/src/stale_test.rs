@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod tests {
+    use crate::stale::*;
+    use chrono::{DateTime, Duration, Utc};
+    use common::models::{Repository, WaitingOn};
+    use db::stale::OpenPrActivity;
+    use uuid::Uuid;
+
+    fn days_ago(days: i64) -> DateTime<Utc> {
+        Utc::now() - Duration::days(days)
+    }
+
+    fn repo() -> Repository {
+        Repository {
+            id: Uuid::new_v4(),
+            github_id: 1,
+            owner: "acme".to_string(),
+            name: "widgets".to_string(),
+            created_at: days_ago(100),
+        }
+    }
+
+    fn activity(
+        number: i32,
+        opened_days_ago: i64,
+        author_days_ago: Option<i64>,
+        reviewer_days_ago: Option<i64>,
+    ) -> OpenPrActivity {
+        OpenPrActivity {
+            pr_id: Uuid::new_v4(),
+            number,
+            title: format!("PR {}", number),
+            author_login: "alice".to_string(),
+            created_at: days_ago(opened_days_ago),
+            author_active_at: author_days_ago.map(days_ago),
+            reviewer_active_at: reviewer_days_ago.map(days_ago),
+            last_reviewer: reviewer_days_ago.map(|_| "bob".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_waiting_on_whoever_acted_last() {
+        assert_eq!(
+            waiting_on(&activity(1, 10, None, None)),
+            WaitingOn::Reviewer
+        );
+        assert_eq!(
+            waiting_on(&activity(1, 10, Some(5), None)),
+            WaitingOn::Reviewer
+        );
+        assert_eq!(
+            waiting_on(&activity(1, 10, Some(2), Some(5))),
+            WaitingOn::Reviewer
+        );
+        assert_eq!(
+            waiting_on(&activity(1, 10, Some(5), Some(2))),
+            WaitingOn::Author
+        );
+        assert_eq!(
+            waiting_on(&activity(1, 10, None, Some(2))),
+            WaitingOn::Author
+        );
+    }
+
+    #[test]
+    fn test_last_activity_falls_back_to_opened() {
+        let pr = activity(1, 10, None, None);
+        assert_eq!(last_activity(&pr), pr.created_at);
+
+        let pr = activity(1, 10, Some(6), Some(3));
+        assert_eq!(last_activity(&pr), pr.reviewer_active_at.unwrap());
+    }
+
+    #[test]
+    fn test_stale_prs_filtered_sorted_and_nudged() {
+        let prs = vec![
+            // Fresh
+            activity(1, 30, Some(2), Some(1)),
+            // Reviewed, author hasn't responded
+            activity(2, 30, Some(12), Some(8)),
+            // Author pushed, reviewer hasn't looked again
+            activity(3, 30, Some(15), Some(20)),
+            // Never reviewed
+            activity(4, 9, None, None),
+        ];
+
+        let stale = stale_prs(&repo(), prs, 7);
+
+        let numbers: Vec<i32> = stale.iter().map(|pr| pr.number).collect();
+        assert_eq!(numbers, vec![3, 4, 2]);
+        assert_eq!(stale[0].waiting_on, WaitingOn::Reviewer);
+        assert_eq!(stale[0].nudge.as_deref(), Some("bob"));
+        assert_eq!(stale[0].idle_days, 15);
+        assert_eq!(stale[1].nudge, None);
+        assert_eq!(stale[2].waiting_on, WaitingOn::Author);
+        assert_eq!(stale[2].nudge.as_deref(), Some("alice"));
+        assert_eq!(stale[2].url, "https://github.com/acme/widgets/pull/2");
+    }
+
+    #[test]
+    fn test_alert_due_repeats_every_threshold() {
+        let stale = stale_prs(&repo(), vec![activity(1, 20, Some(10), None)], 7);
+        let pr = &stale[0];
+
+        assert!(alert_due(pr, None, 7));
+        assert!(!alert_due(pr, Some(days_ago(2)), 7));
+        assert!(alert_due(pr, Some(days_ago(7)), 7));
+        // Alerted before the latest activity: stale again since
+        assert!(alert_due(pr, Some(days_ago(12)), 30));
+    }
+}
@@ -1,18 +1,24 @@
 //! Background sync service
 
 use crate::Backfiller;
+use chrono::{DateTime, Utc};
+use common::models::RepoSyncSettings;
 use sqlx::PgPool;
 use std::time::Duration;
 use tokio::time::interval;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
+
+#[cfg(test)]
+#[path = "sync_test.rs"]
+mod sync_test;
 
 /// Configuration for the sync service
 #[derive(Debug, Clone)]
 pub struct SyncConfig {
-    /// Interval between sync runs
+    /// Default interval between syncs of a repo (overridable per repo)
     pub interval: Duration,
-    /// Maximum age for initial backfill (days)
-    pub max_age_days: u32,
+    /// How often the scheduler checks which repos are due
+    pub poll_interval: Duration,
     /// GitHub token for API access
     pub github_token: Option<String>,
 }
@@ -20,13 +26,39 @@ pub struct SyncConfig {
 impl Default for SyncConfig {
     fn default() -> Self {
         Self {
-            interval: Duration::from_secs(6 * 60 * 60), // 6 hours
-            max_age_days: 365,
+            interval: Duration::from_secs(6 * 60 * 60),  // 6 hours
+            poll_interval: Duration::from_secs(10 * 60), // 10 minutes
             github_token: None,
         }
     }
 }
 
+/// Check whether a repo is due for a scheduled sync
+///
+/// Paused repos are never due. Repos that were never synced are always due.
+/// Otherwise the repo's own interval applies, falling back to `default_interval`.
+pub fn is_due(
+    settings: &RepoSyncSettings,
+    last_synced_at: Option<DateTime<Utc>>,
+    default_interval: Duration,
+    now: DateTime<Utc>,
+) -> bool {
+    if !settings.enabled {
+        return false;
+    }
+
+    let Some(last_synced_at) = last_synced_at else {
+        return true;
+    };
+
+    let interval = match settings.interval_hours {
+        Some(hours) => chrono::Duration::hours(hours as i64),
+        None => chrono::Duration::from_std(default_interval).unwrap_or(chrono::Duration::MAX),
+    };
+
+    now.signed_duration_since(last_synced_at) >= interval
+}
+
 /// Background sync service that periodically updates all tracked repos
 pub struct SyncService {
     pool: PgPool,
@@ -41,28 +73,50 @@ impl SyncService {
     /// Start the background sync loop
     pub async fn run(self) {
         info!(
-            "Starting sync service (interval: {:?})",
-            self.config.interval
+            "Starting sync service (default interval: {:?}, poll: {:?})",
+            self.config.interval, self.config.poll_interval
         );
 
-        let mut ticker = interval(self.config.interval);
+        let mut ticker = interval(self.config.poll_interval);
 
         // Skip the first immediate tick - let the server start up first
         ticker.tick().await;
 
         loop {
             ticker.tick().await;
-            info!("Starting scheduled sync of all tracked repos");
 
-            if let Err(e) = self.sync_all().await {
+            if let Err(e) = self.sync_due().await {
                 error!("Sync failed: {}", e);
             }
         }
     }
 
-    /// Sync all tracked repositories
+    /// Sync repositories whose per-repo interval has elapsed
+    async fn sync_due(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let now = Utc::now();
+        let due: Vec<_> = db::repos::list_sync_states(&self.pool)
+            .await?
+            .into_iter()
+            .filter(|s| is_due(&s.settings, s.last_synced_at, self.config.interval, now))
+            .collect();
+
+        if due.is_empty() {
+            debug!("No repos due for sync");
+            return Ok(());
+        }
+
+        info!("Starting scheduled sync of {} due repos", due.len());
+        self.sync_repos(due).await;
+        Ok(())
+    }
+
+    /// Sync all enabled repositories regardless of interval
     async fn sync_all(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let repos = db::repos::list(&self.pool).await?;
+        let repos: Vec<_> = db::repos::list_sync_states(&self.pool)
+            .await?
+            .into_iter()
+            .filter(|s| s.settings.enabled)
+            .collect();
 
         if repos.is_empty() {
             info!("No tracked repos to sync");
@@ -70,15 +124,24 @@ impl SyncService {
         }
 
         info!("Syncing {} tracked repos", repos.len());
+        self.sync_repos(repos).await;
+        Ok(())
+    }
 
-        let backfiller = Backfiller::new(
-            self.pool.clone(),
-            self.config.github_token.clone(),
-            self.config.max_age_days,
-        );
-
-        for repo in repos {
-            info!("Syncing {}/{}", repo.owner, repo.name);
+    /// Sync the given repos in order (callers sort by priority)
+    async fn sync_repos(&self, repos: Vec<db::repos::RepoSyncState>) {
+        for state in repos {
+            let repo = state.repo;
+            info!(
+                "Syncing {}/{} (priority {})",
+                repo.owner, repo.name, state.settings.priority
+            );
+
+            let backfiller = Backfiller::new(
+                self.pool.clone(),
+                self.config.github_token.clone(),
+                state.settings.max_age_days.max(1) as u32,
+            );
 
             match backfiller.backfill_repo(&repo.owner, &repo.name).await {
                 Ok(progress) => {
@@ -104,8 +167,17 @@ impl SyncService {
             tokio::time::sleep(Duration::from_secs(2)).await;
         }
 
+        // Rescore only what the sync touched
+        if let Err(e) = crate::recalculate_incremental(&self.pool).await {
+            error!("Incremental XP recalculation after sync failed: {}", e);
+        }
+
+        // Flag suspicious patterns for admin review
+        if let Err(e) = crate::gaming::analyze_incremental(&self.pool).await {
+            error!("Gaming analysis after sync failed: {}", e);
+        }
+
         info!("Sync complete");
-        Ok(())
     }
 
     /// Run a single sync (for manual triggers)
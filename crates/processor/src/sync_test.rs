@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use crate::sync::is_due;
+    use chrono::{Duration, TimeZone, Utc};
+    use common::models::RepoSyncSettings;
+
+    const SIX_HOURS: std::time::Duration = std::time::Duration::from_secs(6 * 60 * 60);
+
+    #[test]
+    fn test_never_synced_is_due() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, 0).unwrap();
+        assert!(is_due(&RepoSyncSettings::default(), None, SIX_HOURS, now));
+    }
+
+    #[test]
+    fn test_paused_repo_never_due() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, 0).unwrap();
+        let settings = RepoSyncSettings {
+            enabled: false,
+            ..Default::default()
+        };
+
+        assert!(!is_due(&settings, None, SIX_HOURS, now));
+        assert!(!is_due(
+            &settings,
+            Some(now - Duration::days(30)),
+            SIX_HOURS,
+            now
+        ));
+    }
+
+    #[test]
+    fn test_default_interval_used_when_unset() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, 0).unwrap();
+        let settings = RepoSyncSettings::default();
+
+        assert!(!is_due(
+            &settings,
+            Some(now - Duration::hours(5)),
+            SIX_HOURS,
+            now
+        ));
+        assert!(is_due(
+            &settings,
+            Some(now - Duration::hours(6)),
+            SIX_HOURS,
+            now
+        ));
+    }
+
+    #[test]
+    fn test_repo_interval_overrides_default() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, 0).unwrap();
+        let settings = RepoSyncSettings {
+            interval_hours: Some(1),
+            ..Default::default()
+        };
+
+        // Due after 1 hour even though the global default is 6
+        assert!(is_due(
+            &settings,
+            Some(now - Duration::hours(2)),
+            SIX_HOURS,
+            now
+        ));
+
+        let slow = RepoSyncSettings {
+            interval_hours: Some(24),
+            ..Default::default()
+        };
+        assert!(!is_due(
+            &slow,
+            Some(now - Duration::hours(12)),
+            SIX_HOURS,
+            now
+        ));
+    }
+}
@@ -0,0 +1,113 @@
+//! Untracking and purging repositories
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::HashSet;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::achievements::AchievementChecker;
+use crate::recalculate::{recalculate_all_xp, RecalculationStats};
+
+/// What to do with a repository's data when it is untracked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UntrackMode {
+    /// Stop syncing but keep PRs, reviews and XP as they are
+    #[default]
+    Freeze,
+    /// Stop syncing and delete all PRs, reviews, comments and commits
+    Purge,
+}
+
+/// Result of untracking a repository
+#[derive(Debug)]
+pub struct UntrackStats {
+    pub mode: UntrackMode,
+    pub affected_users: usize,
+    pub prs_deleted: i64,
+    pub reviews_deleted: i64,
+    pub comments_deleted: i64,
+    pub commits_deleted: i64,
+    /// Present when a purge triggered an XP recalculation
+    pub recalculation: Option<RecalculationStats>,
+    /// (user, achievement) held but no longer met after a purge; empty when
+    /// they were revoked
+    pub achievements_stale: Vec<(Uuid, String)>,
+    /// (user, achievement) taken back after a purge
+    pub achievements_revoked: Vec<(Uuid, String)>,
+}
+
+/// Untrack a repository, optionally purging its data.
+///
+/// A purge deletes the repo row (children cascade), then recalculates XP and
+/// re-evaluates achievements for every user who had activity in it. Those no
+/// longer met are reported, and only taken back with `revoke_achievements`.
+pub async fn untrack_repo(
+    pool: &PgPool,
+    repo_id: Uuid,
+    mode: UntrackMode,
+    revoke_achievements: bool,
+) -> Result<UntrackStats, sqlx::Error> {
+    let affected = db::repos::list_affected_users(pool, repo_id).await?;
+
+    let mut stats = UntrackStats {
+        mode,
+        affected_users: affected.len(),
+        prs_deleted: 0,
+        reviews_deleted: 0,
+        comments_deleted: 0,
+        commits_deleted: 0,
+        recalculation: None,
+        achievements_stale: Vec::new(),
+        achievements_revoked: Vec::new(),
+    };
+
+    match mode {
+        UntrackMode::Freeze => {
+            info!("Untracking repo {} (history frozen)", repo_id);
+            db::repos::mark_untracked(pool, repo_id).await?;
+        }
+        UntrackMode::Purge => {
+            info!(
+                "Purging repo {} ({} affected users)",
+                repo_id,
+                affected.len()
+            );
+            let counts = db::repos::purge(pool, repo_id).await?;
+            stats.prs_deleted = counts.prs;
+            stats.reviews_deleted = counts.reviews;
+            stats.comments_deleted = counts.comments;
+            stats.commits_deleted = counts.commits;
+
+            info!(
+                "Purged {} PRs, {} reviews, {} comments, {} commits",
+                counts.prs, counts.reviews, counts.comments, counts.commits
+            );
+
+            stats.recalculation = Some(recalculate_all_xp(pool).await?);
+
+            let checker = AchievementChecker::new(pool.clone());
+            for user_id in &affected {
+                match checker.reevaluate_user(user_id, revoke_achievements).await {
+                    Ok(evaluation) => {
+                        let revoked: HashSet<&String> = evaluation.revoked.iter().collect();
+                        stats.achievements_stale.extend(
+                            evaluation
+                                .stale
+                                .iter()
+                                .filter(|id| !revoked.contains(id))
+                                .map(|id| (*user_id, id.clone())),
+                        );
+                        stats
+                            .achievements_revoked
+                            .extend(evaluation.revoked.iter().map(|id| (*user_id, id.clone())));
+                    }
+                    Err(e) => warn!("Achievement re-evaluation failed for {}: {}", user_id, e),
+                }
+            }
+        }
+    }
+
+    Ok(stats)
+}
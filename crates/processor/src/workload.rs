@@ -0,0 +1,159 @@
+//! Reviewer workload
+//!
+//! Per reviewer: the open PRs they reviewed, which of those got commits since
+//! their last review, how long those PRs have been open, and how many review
+//! sessions they ran per week recently. Reviewers with at least
+//! `overloaded_at` active PRs are flagged as overloaded.
+
+use chrono::{DateTime, Duration, Utc};
+use common::models::{Commit, RepoWorkload, Review, ReviewerWorkload};
+use db::prs::OpenPrWithStats;
+use db::workload::OpenPrReviewer;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::latency::Calendars;
+use crate::sessions::count_sessions;
+
+#[cfg(test)]
+#[path = "workload_test.rs"]
+mod workload_test;
+
+/// Active PRs at which a reviewer is overloaded, unless configured
+pub const DEFAULT_OVERLOADED_AT: i64 = 5;
+
+/// Weeks of sessions averaged, unless configured
+pub const DEFAULT_WEEKS: i64 = 4;
+
+/// Review sessions per reviewer, grouped per PR as for XP
+pub fn session_counts(
+    reviews: Vec<Review>,
+    commits: &[Commit],
+    max_gap: Duration,
+) -> HashMap<Uuid, i64> {
+    let mut counts = HashMap::new();
+    for ((_, reviewer_id), sessions) in count_sessions(reviews, commits, max_gap) {
+        *counts.entry(reviewer_id).or_insert(0) += sessions;
+    }
+    counts
+}
+
+/// Workload of everyone reviewing an open PR or with sessions in the window,
+/// most loaded first
+pub fn reviewer_workload(
+    open_prs: &[OpenPrWithStats],
+    reviewers: &[OpenPrReviewer],
+    sessions_per_week: &HashMap<Uuid, f64>,
+    logins: &HashMap<Uuid, String>,
+    calendars: &Calendars,
+    now: DateTime<Utc>,
+    overloaded_at: i64,
+) -> Vec<ReviewerWorkload> {
+    let ages: HashMap<Uuid, f64> = open_prs
+        .iter()
+        .map(|pr| {
+            let age = calendars
+                .for_user(pr.author_id)
+                .working_time(pr.created_at, now);
+            (pr.id, age.num_minutes() as f64 / 60.0)
+        })
+        .collect();
+
+    let mut active: HashMap<Uuid, (i64, i64, Vec<f64>)> = HashMap::new();
+    for r in reviewers {
+        let Some(&age) = ages.get(&r.pr_id) else {
+            continue;
+        };
+        let entry = active.entry(r.reviewer_id).or_default();
+        entry.0 += 1;
+        if r.last_push_at
+            .is_some_and(|pushed| pushed > r.last_review_at)
+        {
+            entry.1 += 1;
+        }
+        entry.2.push(age);
+    }
+
+    let mut ids: Vec<Uuid> = active
+        .keys()
+        .chain(sessions_per_week.keys())
+        .copied()
+        .collect();
+    ids.sort();
+    ids.dedup();
+
+    let mut result: Vec<ReviewerWorkload> = ids
+        .into_iter()
+        .filter_map(|id| {
+            let login = logins.get(&id)?.clone();
+            let (active_prs, awaiting_rereview, ages) = active.remove(&id).unwrap_or_default();
+            let avg_open_pr_age_hours =
+                (!ages.is_empty()).then(|| ages.iter().sum::<f64>() / ages.len() as f64);
+            Some(ReviewerWorkload {
+                login,
+                active_prs,
+                awaiting_rereview,
+                avg_open_pr_age_hours,
+                sessions_per_week: sessions_per_week.get(&id).copied().unwrap_or(0.0),
+                overloaded: active_prs >= overloaded_at,
+            })
+        })
+        .collect();
+
+    result.sort_by(|a, b| {
+        b.active_prs
+            .cmp(&a.active_prs)
+            .then(b.awaiting_rereview.cmp(&a.awaiting_rereview))
+            .then_with(|| a.login.cmp(&b.login))
+    });
+    result
+}
+
+/// Workload report for a repo, averaging sessions over the last `weeks` (at
+/// least 1)
+pub async fn repo_workload(
+    pool: &PgPool,
+    repo_id: Uuid,
+    overloaded_at: i64,
+    weeks: i64,
+) -> Result<RepoWorkload, sqlx::Error> {
+    let now = Utc::now();
+    let open_prs = db::prs::list_open_with_stats(pool, repo_id).await?;
+    let reviewers = db::workload::open_pr_reviewers(pool, repo_id).await?;
+
+    let reviews = db::workload::reviews_since(pool, repo_id, now - Duration::weeks(weeks)).await?;
+    let mut pr_ids: Vec<Uuid> = reviews.iter().map(|r| r.pr_id).collect();
+    pr_ids.sort();
+    pr_ids.dedup();
+    let commits = db::commits::list_for_prs(pool, &pr_ids).await?;
+    let max_gap = db::scoring_profiles::weights_for_repo(pool, repo_id)
+        .await?
+        .unwrap_or_default()
+        .session_gap();
+    let sessions_per_week: HashMap<Uuid, f64> = session_counts(reviews, &commits, max_gap)
+        .into_iter()
+        .map(|(id, n)| (id, n as f64 / weeks as f64))
+        .collect();
+
+    let mut user_ids: Vec<Uuid> = reviewers.iter().map(|r| r.reviewer_id).collect();
+    user_ids.extend(sessions_per_week.keys());
+    let logins = db::users::logins_by_id(pool, &user_ids).await?;
+    let calendars = Calendars::load(pool).await?;
+
+    let reviewers = reviewer_workload(
+        &open_prs,
+        &reviewers,
+        &sessions_per_week,
+        &logins,
+        &calendars,
+        now,
+        overloaded_at,
+    );
+    Ok(RepoWorkload {
+        overloaded_at,
+        weeks,
+        overloaded: reviewers.iter().filter(|r| r.overloaded).count() as i64,
+        reviewers,
+    })
+}
@@ -0,0 +1,149 @@
+#[cfg(test)]
+mod tests {
+    use crate::latency::Calendars;
+    use crate::workload::*;
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use common::models::{Commit, Review, ReviewState};
+    use db::prs::OpenPrWithStats;
+    use db::workload::OpenPrReviewer;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 6, 1, 12, 0, 0).unwrap()
+    }
+
+    fn open_pr(hours_old: i64) -> OpenPrWithStats {
+        OpenPrWithStats {
+            id: Uuid::new_v4(),
+            number: 1,
+            title: "pr".to_string(),
+            author_id: Uuid::new_v4(),
+            author_login: "author".to_string(),
+            author_avatar: None,
+            created_at: now() - Duration::hours(hours_old),
+            first_review_at: None,
+            review_count: 1,
+            approvals: 0,
+            changes_requested: 0,
+            comments_count: 0,
+            latest_review_state: None,
+            reviewers: Vec::new(),
+        }
+    }
+
+    fn reviewer(
+        pr: &OpenPrWithStats,
+        reviewer_id: Uuid,
+        reviewed_hours_ago: i64,
+        pushed_hours_ago: Option<i64>,
+    ) -> OpenPrReviewer {
+        OpenPrReviewer {
+            pr_id: pr.id,
+            reviewer_id,
+            last_review_at: now() - Duration::hours(reviewed_hours_ago),
+            last_push_at: pushed_hours_ago.map(|h| now() - Duration::hours(h)),
+        }
+    }
+
+    fn review(pr_id: Uuid, reviewer_id: Uuid, hours_ago: i64) -> Review {
+        Review {
+            id: Uuid::new_v4(),
+            pr_id,
+            reviewer_id,
+            github_id: 1,
+            state: ReviewState::Commented,
+            body: None,
+            comments_count: 1,
+            submitted_at: now() - Duration::hours(hours_ago),
+        }
+    }
+
+    fn commit(pr_id: Uuid, hours_ago: i64) -> Commit {
+        Commit {
+            id: Uuid::new_v4(),
+            pr_id,
+            sha: format!("sha{}", hours_ago),
+            author_id: None,
+            committed_at: now() - Duration::hours(hours_ago),
+            message: None,
+            created_at: now() - Duration::hours(hours_ago),
+        }
+    }
+
+    #[test]
+    fn test_session_counts_split_on_commits_and_gaps() {
+        let (pr, alice, bob) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let reviews = vec![
+            review(pr, alice, 100),
+            review(pr, alice, 99),
+            // after a push
+            review(pr, alice, 50),
+            // after a gap longer than a day
+            review(pr, alice, 10),
+            review(pr, bob, 60),
+        ];
+
+        let counts = session_counts(reviews, &[commit(pr, 70)], Duration::hours(24));
+
+        assert_eq!(counts[&alice], 3);
+        assert_eq!(counts[&bob], 1);
+    }
+
+    #[test]
+    fn test_workload_counts_active_rereview_and_age() {
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let old = open_pr(30);
+        let new = open_pr(10);
+        let reviewers = vec![
+            reviewer(&old, alice, 20, Some(5)),
+            reviewer(&new, alice, 2, Some(8)),
+            reviewer(&new, bob, 1, None),
+        ];
+        let logins = HashMap::from([(alice, "alice".to_string()), (bob, "bob".to_string())]);
+        let sessions = HashMap::from([(bob, 1.5)]);
+
+        let result = reviewer_workload(
+            &[old, new],
+            &reviewers,
+            &sessions,
+            &logins,
+            &Calendars::default(),
+            now(),
+            2,
+        );
+
+        assert_eq!(result[0].login, "alice");
+        assert_eq!(result[0].active_prs, 2);
+        assert_eq!(result[0].awaiting_rereview, 1);
+        assert_eq!(result[0].avg_open_pr_age_hours, Some(20.0));
+        assert_eq!(result[0].sessions_per_week, 0.0);
+        assert!(result[0].overloaded);
+        assert_eq!(result[1].login, "bob");
+        assert_eq!(result[1].active_prs, 1);
+        assert_eq!(result[1].sessions_per_week, 1.5);
+        assert!(!result[1].overloaded);
+    }
+
+    #[test]
+    fn test_workload_includes_idle_reviewers_with_sessions() {
+        let carol = Uuid::new_v4();
+        let logins = HashMap::from([(carol, "carol".to_string())]);
+        let sessions = HashMap::from([(carol, 2.0)]);
+
+        let result = reviewer_workload(
+            &[],
+            &[],
+            &sessions,
+            &logins,
+            &Calendars::default(),
+            now(),
+            DEFAULT_OVERLOADED_AT,
+        );
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].active_prs, 0);
+        assert_eq!(result[0].avg_open_pr_age_hours, None);
+        assert!(!result[0].overloaded);
+    }
+}
@@ -1,6 +1,47 @@
 -- Review Royale Schema
 -- Single file schema - nuke and rebuild anytime
 
+-- Scoring profiles: named XP weights, assignable per repository
+CREATE TABLE IF NOT EXISTS scoring_profiles (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL UNIQUE,
+    description TEXT,
+    base_xp INTEGER NOT NULL DEFAULT 10,
+    low_quality_xp INTEGER NOT NULL DEFAULT 2,
+    medium_quality_xp INTEGER NOT NULL DEFAULT 5,
+    high_quality_xp INTEGER NOT NULL DEFAULT 8,
+    uncategorized_xp INTEGER NOT NULL DEFAULT 5,
+    logic_bonus INTEGER NOT NULL DEFAULT 3,
+    structural_bonus INTEGER NOT NULL DEFAULT 2,
+    addressed_bonus INTEGER NOT NULL DEFAULT 5,
+    fast_review_bonus INTEGER NOT NULL DEFAULT 10,
+    fast_review_minutes INTEGER NOT NULL DEFAULT 60,
+    thorough_threshold INTEGER NOT NULL DEFAULT 5,
+    thorough_bonus INTEGER NOT NULL DEFAULT 5,
+    deep_threshold INTEGER NOT NULL DEFAULT 10,
+    deep_bonus INTEGER NOT NULL DEFAULT 10,
+    session_gap_hours INTEGER NOT NULL DEFAULT 24,
+    min_comment_chars INTEGER NOT NULL DEFAULT 20,
+    difficulty_max_pct INTEGER NOT NULL DEFAULT 150,
+    difficulty_lines INTEGER NOT NULL DEFAULT 500,
+    difficulty_files INTEGER NOT NULL DEFAULT 20,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Migration: bonus for comments addressed by a follow-up commit
+ALTER TABLE scoring_profiles ADD COLUMN IF NOT EXISTS addressed_bonus INTEGER NOT NULL DEFAULT 5;
+
+-- Migration: PR difficulty multiplier
+ALTER TABLE scoring_profiles ADD COLUMN IF NOT EXISTS difficulty_max_pct INTEGER NOT NULL DEFAULT 150;
+ALTER TABLE scoring_profiles ADD COLUMN IF NOT EXISTS difficulty_lines INTEGER NOT NULL DEFAULT 500;
+ALTER TABLE scoring_profiles ADD COLUMN IF NOT EXISTS difficulty_files INTEGER NOT NULL DEFAULT 20;
+
+-- Repos without an explicit profile use this one
+INSERT INTO scoring_profiles (name, description)
+VALUES ('default', 'Standard Review Royale scoring')
+ON CONFLICT (name) DO NOTHING;
+
 -- Repositories
 CREATE TABLE IF NOT EXISTS repositories (
     id UUID PRIMARY KEY,
@@ -9,9 +50,34 @@ CREATE TABLE IF NOT EXISTS repositories (
     name TEXT NOT NULL,
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
     last_synced_at TIMESTAMPTZ,
-    sync_cursor TEXT
+    sync_cursor TEXT,
+    sync_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    sync_interval_hours INTEGER,
+    sync_max_age_days INTEGER NOT NULL DEFAULT 365,
+    sync_priority INTEGER NOT NULL DEFAULT 0,
+    fetch_commits BOOLEAN NOT NULL DEFAULT TRUE,
+    fetch_comments BOOLEAN NOT NULL DEFAULT TRUE,
+    untracked_at TIMESTAMPTZ,
+    scoring_profile_id UUID REFERENCES scoring_profiles(id) ON DELETE SET NULL
 );
 
+-- Migration: per-repository sync settings (for existing DBs)
+ALTER TABLE repositories ADD COLUMN IF NOT EXISTS sync_enabled BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE repositories ADD COLUMN IF NOT EXISTS sync_interval_hours INTEGER;
+ALTER TABLE repositories ADD COLUMN IF NOT EXISTS sync_max_age_days INTEGER NOT NULL DEFAULT 365;
+ALTER TABLE repositories ADD COLUMN IF NOT EXISTS sync_priority INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE repositories ADD COLUMN IF NOT EXISTS fetch_commits BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE repositories ADD COLUMN IF NOT EXISTS fetch_comments BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- Migration: untracked repos keep their history but are no longer synced
+ALTER TABLE repositories ADD COLUMN IF NOT EXISTS untracked_at TIMESTAMPTZ;
+
+-- Migration: per-repository scoring profile (NULL = 'default')
+ALTER TABLE repositories ADD COLUMN IF NOT EXISTS scoring_profile_id UUID REFERENCES scoring_profiles(id) ON DELETE SET NULL;
+
+-- Migration: days without activity before an open PR is stale
+ALTER TABLE repositories ADD COLUMN IF NOT EXISTS stale_after_days INTEGER NOT NULL DEFAULT 7;
+
 CREATE INDEX IF NOT EXISTS idx_repos_owner_name ON repositories(owner, name);
 CREATE INDEX IF NOT EXISTS idx_repos_last_synced ON repositories(last_synced_at);
 
@@ -35,7 +101,7 @@ CREATE INDEX IF NOT EXISTS idx_users_sessions ON users(review_sessions DESC);
 -- Pull Requests
 CREATE TABLE IF NOT EXISTS pull_requests (
     id UUID PRIMARY KEY,
-    repo_id UUID NOT NULL REFERENCES repositories(id),
+    repo_id UUID NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
     github_id BIGINT NOT NULL UNIQUE,
     number INTEGER NOT NULL,
     title TEXT NOT NULL,
@@ -52,6 +118,25 @@ CREATE INDEX IF NOT EXISTS idx_prs_author ON pull_requests(author_id);
 CREATE INDEX IF NOT EXISTS idx_prs_created ON pull_requests(created_at DESC);
 CREATE INDEX IF NOT EXISTS idx_prs_state ON pull_requests(state);
 
+-- Migration: PR size for the difficulty multiplier, NULL until fetched
+ALTER TABLE pull_requests ADD COLUMN IF NOT EXISTS additions INTEGER;
+ALTER TABLE pull_requests ADD COLUMN IF NOT EXISTS deletions INTEGER;
+ALTER TABLE pull_requests ADD COLUMN IF NOT EXISTS changed_files INTEGER;
+
+-- Files changed by open PRs (first 100), for reviewer recommendations.
+-- Refreshed on every sync while the PR is open.
+CREATE TABLE IF NOT EXISTS pr_files (
+    pr_id UUID NOT NULL REFERENCES pull_requests(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    PRIMARY KEY (pr_id, path)
+);
+
+-- When the bot last posted about a stale PR
+CREATE TABLE IF NOT EXISTS stale_pr_alerts (
+    pr_id UUID PRIMARY KEY REFERENCES pull_requests(id) ON DELETE CASCADE,
+    alerted_at TIMESTAMPTZ NOT NULL
+);
+
 -- Commits (for review session boundaries)
 CREATE TABLE IF NOT EXISTS commits (
     id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
@@ -61,29 +146,76 @@ CREATE TABLE IF NOT EXISTS commits (
     committed_at TIMESTAMPTZ NOT NULL,
     message TEXT,
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    touched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
     UNIQUE(pr_id, sha)
 );
 
+ALTER TABLE commits ADD COLUMN IF NOT EXISTS touched_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
 CREATE INDEX IF NOT EXISTS idx_commits_pr ON commits(pr_id, committed_at DESC);
 CREATE INDEX IF NOT EXISTS idx_commits_author ON commits(author_id);
+CREATE INDEX IF NOT EXISTS idx_commits_touched ON commits(touched_at);
+
+-- Migration: set once the commit's changed lines have been fetched
+ALTER TABLE commits ADD COLUMN IF NOT EXISTS hunks_fetched_at TIMESTAMPTZ;
+
+-- Lines each commit changed (old side of the patch hunks), for matching
+-- review comments to the commits that addressed them
+CREATE TABLE IF NOT EXISTS commit_hunks (
+    commit_id UUID NOT NULL REFERENCES commits(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    old_start INTEGER NOT NULL,
+    old_lines INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_commit_hunks_commit ON commit_hunks(commit_id, path);
 
 -- Reviews
 CREATE TABLE IF NOT EXISTS reviews (
     id UUID PRIMARY KEY,
-    pr_id UUID NOT NULL REFERENCES pull_requests(id),
+    pr_id UUID NOT NULL REFERENCES pull_requests(id) ON DELETE CASCADE,
     reviewer_id UUID NOT NULL REFERENCES users(id),
     github_id BIGINT NOT NULL UNIQUE,
     state TEXT NOT NULL,
     body TEXT,
     comments_count INTEGER NOT NULL DEFAULT 0,
     submitted_at TIMESTAMPTZ NOT NULL,
-    xp_earned INTEGER NOT NULL DEFAULT 0
+    xp_earned INTEGER NOT NULL DEFAULT 0,
+    session_start BOOLEAN NOT NULL DEFAULT FALSE,
+    touched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
 
+-- Migration: session markers and change tracking for incremental recalculation
+ALTER TABLE reviews ADD COLUMN IF NOT EXISTS session_start BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE reviews ADD COLUMN IF NOT EXISTS touched_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
 CREATE INDEX IF NOT EXISTS idx_reviews_pr ON reviews(pr_id);
 CREATE INDEX IF NOT EXISTS idx_reviews_reviewer ON reviews(reviewer_id);
 CREATE INDEX IF NOT EXISTS idx_reviews_submitted ON reviews(submitted_at DESC);
 CREATE INDEX IF NOT EXISTS idx_reviews_xp_period ON reviews(reviewer_id, submitted_at, xp_earned);
+CREATE INDEX IF NOT EXISTS idx_reviews_touched ON reviews(touched_at);
+
+-- Migration: cascade deletes from repositories -> pull_requests -> reviews (for existing DBs)
+DO $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM pg_constraint
+        WHERE conname = 'pull_requests_repo_id_fkey' AND confdeltype <> 'c'
+    ) THEN
+        ALTER TABLE pull_requests DROP CONSTRAINT pull_requests_repo_id_fkey;
+        ALTER TABLE pull_requests ADD CONSTRAINT pull_requests_repo_id_fkey
+            FOREIGN KEY (repo_id) REFERENCES repositories(id) ON DELETE CASCADE;
+    END IF;
+
+    IF EXISTS (
+        SELECT 1 FROM pg_constraint
+        WHERE conname = 'reviews_pr_id_fkey' AND confdeltype <> 'c'
+    ) THEN
+        ALTER TABLE reviews DROP CONSTRAINT reviews_pr_id_fkey;
+        ALTER TABLE reviews ADD CONSTRAINT reviews_pr_id_fkey
+            FOREIGN KEY (pr_id) REFERENCES pull_requests(id) ON DELETE CASCADE;
+    END IF;
+END $$;
 
 -- Review Comments (for AI categorization)
 CREATE TABLE IF NOT EXISTS review_comments (
@@ -99,14 +231,149 @@ CREATE TABLE IF NOT EXISTS review_comments (
     in_reply_to_id BIGINT,
     created_at TIMESTAMPTZ NOT NULL,
     category TEXT,
-    quality_score INTEGER
+    quality_score INTEGER,
+    touched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
 
+ALTER TABLE review_comments ADD COLUMN IF NOT EXISTS touched_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+-- Migration: first commit that changed the commented lines before merge
+ALTER TABLE review_comments ADD COLUMN IF NOT EXISTS addressed_by UUID REFERENCES commits(id) ON DELETE SET NULL;
+
+-- Migration: reply thanks the reviewer (decided in Rust on save, see
+-- db::review_threads::is_thanks; NULL until classified)
+ALTER TABLE review_comments ADD COLUMN IF NOT EXISTS thanks BOOLEAN;
+
 CREATE INDEX IF NOT EXISTS idx_review_comments_review ON review_comments(review_id);
 CREATE INDEX IF NOT EXISTS idx_review_comments_pr ON review_comments(pr_id);
 CREATE INDEX IF NOT EXISTS idx_review_comments_user ON review_comments(user_id);
 CREATE INDEX IF NOT EXISTS idx_review_comments_created ON review_comments(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_review_comments_reply ON review_comments(in_reply_to_id);
 CREATE INDEX IF NOT EXISTS idx_review_comments_category ON review_comments(category) WHERE category IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_review_comments_touched ON review_comments(touched_at);
+
+-- Review threads: a top-level comment and its replies (GitHub points every
+-- reply at the root via in_reply_to_id). The PR author acknowledges a thread
+-- someone else started by replying, and thanks it with a reply marked
+-- `thanks`. Append new columns at the end (CREATE OR REPLACE VIEW). Grouping
+-- by the root's PR and starter lets filters on them reach the base table.
+CREATE OR REPLACE VIEW review_threads AS
+SELECT
+    root.id,
+    root.github_id,
+    root.pr_id,
+    root.user_id as starter_id,
+    root.path,
+    root.created_at as started_at,
+    (1 + COUNT(reply.id))::int as depth,
+    (1 + COUNT(DISTINCT reply.user_id) FILTER (WHERE reply.user_id <> root.user_id))::int as participants,
+    MIN(reply.created_at) FILTER (WHERE reply.user_id <> root.user_id) as first_reply_at,
+    COALESCE(BOOL_OR(
+        reply.user_id = p.author_id AND root.user_id <> p.author_id
+    ), FALSE) as author_acknowledged,
+    COALESCE(BOOL_OR(
+        reply.user_id = p.author_id AND root.user_id <> p.author_id AND reply.thanks
+    ), FALSE) as author_thanked
+FROM review_comments root
+JOIN pull_requests p ON p.id = root.pr_id
+LEFT JOIN review_comments reply ON reply.in_reply_to_id = root.github_id
+WHERE root.in_reply_to_id IS NULL
+GROUP BY root.id, root.pr_id, root.user_id, p.author_id;
+
+-- Recalculation watermark (single row): rows touched after this need reprocessing
+CREATE TABLE IF NOT EXISTS recalc_state (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    last_run_at TIMESTAMPTZ
+);
+
+-- Time-decayed XP (single row): reviews store 2^((submitted_at - epoch) / half-life)
+-- in decay_weight; see db::decay
+CREATE TABLE IF NOT EXISTS xp_decay (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    half_life_days DOUBLE PRECISION NOT NULL,
+    epoch TIMESTAMPTZ NOT NULL
+);
+
+INSERT INTO xp_decay (id, half_life_days, epoch) VALUES (TRUE, 14, NOW())
+ON CONFLICT (id) DO NOTHING;
+
+ALTER TABLE reviews ADD COLUMN IF NOT EXISTS decay_weight DOUBLE PRECISION;
+
+-- XP ledger: append-only record of every award with itemized components.
+-- Rescoring a session appends a reversal of its previous entries plus a new entry.
+-- Review, PR and repo references are nulled (not cascaded) when their rows are
+-- deleted; the repo and PR labels stored on each row keep the history readable.
+CREATE TABLE IF NOT EXISTS xp_ledger (
+    id BIGSERIAL PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL, -- session | reversal | achievement | revocation
+    session_review_id UUID REFERENCES reviews(id) ON DELETE SET NULL,
+    pr_id UUID REFERENCES pull_requests(id) ON DELETE SET NULL,
+    repo_id UUID REFERENCES repositories(id) ON DELETE SET NULL,
+    achievement_id TEXT,
+    rule_version INTEGER NOT NULL,
+    scoring_profile TEXT,
+    base INTEGER NOT NULL DEFAULT 0,
+    comments_low INTEGER NOT NULL DEFAULT 0,
+    comments_medium INTEGER NOT NULL DEFAULT 0,
+    comments_high INTEGER NOT NULL DEFAULT 0,
+    comments_uncategorized INTEGER NOT NULL DEFAULT 0,
+    logic_bonus INTEGER NOT NULL DEFAULT 0,
+    structural_bonus INTEGER NOT NULL DEFAULT 0,
+    addressed_bonus INTEGER NOT NULL DEFAULT 0,
+    fast_bonus INTEGER NOT NULL DEFAULT 0,
+    thorough_bonus INTEGER NOT NULL DEFAULT 0,
+    deep_bonus INTEGER NOT NULL DEFAULT 0,
+    achievement INTEGER NOT NULL DEFAULT 0,
+    total INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE xp_ledger ADD COLUMN IF NOT EXISTS addressed_bonus INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE xp_ledger ADD COLUMN IF NOT EXISTS difficulty_bonus INTEGER NOT NULL DEFAULT 0;
+-- Multiplier in percent, not XP: a session's net (session minus reversals) is
+-- the multiplier it was last scored with
+ALTER TABLE xp_ledger ADD COLUMN IF NOT EXISTS difficulty_pct INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE xp_ledger ADD COLUMN IF NOT EXISTS repo_name TEXT;
+ALTER TABLE xp_ledger ADD COLUMN IF NOT EXISTS pr_number INTEGER;
+ALTER TABLE xp_ledger ADD COLUMN IF NOT EXISTS pr_title TEXT;
+
+-- Migration: keep ledger history when reviews, PRs or repos are deleted
+DO $$
+DECLARE
+    fk RECORD;
+BEGIN
+    FOR fk IN
+        SELECT conname, a.attname as col, confrelid::regclass::text as ref
+        FROM pg_constraint c
+        JOIN pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = c.conkey[1]
+        WHERE c.conrelid = 'xp_ledger'::regclass
+          AND c.contype = 'f'
+          AND a.attname IN ('session_review_id', 'pr_id', 'repo_id')
+          AND c.confdeltype <> 'n'
+    LOOP
+        EXECUTE format('ALTER TABLE xp_ledger DROP CONSTRAINT %I', fk.conname);
+        EXECUTE format(
+            'ALTER TABLE xp_ledger ADD CONSTRAINT %I FOREIGN KEY (%I) REFERENCES %s(id) ON DELETE SET NULL',
+            fk.conname, fk.col, fk.ref
+        );
+    END LOOP;
+END $$;
+
+-- Migration: label existing entries with their repo and PR
+UPDATE xp_ledger l
+SET repo_name = r.owner || '/' || r.name
+FROM repositories r
+WHERE l.repo_name IS NULL AND r.id = l.repo_id;
+
+UPDATE xp_ledger l
+SET pr_number = p.number, pr_title = p.title
+FROM pull_requests p
+WHERE l.pr_number IS NULL AND p.id = l.pr_id;
+
+CREATE INDEX IF NOT EXISTS idx_xp_ledger_user ON xp_ledger(user_id, id DESC);
+CREATE INDEX IF NOT EXISTS idx_xp_ledger_session ON xp_ledger(session_review_id);
+CREATE INDEX IF NOT EXISTS idx_xp_ledger_pr_user ON xp_ledger(pr_id, user_id);
 
 -- Achievements
 CREATE TABLE IF NOT EXISTS achievements (
@@ -118,12 +385,23 @@ CREATE TABLE IF NOT EXISTS achievements (
     rarity TEXT NOT NULL DEFAULT 'common'
 );
 
+-- Custom achievements (created through the API) carry their rule; built-in
+-- rules live in db::achievement_rules. repo_id has no FK so purging a repo
+-- doesn't touch unlocked achievements.
+ALTER TABLE achievements ADD COLUMN IF NOT EXISTS is_custom BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE achievements ADD COLUMN IF NOT EXISTS metric TEXT;
+ALTER TABLE achievements ADD COLUMN IF NOT EXISTS threshold BIGINT;
+ALTER TABLE achievements ADD COLUMN IF NOT EXISTS metric_category TEXT;
+ALTER TABLE achievements ADD COLUMN IF NOT EXISTS repo_id UUID;
+ALTER TABLE achievements ADD COLUMN IF NOT EXISTS window_days INTEGER;
+
 -- User Achievements
 CREATE TABLE IF NOT EXISTS user_achievements (
     user_id UUID NOT NULL REFERENCES users(id),
     achievement_id TEXT NOT NULL REFERENCES achievements(id),
     unlocked_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
     notified_at TIMESTAMPTZ,
+    xp_awarded INTEGER NOT NULL DEFAULT 0,
     PRIMARY KEY (user_id, achievement_id)
 );
 
@@ -142,6 +420,22 @@ CREATE INDEX IF NOT EXISTS idx_user_achievements_user ON user_achievements(user_
 CREATE INDEX IF NOT EXISTS idx_user_achievements_unlocked ON user_achievements(unlocked_at DESC);
 CREATE INDEX IF NOT EXISTS idx_user_achievements_pending ON user_achievements(unlocked_at) WHERE notified_at IS NULL;
 
+-- Audit log of achievements taken back because the holder no longer meets
+-- the rule (revocation is opt-in; re-evaluation alone never revokes)
+CREATE TABLE IF NOT EXISTS achievement_revocations (
+    id BIGSERIAL PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    achievement_id TEXT NOT NULL REFERENCES achievements(id) ON DELETE CASCADE,
+    unlocked_at TIMESTAMPTZ NOT NULL,
+    xp_revoked INTEGER NOT NULL,
+    metric_value BIGINT NOT NULL,
+    threshold BIGINT NOT NULL,
+    rule_version INTEGER NOT NULL,
+    revoked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_achievement_revocations_user ON achievement_revocations(user_id, revoked_at DESC);
+
 -- Seasons
 CREATE TABLE IF NOT EXISTS seasons (
     id UUID PRIMARY KEY,
@@ -182,6 +476,27 @@ CREATE TABLE IF NOT EXISTS team_members (
 CREATE INDEX IF NOT EXISTS idx_team_members_team ON team_members(team_id);
 CREATE INDEX IF NOT EXISTS idx_team_members_user ON team_members(user_id);
 
+-- Achievements unlocked within one repository or team, tracked apart from
+-- the global unlocks. They carry no XP (the global unlock already credits it).
+CREATE TABLE IF NOT EXISTS scoped_achievements (
+    id BIGSERIAL PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    achievement_id TEXT NOT NULL REFERENCES achievements(id) ON DELETE CASCADE,
+    repo_id UUID REFERENCES repositories(id) ON DELETE CASCADE,
+    team_id UUID REFERENCES teams(id) ON DELETE CASCADE,
+    unlocked_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((repo_id IS NULL) <> (team_id IS NULL))
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_scoped_achievements_repo
+    ON scoped_achievements(user_id, achievement_id, repo_id) WHERE repo_id IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_scoped_achievements_team
+    ON scoped_achievements(user_id, achievement_id, team_id) WHERE team_id IS NOT NULL;
+
+-- Revocations of scoped unlocks record their scope
+ALTER TABLE achievement_revocations ADD COLUMN IF NOT EXISTS repo_id UUID REFERENCES repositories(id) ON DELETE CASCADE;
+ALTER TABLE achievement_revocations ADD COLUMN IF NOT EXISTS team_id UUID REFERENCES teams(id) ON DELETE CASCADE;
+
 -- Default achievements
 INSERT INTO achievements (id, name, description, emoji, xp_reward, rarity) VALUES
     -- Milestone achievements
@@ -192,11 +507,11 @@ INSERT INTO achievements (id, name, description, emoji, xp_reward, rarity) VALUE
     ('review_500', 'Gatekeeper', 'Submit 500 reviews', '🏰', 1000, 'epic'),
     ('review_1000', 'Code Guardian', 'Submit 1000 reviews', '⚔️', 2000, 'legendary'),
     -- Speed achievements
-    ('speed_demon', 'Speed Demon', 'Review within 1 hour of PR creation (10x)', '⚡', 200, 'uncommon'),
+    ('speed_demon', 'Speed Demon', 'Earn the fast review bonus 10 times', '⚡', 200, 'uncommon'),
     ('first_responder', 'First Responder', 'Be first reviewer on a PR (25x)', '🚨', 300, 'rare'),
     -- Quality achievements  
     ('nitpicker', 'Nitpicker', 'Leave 50 comments marked as nits', '🔍', 100, 'common'),
-    ('bug_hunter', 'Bug Hunter', 'Catch 10 bugs in reviews', '🐛', 400, 'rare'),
+    ('bug_hunter', 'Bug Hunter', 'Have 10 review comments fixed by a follow-up commit', '🐛', 400, 'rare'),
     ('thorough', 'Deep Dive', 'Leave 10+ comments in a single review (5x)', '🤿', 250, 'uncommon'),
     -- Streak achievements
     ('review_streak_7', 'On Fire', 'Review PRs 7 days in a row', '🔥', 300, 'rare'),
@@ -205,11 +520,14 @@ INSERT INTO achievements (id, name, description, emoji, xp_reward, rarity) VALUE
     ('comeback_kid', 'Comeback Kid', 'Return after 30+ day absence', '🦅', 150, 'uncommon'),
     ('review_rampage', 'Review Rampage', 'Review 5 PRs in a single day', '💥', 200, 'uncommon'),
     ('the_closer', 'The Closer', 'Your approval led to 10 merges', '🎬', 350, 'rare'),
-    ('helpful', 'Helpful', 'Get 10 "thanks" replies to your comments', '🙏', 200, 'uncommon'),
+    ('helpful', 'Helpful', 'Get thanked by the PR author on 10 review threads', '🙏', 200, 'uncommon'),
     -- PR author achievements
     ('first_pr', 'Ship It', 'Create your first PR', '🚀', 25, 'common'),
     ('pr_merged_10', 'Contributor', 'Get 10 PRs merged', '🎯', 150, 'uncommon'),
-    ('pr_merged_100', 'Prolific', 'Get 100 PRs merged', '✨', 500, 'rare')
+    ('pr_merged_100', 'Prolific', 'Get 100 PRs merged', '✨', 500, 'rare'),
+    -- Author responsiveness achievements
+    ('quick_fix', 'Quick Fix', 'Push a fix within 24 hours of a change request (10x)', '🔧', 200, 'uncommon'),
+    ('good_listener', 'Good Listener', 'Reply to 50 review threads on your PRs', '👂', 200, 'uncommon')
 ON CONFLICT (id) DO UPDATE SET
     name = EXCLUDED.name,
     description = EXCLUDED.description,
@@ -220,3 +538,113 @@ ON CONFLICT (id) DO UPDATE SET
 -- Remove deprecated achievements (must delete user_achievements first due to FK)
 DELETE FROM user_achievements WHERE achievement_id = 'night_owl';
 DELETE FROM achievements WHERE id = 'night_owl';
+
+-- Migration: achievement XP. The reward is snapshotted at unlock so editing
+-- `xp_reward` later doesn't move existing totals. Achievements unlocked before
+-- this column existed are credited once, with ledger entries, here.
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_name = 'user_achievements' AND column_name = 'xp_awarded'
+    ) THEN
+        ALTER TABLE user_achievements ADD COLUMN xp_awarded INTEGER NOT NULL DEFAULT 0;
+
+        UPDATE user_achievements ua
+        SET xp_awarded = a.xp_reward
+        FROM achievements a
+        WHERE a.id = ua.achievement_id;
+
+        INSERT INTO xp_ledger (user_id, kind, achievement_id, rule_version, achievement, total, created_at)
+        SELECT user_id, 'achievement', achievement_id, 1, xp_awarded, xp_awarded, unlocked_at
+        FROM user_achievements
+        WHERE xp_awarded <> 0;
+
+        UPDATE users u
+        SET xp = u.xp + t.xp,
+            level = FLOOR(SQRT((u.xp + t.xp) / 100.0))::int + 1,
+            updated_at = NOW()
+        FROM (
+            SELECT user_id, SUM(xp_awarded)::bigint as xp
+            FROM user_achievements
+            GROUP BY user_id
+        ) t
+        WHERE u.id = t.user_id AND t.xp <> 0;
+    END IF;
+END $$;
+
+-- Anti-gaming: suspicious patterns found by the analysis job. `fingerprint`
+-- identifies the same finding across runs, so re-running updates open flags
+-- and leaves resolved ones alone. The (PR, user) pairs whose XP is involved
+-- are stored as parallel arrays.
+CREATE TABLE IF NOT EXISTS gaming_flags (
+    id BIGSERIAL PRIMARY KEY,
+    kind TEXT NOT NULL, -- mutual_approvals, duplicate_comments, fast_approval, author_favoritism
+    fingerprint TEXT NOT NULL UNIQUE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    other_user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    details TEXT NOT NULL,
+    pair_pr_ids UUID[] NOT NULL,
+    pair_user_ids UUID[] NOT NULL,
+    status TEXT NOT NULL DEFAULT 'open', -- open, dismissed, penalized
+    detected_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_gaming_flags_status ON gaming_flags(status, id DESC);
+
+-- Gaming analysis watermark (single row): activity touched after this is
+-- analyzed by the next run after a sync
+CREATE TABLE IF NOT EXISTS gaming_state (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    last_run_at TIMESTAMPTZ
+);
+
+-- (PR, reviewer) pairs whose reviews earn no XP, one row per penalized flag
+-- involving the pair: the pair stays excluded until every such flag is dismissed
+CREATE TABLE IF NOT EXISTS xp_exclusions (
+    pr_id UUID NOT NULL REFERENCES pull_requests(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    flag_id BIGINT NOT NULL REFERENCES gaming_flags(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (pr_id, user_id, flag_id)
+);
+
+-- Migration: exclusions were keyed by pair only, owned by the first flag
+DO $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM pg_constraint
+        WHERE conname = 'xp_exclusions_pkey' AND cardinality(conkey) = 2
+    ) THEN
+        DELETE FROM xp_exclusions WHERE flag_id IS NULL;
+        ALTER TABLE xp_exclusions DROP CONSTRAINT xp_exclusions_pkey;
+        ALTER TABLE xp_exclusions ADD PRIMARY KEY (pr_id, user_id, flag_id);
+        ALTER TABLE xp_exclusions ALTER COLUMN flag_id SET NOT NULL;
+        ALTER TABLE xp_exclusions DROP CONSTRAINT IF EXISTS xp_exclusions_flag_id_fkey;
+        ALTER TABLE xp_exclusions ADD CONSTRAINT xp_exclusions_flag_id_fkey
+            FOREIGN KEY (flag_id) REFERENCES gaming_flags(id) ON DELETE CASCADE;
+
+        -- Restore the rows later flags on an already excluded pair never got
+        INSERT INTO xp_exclusions (pr_id, user_id, flag_id)
+        SELECT DISTINCT x.pr_id, x.user_id, f.id
+        FROM gaming_flags f, UNNEST(f.pair_pr_ids, f.pair_user_ids) AS x(pr_id, user_id)
+        WHERE f.status = 'penalized'
+          AND EXISTS (SELECT 1 FROM pull_requests p WHERE p.id = x.pr_id)
+          AND EXISTS (SELECT 1 FROM users u WHERE u.id = x.user_id)
+        ON CONFLICT DO NOTHING;
+    END IF;
+END $$;
+
+-- Team working calendars: review latency of members only counts working
+-- hours. Members of several teams use the first team by name that has one.
+CREATE TABLE IF NOT EXISTS team_calendars (
+    team_id UUID PRIMARY KEY REFERENCES teams(id) ON DELETE CASCADE,
+    timezone TEXT NOT NULL DEFAULT 'UTC',
+    work_days INT[] NOT NULL DEFAULT '{1,2,3,4,5}',
+    day_start_hour INT NOT NULL DEFAULT 9,
+    day_end_hour INT NOT NULL DEFAULT 17,
+    holidays DATE[] NOT NULL DEFAULT '{}',
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);